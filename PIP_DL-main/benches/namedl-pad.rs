@@ -56,6 +56,8 @@ fn nearest_power_of_two(num: usize) -> (usize, usize) {
 }
 
 
+// Measures the padded variant of PIP_DL: the instances are twice as long as in
+// `bulletproofs::pip_dl`, and the opening proves twice their length
 fn prove(criterion: &mut Criterion, log_n: usize) {
 
     let (instance_num, power) = nearest_power_of_two(log_n);
@@ -30,6 +30,7 @@ mod errors;
 mod generators;
 mod inner_product_proof;
 mod linear_proof;
+pub mod pip_dl;
 mod range_proof;
 mod transcript;
 
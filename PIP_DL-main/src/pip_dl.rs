@@ -0,0 +1,516 @@
+//! The `pip_dl` module implements PIP_DL, a commitment scheme for
+//! multilinear polynomials built on top of Pedersen vector commitments
+//! and the [`LinearProof`] argument.
+//!
+//! A polynomial with \\(2^n\\) coefficients (in the monomial basis, the
+//! \\(i\\)-th coefficient multiplying \\(\prod_{j \in \mathrm{bits}(i)} x_j\\))
+//! is split into \\(k\\) consecutive instances of length \\(m = 2^n / k\\).
+//! Each instance is Pedersen-committed, every instance commitment is hashed
+//! to a scalar with blake3, and the vector of hashes is committed once more.
+//! That last point is the (constant-size) commitment to the polynomial.
+//!
+//! To open at \\(x = (x_{lo}, x_{hi})\\), the prover reveals the instance
+//! commitments together with the outer blinding factor, so the verifier can
+//! recompute the outer commitment.  Both sides then fold the instance
+//! commitments with the weights \\(\mathrm{eq}(x_{hi}, i)\\) and a single
+//! length-\\(m\\) [`LinearProof`] shows that the folded instance evaluates
+//! to the claimed value against the public vector built from \\(x_{lo}\\).
+//!
+//! The `namedl-pad` benchmark does not call this module: it measures the
+//! padded variant, which doubles the instance length and opens with a
+//! [`LinearProof`] over twice that length.  Its timings and proof sizes are
+//! therefore an upper bound for [`open`] and [`verify`] here, which prove a
+//! single length-\\(m\\) instance.
+
+#![allow(non_snake_case)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::iter;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::inner_product_proof::inner_product;
+use crate::linear_proof::LinearProof;
+use crate::transcript::TranscriptProtocol;
+use crate::util::read32;
+
+/// Public parameters for committing to and opening polynomials with
+/// `num_vars` variables split into `instance_num` instances.
+#[derive(Clone)]
+pub struct PipDlGens {
+    /// Number of variables of the committed polynomials.
+    pub num_vars: usize,
+    /// Number of instances every polynomial is split into.
+    pub instance_num: usize,
+    /// Pedersen generators, `B` for evaluations and `B_blinding` for blindings.
+    pub pc_gens: PedersenGens,
+    /// Vector generators: share 0 commits instances, share 1 commits the
+    /// hashes of the instance commitments.
+    pub bp_gens: BulletproofGens,
+}
+
+impl PipDlGens {
+    /// Length of each instance, \\(2^n / k\\).
+    pub fn instance_len(&self) -> usize {
+        (1 << self.num_vars) / self.instance_num
+    }
+
+    fn instance_vars(&self) -> usize {
+        self.instance_len().trailing_zeros() as usize
+    }
+
+    fn instance_G(&self) -> Vec<RistrettoPoint> {
+        self.bp_gens
+            .share(0)
+            .G(self.instance_len())
+            .cloned()
+            .collect()
+    }
+
+    fn outer_G(&self) -> Vec<RistrettoPoint> {
+        self.bp_gens
+            .share(1)
+            .G(self.instance_num)
+            .cloned()
+            .collect()
+    }
+}
+
+/// The commitment to a polynomial: a Pedersen commitment to the hashes of
+/// the instance commitments.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PipDlCommitment(pub CompressedRistretto);
+
+/// Prover-side data produced by [`commit`] and consumed by [`open`].
+#[derive(Clone, Debug)]
+pub struct PipDlOpening {
+    instance_commitments: Vec<CompressedRistretto>,
+    instance_blindings: Vec<Scalar>,
+    outer_blinding: Scalar,
+}
+
+/// A proof that a committed polynomial evaluates to a given value.
+#[derive(Clone, Debug)]
+pub struct PipDlProof {
+    /// Pedersen commitments to each instance of the polynomial.
+    pub(crate) instance_commitments: Vec<CompressedRistretto>,
+    /// Blinding factor of the outer commitment.
+    pub(crate) outer_blinding: Scalar,
+    /// Evaluation argument for the folded instance.
+    pub(crate) linear_proof: LinearProof,
+}
+
+/// Creates the public parameters for `num_vars`-variable polynomials
+/// split into `instance_num` instances.
+///
+/// `instance_num` must be a power of two no larger than \\(2^n\\).
+pub fn setup(num_vars: usize, instance_num: usize) -> Result<PipDlGens, ProofError> {
+    if num_vars >= 32 {
+        return Err(ProofError::InvalidInputLength);
+    }
+    if !instance_num.is_power_of_two() || instance_num > (1 << num_vars) {
+        return Err(ProofError::InvalidAggregation);
+    }
+    let instance_len = (1 << num_vars) / instance_num;
+    Ok(PipDlGens {
+        num_vars,
+        instance_num,
+        pc_gens: PedersenGens::default(),
+        bp_gens: BulletproofGens::new(instance_len.max(instance_num), 2),
+    })
+}
+
+/// Commits to the polynomial with the given `coefficients`.
+///
+/// Returns the public commitment and the data the prover keeps for [`open`].
+pub fn commit<T: RngCore + CryptoRng>(
+    gens: &PipDlGens,
+    coefficients: &[Scalar],
+    rng: &mut T,
+) -> Result<(PipDlCommitment, PipDlOpening), ProofError> {
+    if coefficients.len() != 1 << gens.num_vars {
+        return Err(ProofError::InvalidInputLength);
+    }
+    let B = gens.pc_gens.B_blinding;
+    let G = gens.instance_G();
+
+    let instance_blindings: Vec<Scalar> = (0..gens.instance_num)
+        .map(|_| Scalar::random(rng))
+        .collect();
+    // C_i = <a_i, G> + r_i * B
+    let instance_commitments: Vec<CompressedRistretto> = coefficients
+        .chunks(gens.instance_len())
+        .zip(instance_blindings.iter())
+        .map(|(a, r)| {
+            RistrettoPoint::vartime_multiscalar_mul(
+                a.iter().chain(iter::once(r)),
+                G.iter().chain(iter::once(&B)),
+            )
+            .compress()
+        })
+        .collect();
+
+    let outer_blinding = Scalar::random(rng);
+    let commitment = outer_commitment(gens, &instance_commitments, &outer_blinding);
+
+    Ok((
+        commitment,
+        PipDlOpening {
+            instance_commitments,
+            instance_blindings,
+            outer_blinding,
+        },
+    ))
+}
+
+/// Opens the committed polynomial at `point`.
+///
+/// Returns the evaluation together with a proof of its correctness.
+pub fn open<T: RngCore + CryptoRng>(
+    gens: &PipDlGens,
+    transcript: &mut Transcript,
+    rng: &mut T,
+    coefficients: &[Scalar],
+    opening: &PipDlOpening,
+    point: &[Scalar],
+) -> Result<(Scalar, PipDlProof), ProofError> {
+    if coefficients.len() != 1 << gens.num_vars || point.len() != gens.num_vars {
+        return Err(ProofError::InvalidInputLength);
+    }
+    if opening.instance_commitments.len() != gens.instance_num
+        || opening.instance_blindings.len() != gens.instance_num
+    {
+        return Err(ProofError::WrongNumBlindingFactors);
+    }
+    let (point_lo, point_hi) = point.split_at(gens.instance_vars());
+    let b = tensor(point_lo);
+    let e = tensor(point_hi);
+
+    // Fold the instances: a = sum_i e_i * a_i, r = sum_i e_i * r_i
+    let mut a = vec![Scalar::zero(); gens.instance_len()];
+    for (a_i, e_i) in coefficients.chunks(gens.instance_len()).zip(e.iter()) {
+        for (x, y) in a.iter_mut().zip(a_i.iter()) {
+            *x += e_i * y;
+        }
+    }
+    let r = inner_product(&opening.instance_blindings, &e);
+    let value = inner_product(&a, &b);
+
+    let G = gens.instance_G();
+    let F = gens.pc_gens.B;
+    let B = gens.pc_gens.B_blinding;
+    // C = <a, G> + r * B + value * F = sum_i e_i * C_i + value * F
+    let C = RistrettoPoint::vartime_multiscalar_mul(
+        a.iter().chain(iter::once(&r)).chain(iter::once(&value)),
+        G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
+    )
+    .compress();
+
+    transcript.pip_dl_domain_sep(gens.num_vars as u64, gens.instance_num as u64);
+    for C_i in &opening.instance_commitments {
+        transcript.append_point(b"C_i", C_i);
+    }
+    transcript.append_scalar(b"v", &value);
+
+    let linear_proof = LinearProof::create(transcript, rng, &C, r, a, b, G, &F, &B)?;
+
+    Ok((
+        value,
+        PipDlProof {
+            instance_commitments: opening.instance_commitments.clone(),
+            outer_blinding: opening.outer_blinding,
+            linear_proof,
+        },
+    ))
+}
+
+/// Verifies that the polynomial committed in `commitment` evaluates to
+/// `value` at `point`.
+pub fn verify(
+    gens: &PipDlGens,
+    transcript: &mut Transcript,
+    commitment: &PipDlCommitment,
+    point: &[Scalar],
+    value: &Scalar,
+    proof: &PipDlProof,
+) -> Result<(), ProofError> {
+    if point.len() != gens.num_vars || proof.instance_commitments.len() != gens.instance_num {
+        return Err(ProofError::VerificationError);
+    }
+    if outer_commitment(gens, &proof.instance_commitments, &proof.outer_blinding) != *commitment {
+        return Err(ProofError::VerificationError);
+    }
+
+    let (point_lo, point_hi) = point.split_at(gens.instance_vars());
+    let b = tensor(point_lo);
+    let e = tensor(point_hi);
+
+    let Cs = proof
+        .instance_commitments
+        .iter()
+        .map(|p| p.decompress().ok_or(ProofError::VerificationError))
+        .collect::<Result<Vec<_>, _>>()?;
+    let F = gens.pc_gens.B;
+    let B = gens.pc_gens.B_blinding;
+    // C = sum_i e_i * C_i + value * F
+    let C = RistrettoPoint::vartime_multiscalar_mul(
+        e.iter().chain(iter::once(value)),
+        Cs.iter().chain(iter::once(&F)),
+    )
+    .compress();
+
+    transcript.pip_dl_domain_sep(gens.num_vars as u64, gens.instance_num as u64);
+    for C_i in &proof.instance_commitments {
+        transcript.append_point(b"C_i", C_i);
+    }
+    transcript.append_scalar(b"v", value);
+
+    proof
+        .linear_proof
+        .verify(transcript, &C, &gens.instance_G(), &F, &B, b)
+}
+
+impl PipDlProof {
+    /// Returns the size in bytes required to serialize the proof.
+    ///
+    /// For \\(k\\) instances of length \\(m\\) the proof size is
+    /// \\(32 \cdot (k + 1 + 2\lg m + 3)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        (self.instance_commitments.len() + 1) * 32 + self.linear_proof.serialized_size()
+    }
+
+    /// Serializes the proof into a byte array.
+    /// The layout of the proof is:
+    /// * \\(k\\) compressed Ristretto points \\(C_0, \dots, C_{k-1}\\),
+    /// * the outer blinding scalar,
+    /// * the serialized [`LinearProof`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        for C_i in &self.instance_commitments {
+            buf.extend_from_slice(C_i.as_bytes());
+        }
+        buf.extend_from_slice(self.outer_blinding.as_bytes());
+        buf.extend_from_slice(&self.linear_proof.to_bytes());
+        buf
+    }
+
+    /// Deserializes a proof over `instance_num` instances from a byte slice.
+    /// Returns an error if the slice is too short, the outer blinding is not
+    /// a canonical scalar, or the trailing bytes are not a valid [`LinearProof`].
+    pub fn from_bytes(slice: &[u8], instance_num: usize) -> Result<PipDlProof, ProofError> {
+        let header = (instance_num + 1) * 32;
+        if slice.len() < header {
+            return Err(ProofError::FormatError);
+        }
+        let instance_commitments = (0..instance_num)
+            .map(|i| CompressedRistretto(read32(&slice[i * 32..])))
+            .collect();
+        let outer_blinding = Scalar::from_canonical_bytes(read32(&slice[instance_num * 32..]))
+            .ok_or(ProofError::FormatError)?;
+        let linear_proof = LinearProof::from_bytes(&slice[header..])?;
+        Ok(PipDlProof {
+            instance_commitments,
+            outer_blinding,
+            linear_proof,
+        })
+    }
+}
+
+/// Hashes an instance commitment to a scalar with blake3.
+fn hash_to_scalar(point: &CompressedRistretto) -> Scalar {
+    Scalar::from_bytes_mod_order(*blake3::hash(point.as_bytes()).as_bytes())
+}
+
+/// Computes \\(\sum_i H(C_i) \cdot G'_i + r \cdot B\\).
+fn outer_commitment(
+    gens: &PipDlGens,
+    instance_commitments: &[CompressedRistretto],
+    blinding: &Scalar,
+) -> PipDlCommitment {
+    let hashes: Vec<Scalar> = instance_commitments.iter().map(hash_to_scalar).collect();
+    let G = gens.outer_G();
+    PipDlCommitment(
+        RistrettoPoint::vartime_multiscalar_mul(
+            hashes.iter().chain(iter::once(blinding)),
+            G.iter().chain(iter::once(&gens.pc_gens.B_blinding)),
+        )
+        .compress(),
+    )
+}
+
+/// Expands `point` into the vector \\(\prod_{j \in \mathrm{bits}(i)} x_j\\).
+fn tensor(point: &[Scalar]) -> Vec<Scalar> {
+    let mut res = Vec::with_capacity(1 << point.len());
+    res.push(Scalar::one());
+    for x in point {
+        let len = res.len();
+        for j in 0..len {
+            let v = res[j] * x;
+            res.push(v);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+
+    fn evaluate(coefficients: &[Scalar], point: &[Scalar]) -> Scalar {
+        let mut res = Scalar::zero();
+        for (i, c) in coefficients.iter().enumerate() {
+            let mut term = *c;
+            for (j, x) in point.iter().enumerate() {
+                if (i >> j) & 1 == 1 {
+                    term *= x;
+                }
+            }
+            res += term;
+        }
+        res
+    }
+
+    fn test_helper(num_vars: usize, instance_num: usize) {
+        let mut rng = ChaChaRng::from_seed([24u8; 32]);
+        let gens = setup(num_vars, instance_num).unwrap();
+        let coefficients: Vec<_> = (0..1 << num_vars)
+            .map(|_| Scalar::random(&mut rng))
+            .collect();
+        let point: Vec<_> = (0..num_vars).map(|_| Scalar::random(&mut rng)).collect();
+
+        let (commitment, opening) = commit(&gens, &coefficients, &mut rng).unwrap();
+        let mut prover_transcript = Transcript::new(b"pipdltest");
+        let (value, proof) = open(
+            &gens,
+            &mut prover_transcript,
+            &mut rng,
+            &coefficients,
+            &opening,
+            &point,
+        )
+        .unwrap();
+        assert_eq!(value, evaluate(&coefficients, &point));
+
+        let mut verifier_transcript = Transcript::new(b"pipdltest");
+        assert!(verify(
+            &gens,
+            &mut verifier_transcript,
+            &commitment,
+            &point,
+            &value,
+            &proof
+        )
+        .is_ok());
+
+        // Test serialization and deserialization
+        let serialized_proof = proof.to_bytes();
+        assert_eq!(proof.serialized_size(), serialized_proof.len());
+        let deserialized_proof = PipDlProof::from_bytes(&serialized_proof, instance_num).unwrap();
+        let mut serde_verifier_transcript = Transcript::new(b"pipdltest");
+        assert!(verify(
+            &gens,
+            &mut serde_verifier_transcript,
+            &commitment,
+            &point,
+            &value,
+            &deserialized_proof
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_pip_dl_single_instance() {
+        test_helper(4, 1);
+    }
+
+    #[test]
+    fn test_pip_dl_4_instances() {
+        test_helper(6, 4);
+    }
+
+    #[test]
+    fn test_pip_dl_all_instances() {
+        test_helper(3, 8);
+    }
+
+    #[test]
+    fn test_pip_dl_rejects_wrong_evaluation() {
+        let mut rng = ChaChaRng::from_seed([24u8; 32]);
+        let gens = setup(5, 4).unwrap();
+        let coefficients: Vec<_> = (0..32).map(|_| Scalar::random(&mut rng)).collect();
+        let point: Vec<_> = (0..5).map(|_| Scalar::random(&mut rng)).collect();
+        let (commitment, opening) = commit(&gens, &coefficients, &mut rng).unwrap();
+        let (value, proof) = open(
+            &gens,
+            &mut Transcript::new(b"pipdltest"),
+            &mut rng,
+            &coefficients,
+            &opening,
+            &point,
+        )
+        .unwrap();
+
+        let wrong_value = value + Scalar::one();
+        assert_eq!(
+            verify(
+                &gens,
+                &mut Transcript::new(b"pipdltest"),
+                &commitment,
+                &point,
+                &wrong_value,
+                &proof
+            ),
+            Err(ProofError::VerificationError)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.instance_commitments.swap(0, 1);
+        assert_eq!(
+            verify(
+                &gens,
+                &mut Transcript::new(b"pipdltest"),
+                &commitment,
+                &point,
+                &value,
+                &tampered
+            ),
+            Err(ProofError::VerificationError)
+        );
+
+        let (other_commitment, _) = commit(&gens, &coefficients, &mut rng).unwrap();
+        assert_eq!(
+            verify(
+                &gens,
+                &mut Transcript::new(b"pipdltest"),
+                &other_commitment,
+                &point,
+                &value,
+                &proof
+            ),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn test_pip_dl_setup_rejects_bad_sizes() {
+        assert!(setup(4, 3).is_err());
+        assert!(setup(2, 8).is_err());
+        let gens = setup(4, 2).unwrap();
+        let mut rng = ChaChaRng::from_seed([24u8; 32]);
+        assert_eq!(
+            commit(&gens, &[Scalar::one(); 8], &mut rng).unwrap_err(),
+            ProofError::InvalidInputLength
+        );
+    }
+}
@@ -13,6 +13,10 @@ pub trait TranscriptProtocol {
     /// Append a domain separator for a length-`n` inner product proof.
     fn innerproduct_domain_sep(&mut self, n: u64);
 
+    /// Append a domain separator for a PIP_DL opening of an `n`-variable
+    /// polynomial split into `k` instances.
+    fn pip_dl_domain_sep(&mut self, n: u64, k: u64);
+
     /// Append a domain separator for a constraint system.
     fn r1cs_domain_sep(&mut self);
 
@@ -52,6 +56,12 @@ impl TranscriptProtocol for Transcript {
         self.append_u64(b"n", n);
     }
 
+    fn pip_dl_domain_sep(&mut self, n: u64, k: u64) {
+        self.append_message(b"dom-sep", b"pip-dl v1");
+        self.append_u64(b"n", n);
+        self.append_u64(b"k", k);
+    }
+
     fn r1cs_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs v1");
     }
@@ -1,28 +1,23 @@
 extern crate criterion;
 use criterion::*;
 
-use basefold::{prover::Prover, verifier::Verifier};
-use util::{
-    algebra::{
-        coset::Coset,
-        field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
-        polynomial::MultilinearPolynomial,
-    },
-    random_oracle::RandomOracle,
+use std::sync::Arc;
+
+use basefold::{
+    prover::{self, BasefoldCommitment, Prover},
+    verifier::Verifier,
+};
+use util::algebra::{
+    field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
+    polynomial::MultilinearPolynomial,
 };
+use util::foldable_code::{FoldableCode, ReedSolomonCode};
 
-use util::{CODE_RATE, SECURITY_BITS};
+use util::parameters::FriParameters;
 fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
-    let mut interpolate_cosets = vec![Coset::new(1 << (variable_num + CODE_RATE), T::from_int(1))];
-    for i in 1..variable_num {
-        interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
-    }
-    let oracle = RandomOracle::new(
-        variable_num,
-        (SECURITY_BITS as f32 / (2.0 / (1.0 + 0.5_f32.powi(CODE_RATE as i32))).log2()).ceil()
-            as usize,
-    );
+    let code = ReedSolomonCode::<T>::setup(variable_num, parameters.log_blowup);
 
     criterion.bench_function(
         &format!("basefold {} commit {}", T::FIELD_NAME, variable_num),
@@ -30,7 +25,7 @@ fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize) {
             b.iter_batched(
                 || polynomial.clone(),
                 |p| {
-                    let prover = Prover::new(variable_num, &interpolate_cosets, p, &oracle);
+                    let prover = Prover::new(variable_num, &code, p, parameters);
                     let _commit = prover.commit_polynomial();
                 },
                 BatchSize::SmallInput,
@@ -47,34 +42,22 @@ fn bench_commit(c: &mut Criterion) {
 }
 
 fn open<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
-    let mut interpolate_cosets = vec![Coset::new(1 << (variable_num + CODE_RATE), T::from_int(1))];
-    for i in 1..variable_num {
-        interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
-    }
-    let oracle = RandomOracle::new(
+    let code = ReedSolomonCode::<T>::setup(variable_num, parameters.log_blowup);
+    // Committed once, opened in every iteration
+    let commitment = Arc::new(BasefoldCommitment::<T>::new(
         variable_num,
-        (SECURITY_BITS as f32 / (2.0 / (1.0 + 0.5_f32.powi(CODE_RATE as i32))).log2()).ceil()
-            as usize,
-    );
-    let prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
-    let commit = prover.commit_polynomial();
-    let verifier = Verifier::new(variable_num, &interpolate_cosets, commit, &oracle);
-    let point = verifier.get_open_point();
+        &code,
+        polynomial,
+        parameters,
+    ));
+    let point = (0..variable_num).map(|_| T::random_element()).collect();
 
     criterion.bench_function(
         &format!("basefold {} open {}", T::FIELD_NAME, variable_num),
         move |b| {
-            b.iter_batched(
-                || (prover.clone(), verifier.clone()),
-                |(mut p, mut v)| {
-                    p.send_evaluation(&mut v, &point);
-                    p.prove(&point);
-                    p.commit_foldings(&mut v);
-                    let _ = p.query();
-                },
-                BatchSize::SmallInput,
-            )
+            b.iter(|| prover::open(&commitment, &point, &mut commitment.transcript()).unwrap());
         },
     );
 }
@@ -87,30 +70,23 @@ fn bench_open(c: &mut Criterion) {
 }
 
 fn verify<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
-    let mut interpolate_cosets = vec![Coset::new(1 << (variable_num + CODE_RATE), T::from_int(1))];
-    for i in 1..variable_num {
-        interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
-    }
-    let oracle = RandomOracle::new(
-        variable_num,
-        (SECURITY_BITS as f32 / (2.0 / (1.0 + 0.5_f32.powi(CODE_RATE as i32))).log2()).ceil()
-            as usize,
-    );
-    let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let code = ReedSolomonCode::<T>::setup(variable_num, parameters.log_blowup);
+    let mut prover = Prover::new(variable_num, &code, polynomial, parameters);
     let commit = prover.commit_polynomial();
-    let mut verifier = Verifier::new(variable_num, &interpolate_cosets, commit, &oracle);
-    let point = verifier.get_open_point();
-    prover.send_evaluation(&mut verifier, &point);
-    prover.prove(&point);
+    let point = (0..variable_num).map(|_| T::random_element()).collect();
+    let mut verifier = Verifier::new(variable_num, &code, commit, &point, parameters);
+    prover.send_evaluation(&mut verifier, &point).unwrap();
+    prover.prove(&point).unwrap();
     prover.commit_foldings(&mut verifier);
-    let proof = prover.query();
+    let (polynomial_proof, folding_proof) = prover.query().unwrap();
 
     criterion.bench_function(
         &format!("basefold {} verify {}", T::FIELD_NAME, variable_num),
         move |b| {
             b.iter(|| {
-                assert!(verifier.verify(&proof));
+                assert!(verifier.verify(&polynomial_proof, &folding_proof).is_ok());
             })
         },
     );
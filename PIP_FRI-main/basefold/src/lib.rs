@@ -1,46 +1,66 @@
+pub mod proof;
 pub mod prover;
+pub mod scheme;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
     use std::mem::size_of;
+    use std::sync::Arc;
+    use std::{fs, io};
 
-    use crate::{prover::Prover, verifier::Verifier};
+    use crate::{
+        proof::BasefoldParams,
+        prover::{self, BasefoldCommitment, Prover},
+        scheme::Basefold,
+        verifier::{self, Verifier},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
     use util::{
         algebra::{
-            coset::Coset,
-            field::{mersenne61_ext::Mersenne61Ext, Field},
+            field::{
+                fp64::Fp64,
+                fp64_ext::{Fp64Ext2, Fp64Ext3},
+                ft255::Ft255,
+                mersenne61_ext::Mersenne61Ext,
+                ExtensionOf, Field,
+            },
             polynomial::MultilinearPolynomial,
         },
-        merkle_tree::MERKLE_ROOT_SIZE,
-        random_oracle::RandomOracle,
+        error::VerifyError,
+        foldable_code::{FoldableCode, RandomFoldableCode, ReedSolomonCode},
+        merkle_tree::{
+            Blake3Algorithm, Keccak256Algorithm, MerkleHasher, Sha256Algorithm, MERKLE_ROOT_SIZE,
+        },
+        parameters::FriParameters,
+        pcs::PolynomialCommitmentScheme,
+        poseidon::PoseidonAlgorithm,
+        stream::FileSource,
     };
-    use util::{CODE_RATE, SECURITY_BITS};
 
     fn output_proof_size(variable_num: usize) -> usize {
+        let parameters = FriParameters::default();
         let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
-        let mut interpolate_cosets = vec![Coset::new(
-            1 << (variable_num + CODE_RATE),
-            Mersenne61Ext::from_int(1),
-        )];
-        for i in 1..variable_num {
-            interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
-        }
-        let oracle = RandomOracle::new(
+        let code = ReedSolomonCode::<Mersenne61Ext>::setup(variable_num, parameters.log_blowup);
+        let mut prover = Prover::new(variable_num, &code, polynomial, parameters);
+        let commit = prover.commit_polynomial();
+        let point = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let mut verifier = Verifier::new(
             variable_num,
-            (SECURITY_BITS as f32 / (2.0 / (1.0 + 0.5_f32.powi(CODE_RATE as i32))).log2()).ceil()
-                as usize,
+            &code,
+            commit,
+            &point,
+            parameters,
         );
-        let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
-        let commit = prover.commit_polynomial();
-        let mut verifier = Verifier::new(variable_num, &interpolate_cosets, commit, &oracle);
-        let point = verifier.get_open_point();
-        prover.send_evaluation(&mut verifier, &point);
-        prover.prove(&point);
+        prover.send_evaluation(&mut verifier, &point).unwrap();
+        prover.prove(&point).unwrap();
         prover.commit_foldings(&mut verifier);
-        let proof = prover.query();
-        assert!(verifier.verify(&proof));
-        proof.iter().map(|x| x.proof_size()).sum::<usize>()
+        let (polynomial_proof, folding_proof) = prover.query().unwrap();
+        assert!(verifier.verify(&polynomial_proof, &folding_proof).is_ok());
+        polynomial_proof.proof_size()
+            + folding_proof.iter().map(|x| x.proof_size()).sum::<usize>()
             + variable_num * (MERKLE_ROOT_SIZE + size_of::<Mersenne61Ext>() * 3)
     }
 
@@ -54,4 +74,371 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_reject_wrong_evaluation() {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let code = ReedSolomonCode::<Mersenne61Ext>::setup(variable_num, parameters.log_blowup);
+        let mut prover = Prover::new(variable_num, &code, polynomial, parameters);
+        let commit = prover.commit_polynomial();
+        let point = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let mut verifier = Verifier::new(
+            variable_num,
+            &code,
+            commit,
+            &point,
+            parameters,
+        );
+        prover.send_evaluation(&mut verifier, &point).unwrap();
+        prover.prove(&point).unwrap();
+        prover.commit_foldings(&mut verifier);
+        let (polynomial_proof, folding_proof) = prover.query().unwrap();
+        assert!(verifier.verify(&polynomial_proof, &folding_proof).is_ok());
+
+        verifier.set_evalutation(Mersenne61Ext::random_element());
+        assert!(verifier.verify(&polynomial_proof, &folding_proof).is_err());
+
+        // Dropping a layer of the proof is reported rather than panicking
+        assert_eq!(
+            verifier.verify(&polynomial_proof, &folding_proof[1..].to_vec()),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    fn commitment_scheme<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let params: BasefoldParams<Mersenne61Ext, H> =
+            Basefold::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let (commitment, prover_data) = Basefold::commit(&params, polynomial.clone());
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Basefold::open(&params, prover_data, &point).unwrap();
+        assert_eq!(value, polynomial.evaluate(&point));
+        assert!(Basefold::verify(&params, &commitment, &point, &value, &proof).is_ok());
+        // A caller that expects another value rejects the proof
+        let wrong_value = value + Mersenne61Ext::from_int(1);
+        assert_eq!(
+            Basefold::verify(&params, &commitment, &point, &wrong_value, &proof),
+            Err(VerifyError::ClaimedValue)
+        );
+    }
+
+    #[test]
+    fn test_commitment_scheme() {
+        commitment_scheme(FriParameters::default());
+    }
+
+    #[test]
+    fn test_folding_arity() {
+        for log_arity in 2..5 {
+            let parameters = FriParameters::default().with_folding_arity(log_arity);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_hiding());
+        }
+    }
+
+    #[test]
+    fn test_final_polynomial() {
+        for log_final_degree in [1, 3, 8] {
+            let parameters = FriParameters::default().with_final_degree(log_final_degree);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_folding_arity(2).with_hiding());
+        }
+    }
+
+    #[test]
+    fn test_merkle_hashers() {
+        let parameters = FriParameters::default();
+        commitment_scheme(parameters.with_hasher::<Sha256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<Keccak256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<PoseidonAlgorithm<Fp64>>());
+    }
+
+    #[test]
+    fn test_hiding_opening() {
+        let parameters = FriParameters::default().with_hiding();
+        let variable_num = 8;
+        let params: BasefoldParams<Mersenne61Ext> =
+            Basefold::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let evaluation = polynomial.evaluate(&point);
+        let (commitment, prover_data) = Basefold::commit(&params, polynomial);
+        let (value, proof) = Basefold::open(&params, prover_data, &point).unwrap();
+        assert_eq!(proof.evaluation, evaluation);
+        assert!(!proof.polynomial_proof.salts.is_empty());
+        assert!(Basefold::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.mask.as_mut().unwrap().evaluation += Mersenne61Ext::from_int(1);
+        assert!(Basefold::verify(&params, &commitment, &point, &value, &tampered).is_err());
+        tampered.mask = None;
+        assert_eq!(
+            Basefold::verify(&params, &commitment, &point, &value, &tampered),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    // Hiding proofs drawn from the same seed are the same
+    #[test]
+    fn test_seeded_hiding_opening() {
+        let parameters = FriParameters::default().with_hiding();
+        let variable_num = 8;
+        let params: BasefoldParams<Mersenne61Ext> =
+            Basefold::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let open = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let commitment = Arc::new(BasefoldCommitment::new_with(
+                variable_num,
+                &params.code,
+                polynomial.clone(),
+                parameters,
+                &mut rng,
+            ));
+            let proof =
+                prover::open_with(&commitment, &point, &mut commitment.transcript(), &mut rng)
+                    .unwrap();
+            (commitment.root(), proof)
+        };
+        let (root, proof) = open(7);
+        let (same_root, same_proof) = open(7);
+        assert_eq!(root, same_root);
+        assert_eq!(proof.folding_roots, same_proof.folding_roots);
+        assert_eq!(
+            proof.mask.as_ref().unwrap().root,
+            same_proof.mask.unwrap().root
+        );
+        assert_eq!(
+            proof.polynomial_proof.salts,
+            same_proof.polynomial_proof.salts
+        );
+        assert_ne!(root, open(8).0);
+        let value = polynomial.evaluate(&point);
+        assert!(Basefold::verify(&params, &root, &point, &value, &proof).is_ok());
+    }
+
+    // One commitment answers any number of openings, one after the other or at once
+    #[test]
+    fn test_shared_commitment() {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let params: BasefoldParams<Mersenne61Ext> =
+            Basefold::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let (root, commitment) = Basefold::commit(&params, polynomial.clone());
+        let points = (0..3)
+            .map(|_| {
+                (0..variable_num)
+                    .map(|_| Mersenne61Ext::random_element())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let proofs = std::thread::scope(|s| {
+            let handles = points
+                .iter()
+                .map(|point| {
+                    let (params, commitment) = (&params, commitment.clone());
+                    s.spawn(move || Basefold::open(params, commitment, point))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|x| x.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+        for (point, (value, proof)) in points.iter().zip(&proofs) {
+            assert_eq!(*value, polynomial.evaluate(point));
+            assert!(Basefold::verify(&params, &root, point, value, proof).is_ok());
+        }
+
+        // An opening inside a larger protocol continues its transcript, and so does the
+        // verifier
+        let mut transcript = commitment.transcript();
+        transcript.append_message(b"earlier messages");
+        let mut verifier_transcript = transcript.clone();
+        let proof = prover::open(&commitment, &points[0], &mut transcript).unwrap();
+        assert!(
+            verifier::verify(&params, &root, &points[0], &proof, &mut verifier_transcript).is_ok()
+        );
+        assert_eq!(
+            transcript.challenge_bytes(),
+            verifier_transcript.challenge_bytes()
+        );
+        assert!(Basefold::verify(&params, &root, &points[0], &proof.evaluation, &proof).is_err());
+    }
+
+    // Committed polynomials live in the base field, challenges in the extension
+    fn open_base_polynomial<T: ExtensionOf<Fp64>>(variable_num: usize) {
+        let parameters = FriParameters::default();
+        let polynomial = MultilinearPolynomial::<Fp64>::random_polynomial(variable_num);
+        let params: BasefoldParams<T> =
+            Basefold::<_, Fp64>::setup(variable_num, parameters).unwrap();
+        let (commitment, prover_data) = Basefold::commit(&params, polynomial);
+        let point: Vec<T> = (0..variable_num).map(|_| T::random_element()).collect();
+        let (value, proof) = Basefold::open(&params, prover_data, &point).unwrap();
+        assert!(Basefold::verify(&params, &commitment, &point, &value, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_goldilocks_extension() {
+        open_base_polynomial::<Fp64Ext2>(8);
+        open_base_polynomial::<Fp64Ext3>(8);
+    }
+
+    fn commit_base_polynomial<T: ExtensionOf<Fp64>, C: FoldableCode<T>>(code: C) {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let polynomial = MultilinearPolynomial::<Fp64>::random_polynomial(variable_num);
+        let params = BasefoldParams::with_code(variable_num, code, parameters);
+        let mut prover = Prover::<T, Fp64, _, C>::new(
+            variable_num,
+            &params.code,
+            polynomial,
+            parameters,
+        );
+        let commit = prover.commit_polynomial();
+        let point = (0..variable_num).map(|_| T::random_element()).collect();
+        let mut verifier = Verifier::new(
+            variable_num,
+            &params.code,
+            commit,
+            &point,
+            parameters,
+        );
+        prover.send_evaluation(&mut verifier, &point).unwrap();
+        prover.prove(&point).unwrap();
+        prover.commit_foldings(&mut verifier);
+        let (polynomial_proof, folding_proof) = prover.query().unwrap();
+        assert!(verifier.verify(&polynomial_proof, &folding_proof).is_ok());
+    }
+
+    #[test]
+    fn test_base_field_commitment() {
+        let log_blowup = FriParameters::default().log_blowup;
+        commit_base_polynomial(ReedSolomonCode::<Fp64Ext2>::setup(8, log_blowup));
+        commit_base_polynomial(ReedSolomonCode::<Fp64Ext3>::setup(8, log_blowup));
+        commit_base_polynomial(RandomFoldableCode::<Fp64Ext2>::over_subfield::<Fp64>(
+            8,
+            log_blowup,
+            b"basefold",
+        ));
+    }
+
+    // Random foldable codes need no FFT-friendly field
+    #[test]
+    fn test_random_foldable_code() {
+        let parameters = FriParameters::default();
+        for parameters in [
+            parameters,
+            parameters.with_hiding(),
+            parameters.with_folding_arity(2).with_final_degree(3),
+        ] {
+            let variable_num = 8;
+            let params: BasefoldParams<Ft255, Blake3Algorithm, RandomFoldableCode<Ft255>> =
+                Basefold::setup(variable_num, parameters).unwrap();
+            let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+            let point: Vec<Ft255> = (0..variable_num).map(|_| Ft255::random_element()).collect();
+            let evaluation = polynomial.evaluate(&point);
+            let (commitment, prover_data) = Basefold::commit(&params, polynomial);
+            let (value, mut proof) = Basefold::open(&params, prover_data, &point).unwrap();
+            assert_eq!(proof.evaluation, evaluation);
+            assert!(Basefold::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+            proof.evaluation += Ft255::from_int(1);
+            assert!(Basefold::verify(&params, &commitment, &point, &value, &proof).is_err());
+        }
+    }
+
+    // A commitment whose codeword is spilled to disk opens like one held in memory
+    fn streaming_commitment<T: ExtensionOf<B>, B: Field>(parameters: FriParameters) {
+        let variable_num = 8;
+        // The shift of the code has to lie in `B`, which the unit shift of `new` does
+        let params: BasefoldParams<T> = BasefoldParams::new(variable_num, parameters);
+        let polynomial = MultilinearPolynomial::<B>::random_polynomial(variable_num);
+        let path = std::env::temp_dir().join(format!("basefold-{}", std::process::id()));
+        let mut source = FileSource::from_iter(&path, polynomial.coefficients().clone()).unwrap();
+        let commitment = Arc::new(
+            BasefoldCommitment::new_spilled(
+                variable_num,
+                &params.code,
+                &mut source,
+                1 << 4,
+                2,
+                &std::env::temp_dir(),
+                parameters,
+            )
+            .unwrap(),
+        );
+        fs::remove_file(path).unwrap();
+        let in_memory = BasefoldCommitment::<T, B>::new(
+            variable_num,
+            &params.code,
+            polynomial.clone(),
+            parameters,
+        );
+        assert_eq!(commitment.root(), in_memory.root());
+
+        let point: Vec<T> = (0..variable_num).map(|_| T::random_element()).collect();
+        let proof = prover::open(&commitment, &point, &mut commitment.transcript()).unwrap();
+        assert_eq!(
+            proof.evaluation,
+            MultilinearPolynomial::new(
+                polynomial
+                    .coefficients()
+                    .iter()
+                    .map(|x| T::from(*x))
+                    .collect()
+            )
+            .evaluate(&point)
+        );
+        let mut transcript = verifier::commitment_transcript(&commitment.root());
+        assert!(
+            verifier::verify(&params, &commitment.root(), &point, &proof, &mut transcript).is_ok()
+        );
+
+        // Its sumcheck tables and foldings are spilled too, which changes nothing in the proof
+        let in_memory = Arc::new(in_memory);
+        let expected = prover::open(&in_memory, &point, &mut in_memory.transcript()).unwrap();
+        assert_eq!(proof.sumcheck_values, expected.sumcheck_values);
+        assert_eq!(proof.folding_roots, expected.folding_roots);
+        assert_eq!(proof.final_polynomial, expected.final_polynomial);
+        assert_eq!(proof.pow_nonce, expected.pow_nonce);
+        assert_eq!(
+            proof.polynomial_proof.proof_bytes,
+            expected.polynomial_proof.proof_bytes
+        );
+        for (x, y) in proof.folding_proof.iter().zip(expected.folding_proof.iter()) {
+            assert_eq!(x.values, y.values);
+            assert_eq!(x.proof_bytes, y.proof_bytes);
+        }
+    }
+
+    #[test]
+    fn test_streaming_commitment() {
+        streaming_commitment::<Mersenne61Ext, Mersenne61Ext>(FriParameters::default());
+        streaming_commitment::<Fp64Ext2, Fp64>(FriParameters::default().with_folding_arity(3));
+        streaming_commitment::<Fp64Ext3, Fp64>(FriParameters::default().with_final_degree(2));
+        let error = BasefoldCommitment::<Mersenne61Ext>::new_spilled(
+            8,
+            &ReedSolomonCode::setup(9, FriParameters::default().log_blowup),
+            &mut vec![Mersenne61Ext::from_int(0); 1 << 8].as_slice(),
+            1 << 4,
+            2,
+            &std::env::temp_dir(),
+            FriParameters::default().with_hiding(),
+        );
+        assert_eq!(error.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+    }
 }
@@ -0,0 +1,77 @@
+use std::marker::PhantomData;
+
+use rand::RngCore;
+use util::{
+    algebra::field::{ExtensionOf, Field},
+    foldable_code::{FoldableCode, ReedSolomonCode},
+    mask::MaskOpening,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    query_result::QueryResult,
+};
+
+// The polynomials are encoded with the foldable code `C`, Reed-Solomon by default
+#[derive(Clone)]
+pub struct BasefoldParams<
+    T: Field,
+    H: MerkleHasher = Blake3Algorithm,
+    C: FoldableCode<T> = ReedSolomonCode<T>,
+> {
+    pub variable_num: usize,
+    pub code: C,
+    pub parameters: FriParameters<H>,
+    field: PhantomData<T>,
+}
+
+impl<T: Field, H: MerkleHasher, C: FoldableCode<T>> BasefoldParams<T, H, C> {
+    pub fn new(variable_num: usize, parameters: FriParameters<H>) -> Self {
+        // A hiding commitment is to a polynomial in one more variable
+        let total_round = variable_num + parameters.hiding() as usize;
+        Self::with_code(
+            variable_num,
+            C::setup(total_round, parameters.log_blowup),
+            parameters,
+        )
+    }
+
+    // As `new`, with the coset shift or seed of the code drawn from `rng`. The code also
+    // encodes polynomials over the subfield `B`.
+    pub fn new_with<B: Field, R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Self
+    where
+        T: ExtensionOf<B>,
+    {
+        let total_round = variable_num + parameters.hiding() as usize;
+        Self::with_code(
+            variable_num,
+            C::setup_with::<B, R>(total_round, parameters.log_blowup, rng),
+            parameters,
+        )
+    }
+
+    // A code set up for `variable_num` variables, and one more if the parameters hide
+    pub fn with_code(variable_num: usize, code: C, parameters: FriParameters<H>) -> Self {
+        BasefoldParams {
+            variable_num,
+            code,
+            parameters,
+            field: PhantomData,
+        }
+    }
+}
+
+// Everything the prover sends in a basefold opening, in transcript order
+#[derive(Clone)]
+pub struct BasefoldProof<T: Field, B: Field = T> {
+    pub evaluation: T,
+    pub sumcheck_values: Vec<(T, T, T)>,
+    pub folding_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub final_polynomial: Vec<T>,
+    pub pow_nonce: u64,
+    pub polynomial_proof: QueryResult<B>,
+    pub folding_proof: Vec<QueryResult<T>>,
+    pub mask: Option<MaskOpening<T>>,
+}
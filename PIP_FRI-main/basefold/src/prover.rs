@@ -1,135 +1,810 @@
+use std::borrow::Cow;
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
 use util::{
     algebra::{
-        coset::Coset,
-        field::Field,
-        polynomial::{EqMultilinear, MultilinearPolynomial},
+        field::{ExtensionOf, Field},
+        polynomial::{
+            hypercube_transform, inverse_hypercube_transform, EqMultilinear, MultilinearPolynomial,
+        },
     },
+    foldable_code::{FoldableCode, ReedSolomonCode},
     interpolation::InterpolateValue,
-    merkle_tree::MERKLE_ROOT_SIZE,
+    mask::{hiding_point, MaskOpening},
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
     query_result::QueryResult,
-    random_oracle::RandomOracle,
+    stream::{CoefficientSource, SpilledCodeword, SpilledTable},
+    transcript::Transcript,
 };
 
-use crate::verifier::Verifier;
+use crate::proof::BasefoldProof;
+use crate::verifier::{self, Verifier};
 
-#[derive(Clone)]
-pub struct Prover<T: Field> {
+// The polynomial is committed over the base field `B`; challenges and foldings are in `T`.
+// Its codeword is in the foldable code `C`, whose diagonals have to lie in `B`. Nothing in
+// a commitment changes when it is opened, so it can be shared and opened any number of times.
+pub struct BasefoldCommitment<
+    T: Field,
+    B: Field = T,
+    H: MerkleHasher = Blake3Algorithm,
+    C: FoldableCode<T> = ReedSolomonCode<T>,
+> {
     total_round: usize,
-    interpolate_cosets: Vec<Coset<T>>,
-    polynomial: MultilinearPolynomial<T>,
-    interpolations: Vec<InterpolateValue<T>>,
-    hypercube_interpolation: Vec<T>,
+    code: C,
+    parameters: FriParameters<H>,
+    round_starts: Vec<usize>,
+    polynomial_interpolation: Codeword<B, H>,
+    // The evaluations over the hypercube, with variable j in bit j of an index
+    hypercube_interpolation: Table<B>,
+    // Where the openings of a spilled commitment spill their tables and foldings, and how
+    // many values they hold in memory at a time
+    spill_dir: Option<PathBuf>,
+    chunk_size: usize,
+    field: PhantomData<T>,
+}
+
+// The committed codeword or a folding of it, in memory or spilled to disk
+enum Codeword<B: Field, H: MerkleHasher> {
+    InMemory(InterpolateValue<B, H>),
+    Spilled(SpilledCodeword<B, H>),
+}
+
+impl<B: Field, H: MerkleHasher> Codeword<B, H> {
+    fn commit(&self) -> [u8; MERKLE_ROOT_SIZE] {
+        match self {
+            Codeword::InMemory(x) => x.commit(),
+            Codeword::Spilled(x) => x.commit(),
+        }
+    }
+
+    fn leave_num(&self) -> usize {
+        match self {
+            Codeword::InMemory(x) => x.leave_num(),
+            Codeword::Spilled(x) => x.leave_num(),
+        }
+    }
+
+    fn query(&self, leaf_indices: &[usize]) -> io::Result<QueryResult<B>> {
+        match self {
+            Codeword::InMemory(x) => Ok(x.query(leaf_indices)),
+            Codeword::Spilled(x) => x.query(leaf_indices),
+        }
+    }
+
+    // Calls `f(t, values)` with the values of every leaf t, the ones at t + j * leave_num
+    fn for_each_leaf(&self, mut f: impl FnMut(usize, &[B]) -> io::Result<()>) -> io::Result<()> {
+        match self {
+            Codeword::InMemory(x) => {
+                let leave_num = x.leave_num();
+                let arity = x.value.len() / leave_num;
+                for t in 0..leave_num {
+                    let leaf = (0..arity)
+                        .map(|j| x.value[t + j * leave_num])
+                        .collect::<Vec<_>>();
+                    f(t, &leaf)?;
+                }
+                Ok(())
+            }
+            Codeword::Spilled(x) => x.for_each_leaf(f),
+        }
+    }
+}
+
+// A table of evaluations over the hypercube, in memory or spilled to disk
+enum Table<T: Field> {
+    InMemory(Vec<T>),
+    Spilled(SpilledTable<T>),
+}
+
+impl<T: Field> Table<T> {
+    fn len(&self) -> usize {
+        match self {
+            Table::InMemory(x) => x.len(),
+            Table::Spilled(x) => x.len(),
+        }
+    }
+
+    // The `len` entries from `start` on
+    fn chunk(&self, start: usize, len: usize) -> io::Result<Cow<'_, [T]>> {
+        match self {
+            Table::InMemory(x) => Ok(Cow::Borrowed(&x[start..start + len])),
+            Table::Spilled(x) => {
+                let mut buf = vec![T::from_int(0); len];
+                x.read(start, &mut buf)?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+}
+
+// The evaluations over the hypercube of the multilinear polynomial whose coefficients `source`
+// holds, spilled to `dir`. Chunks of `chunk_size` are transformed on their own in the
+// variables of the low bits, then every variable of the high bits takes one more pass.
+fn spilled_hypercube<B: Field, S: CoefficientSource<B>>(
+    source: &mut S,
+    chunk_size: usize,
+    dir: &Path,
+) -> io::Result<SpilledTable<B>> {
+    let n = source.len();
+    let chunk_size = chunk_size.min(n);
+    let table = SpilledTable::from_chunks(dir, n, chunk_size, |start, buf| {
+        source.read_chunk(start, buf)?;
+        hypercube_transform(buf);
+        Ok(())
+    })?;
+    let mut low = vec![B::from_int(0); chunk_size];
+    let mut high = vec![B::from_int(0); chunk_size];
+    let mut m = chunk_size;
+    while m < n {
+        for start in (0..n).step_by(chunk_size).filter(|x| x & m == 0) {
+            table.read(start, &mut low)?;
+            table.read(start + m, &mut high)?;
+            for (h, l) in high.iter_mut().zip(low.iter()) {
+                *h += *l;
+            }
+            table.write(start + m, &high)?;
+        }
+        m *= 2;
+    }
+    Ok(table)
+}
+
+// eq(point, b) over the hypercube of the variables the sumcheck has left, with the first of
+// them in the lowest bit, times what the variables it bound contributed. Chunks are computed
+// when they are read, so the table is never in memory as a whole.
+struct EqTable<T: Field> {
+    point: Vec<T>,
+    scale: T,
+}
+
+impl<T: Field> EqTable<T> {
+    fn new(point: Vec<T>) -> Self {
+        EqTable {
+            point,
+            scale: T::from_int(1),
+        }
+    }
+
+    // The `len` entries from `start` on, for a power of two `len` that divides `start`
+    fn chunk(&self, start: usize, len: usize) -> Vec<T> {
+        let low = len.ilog2() as usize;
+        let high = self.point[low..]
+            .iter()
+            .enumerate()
+            .fold(self.scale, |acc, (j, z)| {
+                if (start >> (low + j)) & 1 == 1 {
+                    acc * *z
+                } else {
+                    acc * (T::from_int(1) - *z)
+                }
+            });
+        // `EqMultilinear` puts its first variable in the top bit
+        EqMultilinear::new(self.point[..low].iter().rev().cloned().collect())
+            .evaluate_hypercube()
+            .into_iter()
+            .map(|e| e * high)
+            .collect()
+    }
+
+    // Binds the first variable left to `challenge`
+    fn fold(&mut self, challenge: T) {
+        let z = self.point.remove(0);
+        self.scale *= z * challenge + (T::from_int(1) - z) * (T::from_int(1) - challenge);
+    }
+}
+
+// Writes the folded `(index, value)` pairs in `pending` to `table`, a run of consecutive
+// indices at a time
+fn write_runs<T: Field>(table: &SpilledTable<T>, pending: &mut Vec<(usize, T)>) -> io::Result<()> {
+    pending.sort_unstable_by_key(|x| x.0);
+    for run in pending.chunk_by(|a, b| b.0 == a.0 + 1) {
+        table.write(run[0].0, &run.iter().map(|x| x.1).collect::<Vec<_>>())?;
+    }
+    pending.clear();
+    Ok(())
+}
+
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>>
+    BasefoldCommitment<T, B, H, C>
+{
+    pub fn new(
+        total_round: usize,
+        code: &C,
+        polynomial: MultilinearPolynomial<B>,
+        parameters: FriParameters<H>,
+    ) -> Self {
+        Self::new_with(
+            total_round,
+            code,
+            polynomial,
+            parameters,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    // The extension and the salts of a hiding commitment are drawn from `rng`
+    pub fn new_with<R: RngCore>(
+        total_round: usize,
+        code: &C,
+        polynomial: MultilinearPolynomial<B>,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Self {
+        // A hiding commitment is to a random extension in one more variable
+        let total_round = total_round + parameters.hiding() as usize;
+        let polynomial = if parameters.hiding() {
+            polynomial.random_extension_with(rng)
+        } else {
+            polynomial
+        };
+        let round_starts = parameters.round_starts(total_round);
+        let interpolation = InterpolateValue::with_arity(
+            code.encode(polynomial.coefficients().clone()),
+            round_starts[1],
+            parameters.hiding().then_some(rng as &mut dyn RngCore),
+        );
+        let mut hypercube_interpolation = polynomial.coefficients().clone();
+        hypercube_transform(&mut hypercube_interpolation);
+        BasefoldCommitment {
+            total_round,
+            code: code.clone(),
+            parameters,
+            round_starts,
+            polynomial_interpolation: Codeword::InMemory(interpolation),
+            hypercube_interpolation: Table::InMemory(hypercube_interpolation),
+            spill_dir: None,
+            chunk_size: usize::MAX,
+            field: PhantomData,
+        }
+    }
+
+    pub fn root(&self) -> [u8; MERKLE_ROOT_SIZE] {
+        self.polynomial_interpolation.commit()
+    }
+
+    // The transcript of an opening on its own, which starts from the root
+    pub fn transcript(&self) -> Transcript {
+        verifier::commitment_transcript(&self.root())
+    }
+}
+
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher> BasefoldCommitment<T, B, H, ReedSolomonCode<T>> {
+    // Commits to the polynomial whose coefficients `source` holds without its codeword ever
+    // being in memory. `SpilledCodeword::new` encodes and hashes it with the given
+    // `chunk_size` and `batch`. Its evaluations over the hypercube are spilled to `dir` as
+    // well, and so are the sumcheck tables and the foldings of every opening, which hold
+    // `chunk_size` values in memory at a time.
+    pub fn new_spilled<S: CoefficientSource<B>>(
+        total_round: usize,
+        code: &ReedSolomonCode<T>,
+        source: &mut S,
+        chunk_size: usize,
+        batch: usize,
+        dir: &Path,
+        parameters: FriParameters<H>,
+    ) -> io::Result<Self> {
+        // Masking the polynomial would need its random extension in memory
+        if parameters.hiding() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a spilled commitment cannot hide",
+            ));
+        }
+        assert_eq!(source.len(), 1 << total_round);
+        let round_starts = parameters.round_starts(total_round);
+        let codeword = SpilledCodeword::new(
+            &code.cosets()[0].to_base_coset::<B>(),
+            source,
+            chunk_size,
+            batch,
+            round_starts[1],
+            dir,
+        )?;
+        let hypercube_interpolation = spilled_hypercube(source, chunk_size, dir)?;
+        Ok(BasefoldCommitment {
+            total_round,
+            code: code.clone(),
+            parameters,
+            round_starts,
+            polynomial_interpolation: Codeword::Spilled(codeword),
+            hypercube_interpolation: Table::Spilled(hypercube_interpolation),
+            spill_dir: Some(dir.to_path_buf()),
+            chunk_size,
+            field: PhantomData,
+        })
+    }
+}
+
+// Opens `commitment` at `point`, continuing `transcript`, which has to have absorbed the
+// root already. The proof stands on its own and the commitment is left as it was.
+pub fn open<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>>(
+    commitment: &Arc<BasefoldCommitment<T, B, H, C>>,
+    point: &Vec<T>,
+    transcript: &mut Transcript,
+) -> io::Result<BasefoldProof<T, B>> {
+    open_with(commitment, point, transcript, &mut rand::thread_rng())
+}
+
+// The mask and the salts of a hiding opening are drawn from `rng`
+pub fn open_with<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>, R: RngCore>(
+    commitment: &Arc<BasefoldCommitment<T, B, H, C>>,
+    point: &Vec<T>,
+    transcript: &mut Transcript,
+    rng: &mut R,
+) -> io::Result<BasefoldProof<T, B>> {
+    let mut prover = Prover::with_commitment(commitment.clone(), transcript.clone(), rng);
+    let proof = prover.open(point)?;
+    *transcript = prover.transcript;
+    Ok(proof)
+}
+
+// The state of one opening of a shared commitment
+pub struct Prover<
+    T: Field,
+    B: Field = T,
+    H: MerkleHasher = Blake3Algorithm,
+    C: FoldableCode<T> = ReedSolomonCode<T>,
+> {
+    commitment: Arc<BasefoldCommitment<T, B, H, C>>,
+    // The foldings a round starts from, the only ones committed
+    interpolations: Vec<Codeword<T, H>>,
+    // The sumcheck table, the commitment's one until the first variable is bound
+    hypercube_interpolation: Option<Table<T>>,
+    // The challenges of the folded rounds, which the foldings are computed from
+    challenges: Vec<T>,
     sumcheck_value: Vec<(T, T, T)>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    transcript: Transcript,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
+    mask: Option<InterpolateValue<T, H>>,
+    // The evaluations of the mask over the hypercube, times its weight
+    mask_hypercube: Option<Vec<T>>,
+    mask_evaluation: Option<T>,
+    mask_weight: Option<T>,
+    mask_proof: Option<QueryResult<T>>,
+    // The source of the mask and the salts of a hiding opening
+    rng: StdRng,
 }
 
-impl<T: Field> Prover<T> {
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>> Prover<T, B, H, C> {
     pub fn new(
         total_round: usize,
-        interpolate_cosets: &Vec<Coset<T>>,
-        polynomial: MultilinearPolynomial<T>,
-        oracle: &RandomOracle<T>,
+        code: &C,
+        polynomial: MultilinearPolynomial<B>,
+        parameters: FriParameters<H>,
     ) -> Self {
-        Prover {
+        Self::new_with(
             total_round,
-            interpolate_cosets: interpolate_cosets.clone(),
-            interpolations: vec![InterpolateValue::new(
-                interpolate_cosets[0].fft(polynomial.coefficients().clone()),
-            )],
-            hypercube_interpolation: polynomial.evaluate_hypercube(),
+            code,
             polynomial,
+            parameters,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    pub fn new_with<R: RngCore>(
+        total_round: usize,
+        code: &C,
+        polynomial: MultilinearPolynomial<B>,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Self {
+        let commitment =
+            BasefoldCommitment::new_with(total_round, code, polynomial, parameters, rng);
+        let transcript = commitment.transcript();
+        Self::with_commitment(Arc::new(commitment), transcript, rng)
+    }
+
+    // An opening of `commitment` that continues `transcript`, with its randomness from `rng`
+    pub fn with_commitment<R: RngCore>(
+        commitment: Arc<BasefoldCommitment<T, B, H, C>>,
+        transcript: Transcript,
+        rng: &mut R,
+    ) -> Self {
+        Prover {
+            commitment,
+            interpolations: vec![],
+            hypercube_interpolation: None,
+            challenges: vec![],
             sumcheck_value: vec![],
-            oracle: oracle.clone(),
-            final_value: None,
+            transcript,
+            final_polynomial: None,
+            pow_nonce: None,
+            mask: None,
+            mask_hypercube: None,
+            mask_evaluation: None,
+            mask_weight: None,
+            mask_proof: None,
+            rng: StdRng::from_rng(rng).unwrap(),
         }
     }
 
     pub fn commit_polynomial(&self) -> [u8; MERKLE_ROOT_SIZE] {
-        self.interpolations[0].commit()
+        self.commitment.root()
     }
 
-    pub fn commit_foldings(&self, verifier: &mut Verifier<T>) {
-        for i in 1..self.total_round {
-            let interpolation = &self.interpolations[i];
+    pub fn commitment(&self) -> &Arc<BasefoldCommitment<T, B, H, C>> {
+        &self.commitment
+    }
+
+    pub fn commit_foldings(&self, verifier: &mut Verifier<T, H, C>) {
+        for interpolation in &self.interpolations {
             verifier.receive_folding_root(interpolation.leave_num(), interpolation.commit());
         }
         for i in &self.sumcheck_value {
             verifier.receive_sumcheck_value(i.clone());
         }
-        verifier.set_final_value(self.final_value.unwrap());
-    }
-
-    pub fn send_evaluation(&self, verifier: &mut Verifier<T>, point: &Vec<T>) {
-        verifier.set_evalutation(self.polynomial.evaluate(point));
-    }
-
-    fn evaluation_next_domain(&self, round: usize, challenge: T) -> Vec<T> {
-        let mut res = vec![];
-        let len = self.interpolate_cosets[round].size();
-        let get_folding_value = &self.interpolations[round].value;
-        let coset = &self.interpolate_cosets[round];
-        for i in 0..(len / 2) {
-            let x = get_folding_value[i];
-            let nx = get_folding_value[i + len / 2];
-            let new_v = (x + nx) + challenge * (x - nx) * coset.element_inv_at(i);
-            res.push(new_v * T::INVERSE_2);
-        }
-        res
-    }
-
-    fn sumcheck_next_domain(hypercube_interpolation: &mut Vec<T>, m: usize, challenge: T) {
-        for i in 0..m {
-            hypercube_interpolation[i] *= T::from_int(1) - challenge;
-            let tmp = hypercube_interpolation[i + m] * challenge;
-            hypercube_interpolation[i] += tmp;
-        }
-    }
-
-    pub fn prove(&mut self, point: &Vec<T>) {
-        let mut poly_hypercube = self.hypercube_interpolation.clone();
-        let mut eq_hypercube = EqMultilinear::new(point.clone()).evaluate_hypercube();
-        for i in 0..self.total_round {
-            let m = 1 << (self.total_round - i - 1);
-            let (sum_0, sum_1, sum_2) = (0..m).into_iter().fold(
-                (T::from_int(0), T::from_int(0), T::from_int(0)),
-                |acc, x| {
-                    let p_0 = poly_hypercube[x];
-                    let p_1 = poly_hypercube[x + m];
-                    let e_0 = eq_hypercube[x];
-                    let e_1 = eq_hypercube[x + m];
-                    (
-                        acc.0 + p_0 * e_0,
-                        acc.1 + p_1 * e_1,
-                        acc.2 + (p_1 + p_1 - p_0) * (e_1 + e_1 - e_0),
-                    )
-                },
+        verifier.set_final_polynomial(self.final_polynomial.clone().unwrap());
+        verifier.set_pow_nonce(self.pow_nonce.unwrap());
+    }
+
+    pub fn send_evaluation(
+        &mut self,
+        verifier: &mut Verifier<T, H, C>,
+        point: &Vec<T>,
+    ) -> io::Result<()> {
+        let evaluation = self.compute_evaluation(point)?;
+        verifier.set_evalutation(evaluation);
+        Ok(())
+    }
+
+    fn compute_evaluation(&mut self, point: &Vec<T>) -> io::Result<T> {
+        let point = hiding_point(point, self.commitment.parameters.hiding());
+        let mask = if self.commitment.parameters.hiding() {
+            let mask = MultilinearPolynomial::<T>::random_polynomial_with(
+                self.commitment.total_round,
+                &mut self.rng,
             );
+            let interpolation = InterpolateValue::with_arity(
+                self.commitment
+                    .code
+                    .encode::<T>(mask.coefficients().clone()),
+                self.commitment.round_starts[1],
+                Some(&mut self.rng),
+            );
+            self.transcript.append_root(&interpolation.commit());
+            self.mask = Some(interpolation);
+            Some(mask)
+        } else {
+            None
+        };
+        // f(z) = sum over the hypercube of f(b) * eq(z, b)
+        let eq = EqTable::new(point.clone());
+        let len = self.table_len();
+        let chunk_size = len.min(self.commitment.chunk_size);
+        let mut evaluation = T::from_int(0);
+        for start in (0..len).step_by(chunk_size) {
+            let values = self
+                .commitment
+                .hypercube_interpolation
+                .chunk(start, chunk_size)?;
+            evaluation = eq
+                .chunk(start, chunk_size)
+                .iter()
+                .zip(values.iter())
+                .fold(evaluation, |acc, (e, f)| acc + e.mul_base(*f));
+        }
+        self.transcript.append_fields(&point);
+        self.transcript.append_field(&evaluation);
+        // The sumcheck and the foldings run on f + weight * g for the random mask g
+        if let Some(mask) = mask {
+            let mask_evaluation = mask.evaluate(&point);
+            self.transcript.append_field(&mask_evaluation);
+            let weight = self.transcript.challenge_field();
+            let mut mask_hypercube = mask
+                .coefficients()
+                .iter()
+                .map(|x| weight * *x)
+                .collect::<Vec<_>>();
+            hypercube_transform(&mut mask_hypercube);
+            self.mask_hypercube = Some(mask_hypercube);
+            self.mask_evaluation = Some(mask_evaluation);
+            self.mask_weight = Some(weight);
+        }
+        Ok(evaluation)
+    }
+
+    // Runs the whole opening non-interactively and packs every prover message into a proof.
+    // The tables and foldings of a spilled commitment are on disk, so this can fail.
+    pub fn open(&mut self, point: &Vec<T>) -> io::Result<BasefoldProof<T, B>> {
+        let evaluation = self.compute_evaluation(point)?;
+        self.prove(point)?;
+        let (polynomial_proof, folding_proof) = self.query()?;
+        Ok(BasefoldProof {
+            evaluation,
+            sumcheck_values: self.sumcheck_value.clone(),
+            folding_roots: self.interpolations.iter().map(|x| x.commit()).collect(),
+            final_polynomial: self.final_polynomial.clone().unwrap(),
+            pow_nonce: self.pow_nonce.unwrap(),
+            polynomial_proof,
+            folding_proof,
+            mask: self.mask_opening(),
+        })
+    }
+
+    // The mask of a hiding opening, once its queries are answered
+    pub fn mask_opening(&self) -> Option<MaskOpening<T>> {
+        Some(MaskOpening {
+            root: self.mask.as_ref()?.commit(),
+            evaluation: self.mask_evaluation?,
+            proof: self.mask_proof.clone()?,
+        })
+    }
+
+    fn table_len(&self) -> usize {
+        match &self.hypercube_interpolation {
+            Some(table) => table.len(),
+            None => self.commitment.hypercube_interpolation.len(),
+        }
+    }
+
+    // The `len` entries of the sumcheck table from `start` on
+    fn read_table(&self, start: usize, len: usize) -> io::Result<Cow<'_, [T]>> {
+        if let Some(table) = &self.hypercube_interpolation {
+            return table.chunk(start, len);
+        }
+        let values = self.commitment.hypercube_interpolation.chunk(start, len)?;
+        let mut values = values.iter().map(|x| T::from(*x)).collect::<Vec<_>>();
+        if let Some(mask) = &self.mask_hypercube {
+            for (v, m) in values.iter_mut().zip(mask[start..start + len].iter()) {
+                *v += *m;
+            }
+        }
+        Ok(Cow::Owned(values))
+    }
+
+    // The sums of the sumcheck round on the variable of the lowest bit, a chunk at a time
+    fn sumcheck_values(&self, eq: &EqTable<T>) -> io::Result<(T, T, T)> {
+        let len = self.table_len();
+        let chunk_size = len.min(self.commitment.chunk_size);
+        let mut sums = (T::from_int(0), T::from_int(0), T::from_int(0));
+        for start in (0..len).step_by(chunk_size) {
+            let poly = self.read_table(start, chunk_size)?;
+            let eq = eq.chunk(start, chunk_size);
+            for (p, e) in poly.chunks(2).zip(eq.chunks(2)) {
+                let (p_0, p_1, e_0, e_1) = (p[0], p[1], e[0], e[1]);
+                sums = (
+                    sums.0 + p_0 * e_0,
+                    sums.1 + p_1 * e_1,
+                    sums.2 + (p_1 + p_1 - p_0) * (e_1 + e_1 - e_0),
+                );
+            }
+        }
+        Ok(sums)
+    }
+
+    // Binds the variable of the lowest bit of the sumcheck table to `challenge`. The table of
+    // the commitment is left as it is, the first round starts one of the opening's own.
+    fn sumcheck_next_domain(&mut self, challenge: T) -> io::Result<()> {
+        let fold = |poly: &[T], res: &mut [T]| {
+            for (x, p) in res.iter_mut().zip(poly.chunks(2)) {
+                *x = p[0] + (p[1] - p[0]) * challenge;
+            }
+        };
+        let len = self.table_len() / 2;
+        let chunk_size = len.min(self.commitment.chunk_size / 2);
+        let table = match self.hypercube_interpolation.take() {
+            Some(Table::InMemory(mut values)) => {
+                for i in 0..len {
+                    values[i] = values[2 * i] + (values[2 * i + 1] - values[2 * i]) * challenge;
+                }
+                values.truncate(len);
+                Table::InMemory(values)
+            }
+            // Entries are written over ones already read
+            Some(Table::Spilled(mut table)) => {
+                let mut poly = vec![T::from_int(0); chunk_size * 2];
+                let mut res = vec![T::from_int(0); chunk_size];
+                for start in (0..len).step_by(chunk_size) {
+                    table.read(start * 2, &mut poly)?;
+                    fold(&poly, &mut res);
+                    table.write(start, &res)?;
+                }
+                table.truncate(len)?;
+                Table::Spilled(table)
+            }
+            None => match &self.commitment.spill_dir {
+                None => {
+                    let mut res = vec![T::from_int(0); len];
+                    for start in (0..len).step_by(chunk_size) {
+                        fold(
+                            &self.read_table(start * 2, chunk_size * 2)?,
+                            &mut res[start..start + chunk_size],
+                        );
+                    }
+                    Table::InMemory(res)
+                }
+                Some(dir) => Table::Spilled(SpilledTable::from_chunks(
+                    dir,
+                    len,
+                    chunk_size,
+                    |start, res| {
+                        fold(&self.read_table(start * 2, res.len() * 2)?, res);
+                        Ok(())
+                    },
+                )?),
+            },
+        };
+        self.hypercube_interpolation = Some(table);
+        Ok(())
+    }
+
+    // Calls `f(t, values)` with the values of every leaf t of the codeword round `round`
+    // starts from
+    fn for_each_leaf(
+        &self,
+        round: usize,
+        mut f: impl FnMut(usize, &mut [T]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if round > 0 {
+            return self.interpolations[round - 1]
+                .for_each_leaf(|t, values| f(t, &mut values.to_vec()));
+        }
+        // The first round folds f + weight * g for the mask g, as the sumcheck does
+        let leave_num = self.commitment.polynomial_interpolation.leave_num();
+        self.commitment
+            .polynomial_interpolation
+            .for_each_leaf(|t, values| {
+                let mut values = values.iter().map(|x| T::from(*x)).collect::<Vec<_>>();
+                if let (Some(mask), Some(weight)) = (&self.mask, self.mask_weight) {
+                    for (j, v) in values.iter_mut().enumerate() {
+                        *v += weight * mask.value[t + j * leave_num];
+                    }
+                }
+                f(t, &mut values)
+            })
+    }
+
+    // Folds the codeword round `round` starts from with the challenges of the round, and
+    // commits to the folding the next round starts from. Leaf t holds exactly the values that
+    // fold into entry t, so the codeword is read once, a leaf at a time.
+    fn evaluation_next_domain(&mut self, round: usize) -> io::Result<()> {
+        let commitment = self.commitment.clone();
+        let (first, end) = (
+            commitment.round_starts[round],
+            commitment.round_starts[round + 1],
+        );
+        let log_arity = commitment.round_starts[round + 2] - end;
+        let leave_num = if round == 0 {
+            commitment.polynomial_interpolation.leave_num()
+        } else {
+            self.interpolations[round - 1].leave_num()
+        };
+        let challenges = &self.challenges[first..end];
+        let fold = |t: usize, values: &mut [T]| {
+            let mut half = values.len() / 2;
+            for (level, challenge) in (first..end).zip(challenges.iter()) {
+                for j in 0..half {
+                    values[j] = commitment.code.fold(
+                        level,
+                        t + j * leave_num,
+                        *challenge,
+                        values[j],
+                        values[j + half],
+                    );
+                }
+                half /= 2;
+            }
+            values[0]
+        };
+        let interpolation = match &commitment.spill_dir {
+            None => {
+                let mut res = vec![T::from_int(0); leave_num];
+                self.for_each_leaf(round, |t, values| {
+                    res[t] = fold(t, values);
+                    Ok(())
+                })?;
+                Codeword::InMemory(InterpolateValue::with_arity(
+                    res,
+                    log_arity,
+                    commitment
+                        .parameters
+                        .hiding()
+                        .then_some(&mut self.rng as &mut dyn RngCore),
+                ))
+            }
+            Some(dir) => {
+                let mut res = SpilledTable::new(dir, leave_num)?;
+                let mut pending = vec![];
+                self.for_each_leaf(round, |t, values| {
+                    pending.push((t, fold(t, values)));
+                    if pending.len() == commitment.chunk_size {
+                        write_runs(&res, &mut pending)?;
+                    }
+                    Ok(())
+                })?;
+                write_runs(&res, &mut pending)?;
+                Codeword::Spilled(SpilledCodeword::from_source(
+                    &mut res,
+                    log_arity,
+                    commitment.chunk_size >> log_arity,
+                    dir,
+                )?)
+            }
+        };
+        self.transcript.append_root(&interpolation.commit());
+        self.interpolations.push(interpolation);
+        Ok(())
+    }
+
+    pub fn prove(&mut self, point: &Vec<T>) -> io::Result<()> {
+        let point = hiding_point(point, self.commitment.parameters.hiding());
+        let mut eq = EqTable::new(point);
+        let folds = *self.commitment.round_starts.last().unwrap();
+        for i in 0..self.commitment.total_round {
+            let (sum_0, sum_1, sum_2) = self.sumcheck_values(&eq)?;
             self.sumcheck_value.push((sum_0, sum_1, sum_2));
-            let challenge = self.oracle.folding_challenges[i];
-            let next_evalutation = self.evaluation_next_domain(i, challenge);
-            if i < self.total_round - 1 {
-                Self::sumcheck_next_domain(&mut poly_hypercube, m, challenge);
-                Self::sumcheck_next_domain(&mut eq_hypercube, m, challenge);
-                self.interpolations
-                    .push(InterpolateValue::new(next_evalutation));
+            self.transcript.append_fields(&[sum_0, sum_1, sum_2]);
+            let challenge = self.transcript.challenge_field();
+            self.sumcheck_next_domain(challenge)?;
+            eq.fold(challenge);
+            // The sumcheck runs over every variable, the folding stops early
+            if i >= folds {
+                continue;
+            }
+            self.challenges.push(challenge);
+            if i < folds - 1 {
+                // Only the foldings a round starts from are computed, once its challenges
+                // are all drawn, and committed
+                if let Some(round) = self
+                    .commitment
+                    .round_starts
+                    .iter()
+                    .position(|x| *x == i + 1)
+                {
+                    self.evaluation_next_domain(round - 1)?;
+                }
             } else {
-                self.final_value = Some(next_evalutation[0]);
+                // The multilinear polynomial left is sent by its coefficients, which the
+                // sumcheck has as evaluations over the hypercube of the variables left
+                let mut coefficients = self
+                    .read_table(0, 1 << (self.commitment.total_round - folds))?
+                    .into_owned();
+                inverse_hypercube_transform(&mut coefficients);
+                self.transcript.append_fields(&coefficients);
+                self.final_polynomial = Some(coefficients);
             }
         }
+        self.pow_nonce = Some(
+            self.transcript
+                .grind(self.commitment.parameters.grinding_bits),
+        );
+        Ok(())
     }
 
-    pub fn query(&self) -> Vec<QueryResult<T>> {
-        let mut res = vec![];
-        let mut leaf_indices = self.oracle.query_list.clone();
+    pub fn query(&mut self) -> io::Result<(QueryResult<B>, Vec<QueryResult<T>>)> {
+        let mut polynomial_res = None;
+        let mut folding_res = vec![];
+        let mut leaf_indices = self
+            .transcript
+            .challenge_indices(self.commitment.parameters.query_num);
 
-        for i in 0..self.total_round {
-            let len = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices.iter_mut().map(|v| *v % (len >> 1)).collect();
+        for i in 0..self.commitment.round_starts.len() - 1 {
+            let len = if i == 0 {
+                self.commitment.polynomial_interpolation.leave_num()
+            } else {
+                self.interpolations[i - 1].leave_num()
+            };
+            leaf_indices = leaf_indices.iter_mut().map(|v| *v % len).collect();
             leaf_indices.sort();
             leaf_indices.dedup();
-            res.push(self.interpolations[i].query(&leaf_indices));
+            if i == 0 {
+                polynomial_res = Some(
+                    self.commitment
+                        .polynomial_interpolation
+                        .query(&leaf_indices)?,
+                );
+                self.mask_proof = self.mask.as_ref().map(|x| x.query(&leaf_indices));
+            } else {
+                folding_res.push(self.interpolations[i - 1].query(&leaf_indices)?);
+            }
         }
-        res
+        Ok((polynomial_res.unwrap(), folding_res))
     }
 }
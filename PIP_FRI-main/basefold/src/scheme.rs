@@ -0,0 +1,91 @@
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use rand::RngCore;
+use util::{
+    algebra::{
+        field::{ExtensionOf, Field},
+        polynomial::MultilinearPolynomial,
+    },
+    error::{ParameterError, VerifyError},
+    foldable_code::FoldableCode,
+    merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    pcs::PolynomialCommitmentScheme,
+};
+
+use crate::{
+    proof::{BasefoldParams, BasefoldProof},
+    prover::{self, BasefoldCommitment},
+    verifier,
+};
+
+// Basefold over the foldable code `C`, which the params pick, committing to polynomials over
+// the subfield `B` of the challenges. `B` has to be named to set up over a proper subfield.
+pub struct Basefold<C, B> {
+    code: PhantomData<C>,
+    field: PhantomData<B>,
+}
+
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>>
+    PolynomialCommitmentScheme<T, H> for Basefold<C, B>
+{
+    type Params = BasefoldParams<T, H, C>;
+    type Polynomial = MultilinearPolynomial<B>;
+    type Point = Vec<T>;
+    type Commitment = [u8; MERKLE_ROOT_SIZE];
+    type Evaluation = T;
+    // Clones of the Arc open the same commitment again
+    type ProverData = Arc<BasefoldCommitment<T, B, H, C>>;
+    type Proof = BasefoldProof<T, B>;
+
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError> {
+        Ok(BasefoldParams::new_with::<B, R>(
+            variable_num,
+            parameters,
+            rng,
+        ))
+    }
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let commitment = BasefoldCommitment::new(
+            params.variable_num,
+            &params.code,
+            polynomial,
+            params.parameters,
+        );
+        (commitment.root(), Arc::new(commitment))
+    }
+
+    fn open(
+        _params: &Self::Params,
+        prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)> {
+        // Only a commitment spilled to disk can fail to open
+        let proof = prover::open(&prover_data, point, &mut prover_data.transcript())?;
+        Ok((proof.evaluation, proof))
+    }
+
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError> {
+        if proof.evaluation != *value {
+            return Err(VerifyError::ClaimedValue);
+        }
+        let mut transcript = verifier::commitment_transcript(commitment);
+        verifier::verify(params, commitment, point, proof, &mut transcript)
+    }
+}
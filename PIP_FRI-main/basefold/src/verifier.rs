@@ -1,46 +1,66 @@
-use util::merkle_tree::MERKLE_ROOT_SIZE;
-use util::random_oracle::RandomOracle;
+use crate::proof::{BasefoldParams, BasefoldProof};
+use util::error::VerifyError;
+use util::foldable_code::{FoldableCode, ReedSolomonCode};
+use util::interpolation::fold_leaf;
+use util::mask::{hiding_point, MaskOpening};
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
+use util::transcript::Transcript;
 use util::{
-    algebra::{coset::Coset, field::Field},
+    algebra::{
+        field::{ExtensionOf, Field},
+        polynomial::{EqMultilinear, MultilinearPolynomial},
+    },
     merkle_tree::MerkleTreeVerifier,
+    parameters::FriParameters,
     query_result::QueryResult,
 };
 
 #[derive(Clone)]
-pub struct Verifier<T: Field> {
+pub struct Verifier<
+    T: Field,
+    H: MerkleHasher = Blake3Algorithm,
+    C: FoldableCode<T> = ReedSolomonCode<T>,
+> {
     total_round: usize,
-    interpolate_cosets: Vec<Coset<T>>,
-    polynomial_roots: Vec<MerkleTreeVerifier>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    code: C,
+    polynomial_roots: Vec<MerkleTreeVerifier<H>>,
+    parameters: FriParameters<H>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
     sumcheck_values: Vec<(T, T, T)>,
     open_point: Vec<T>,
     evaluation: Option<T>,
+    mask: Option<MaskOpening<T>>,
 }
 
-impl<T: Field> Verifier<T> {
+impl<T: Field, H: MerkleHasher, C: FoldableCode<T>> Verifier<T, H, C> {
     pub fn new(
         total_round: usize,
-        coset: &Vec<Coset<T>>,
+        code: &C,
         commit: [u8; MERKLE_ROOT_SIZE],
-        oracle: &RandomOracle<T>,
+        open_point: &Vec<T>,
+        parameters: FriParameters<H>,
     ) -> Self {
+        // The query count of DEEP parameters is only sound with the out-of-domain sample
+        assert!(!parameters.deep(), "DEEP-FRI is not supported by this scheme");
+        let total_round = total_round + parameters.hiding() as usize;
         Verifier {
             total_round,
-            interpolate_cosets: coset.clone(),
-            oracle: oracle.clone(),
-            polynomial_roots: vec![MerkleTreeVerifier::new(coset[0].size() / 2, &commit)],
-            final_value: None,
+            code: code.clone(),
+            parameters,
+            polynomial_roots: vec![MerkleTreeVerifier::new(
+                code.size() >> parameters.round_starts(total_round)[1],
+                &commit,
+            )],
+            final_polynomial: None,
+            pow_nonce: None,
             sumcheck_values: vec![],
-            open_point: (0..total_round).map(|_| T::random_element()).collect(),
+            open_point: hiding_point(open_point, parameters.hiding()),
             evaluation: None,
+            mask: None,
         }
     }
 
-    pub fn get_open_point(&self) -> Vec<T> {
-        self.open_point.clone()
-    }
-
     pub fn receive_sumcheck_value(&mut self, value: (T, T, T)) {
         self.sumcheck_values.push(value);
     }
@@ -50,56 +70,248 @@ impl<T: Field> Verifier<T> {
         leave_number: usize,
         folding_root: [u8; MERKLE_ROOT_SIZE],
     ) {
-        self.polynomial_roots.push(MerkleTreeVerifier {
-            leave_number,
-            merkle_root: folding_root,
-        });
+        self.polynomial_roots
+            .push(MerkleTreeVerifier::new(leave_number, &folding_root));
     }
 
     pub fn set_evalutation(&mut self, evaluation: T) {
         self.evaluation = Some(evaluation);
     }
 
-    pub fn set_final_value(&mut self, value: T) {
-        self.final_value = Some(value);
+    pub fn set_final_polynomial(&mut self, coefficients: Vec<T>) {
+        self.final_polynomial = Some(coefficients);
     }
 
-    pub fn verify(&self, polynomial_proof: &Vec<QueryResult<T>>) -> bool {
-        let mut leaf_indices = self.oracle.query_list.clone();
-        let mut sum = self.sumcheck_values[0].0 + self.sumcheck_values[0].1;
-        for i in 0..self.total_round {
-            let domain_size = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices
-                .iter_mut()
-                .map(|v| *v % (domain_size >> 1))
-                .collect();
-            leaf_indices.sort();
-            leaf_indices.dedup();
+    pub fn set_pow_nonce(&mut self, nonce: u64) {
+        self.pow_nonce = Some(nonce);
+    }
+
+    pub fn set_mask(&mut self, mask: MaskOpening<T>) {
+        self.mask = Some(mask);
+    }
 
-            polynomial_proof[i].verify_merkle_tree(&leaf_indices, &self.polynomial_roots[i]);
-            let folding_value = &polynomial_proof[i].proof_values;
-            let challenge = self.oracle.folding_challenges[i];
+    pub fn verify<B: Field>(
+        &self,
+        polynomial_proof: &QueryResult<B>,
+        folding_proof: &Vec<QueryResult<T>>,
+    ) -> Result<(), VerifyError>
+    where
+        T: ExtensionOf<B>,
+    {
+        let mut transcript = commitment_transcript(&self.polynomial_roots[0].merkle_root);
+        self.verify_with_transcript(&mut transcript, polynomial_proof, folding_proof)
+    }
 
-            let x_0 = self.sumcheck_values[i].0;
-            let x_1 = self.sumcheck_values[i].1;
-            let x_2 = self.sumcheck_values[i].2;
-            assert_eq!(sum, x_0 + x_1);
+    // Continues `transcript` as the opening did, from the point where it had absorbed the root
+    pub fn verify_with_transcript<B: Field>(
+        &self,
+        transcript: &mut Transcript,
+        polynomial_proof: &QueryResult<B>,
+        folding_proof: &[QueryResult<T>],
+    ) -> Result<(), VerifyError>
+    where
+        T: ExtensionOf<B>,
+    {
+        let (evaluation, final_polynomial) = match (self.evaluation, &self.final_polynomial) {
+            (Some(evaluation), Some(final_polynomial)) => (evaluation, final_polynomial),
+            _ => return Err(VerifyError::MalformedProof),
+        };
+        if self.total_round == 0 {
+            return Err(VerifyError::MalformedProof);
+        }
+        let round_starts = self.parameters.round_starts(self.total_round);
+        let rounds = round_starts.len() - 1;
+        let folds = round_starts[rounds];
+        if folding_proof.len() != rounds - 1
+            || self.polynomial_roots.len() != rounds
+            || self.sumcheck_values.len() != self.total_round
+            || final_polynomial.len() != 1 << (self.total_round - folds)
+            || self.mask.is_some() != self.parameters.hiding()
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        // Replay the prover's transcript to derive every challenge
+        if let Some(mask) = &self.mask {
+            transcript.append_root(&mask.root);
+        }
+        transcript.append_fields(&self.open_point);
+        transcript.append_field(&evaluation);
+        // A hiding opening runs the sumcheck and the foldings on f + weight * g
+        let mut sum = evaluation;
+        let mut mask_weight = T::from_int(0);
+        if let Some(mask) = &self.mask {
+            transcript.append_field(&mask.evaluation);
+            mask_weight = transcript.challenge_field();
+            sum += mask_weight * mask.evaluation;
+        }
+        let mut folding_challenges: Vec<T> = vec![];
+        for r in 0..rounds {
+            for i in round_starts[r]..round_starts[r + 1] {
+                let (x_0, x_1, x_2) = self.sumcheck_values[i];
+                transcript.append_fields(&[x_0, x_1, x_2]);
+                folding_challenges.push(transcript.challenge_field());
+            }
+            if r < rounds - 1 {
+                transcript.append_root(&self.polynomial_roots[r + 1].merkle_root);
+            } else {
+                transcript.append_fields(final_polynomial);
+            }
+        }
+        // The sumcheck goes on over the variables left in the final polynomial
+        for i in folds..self.total_round {
+            let (x_0, x_1, x_2) = self.sumcheck_values[i];
+            transcript.append_fields(&[x_0, x_1, x_2]);
+            folding_challenges.push(transcript.challenge_field());
+        }
+        let pow_nonce = self.pow_nonce.ok_or(VerifyError::MalformedProof)?;
+        if !transcript.check_grinding(self.parameters.grinding_bits, pow_nonce) {
+            return Err(VerifyError::ProofOfWork);
+        }
+        for (i, challenge) in folding_challenges.iter().enumerate() {
+            let challenge = *challenge;
+            let (x_0, x_1, x_2) = self.sumcheck_values[i];
+            if sum != x_0 + x_1 {
+                return Err(VerifyError::Sumcheck { round: i });
+            }
             sum = x_0 * (T::from_int(1) - challenge) * (T::from_int(2) - challenge) * T::INVERSE_2
                 + x_1 * challenge * (T::from_int(2) - challenge)
                 + x_2 * challenge * (challenge - T::from_int(1)) * T::INVERSE_2;
-            for j in &leaf_indices {
-                let x = folding_value[j];
-                let nx = folding_value[&(j + domain_size / 2)];
-                let v =
-                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                if i == self.total_round - 1 {
-                    assert_eq!(v * T::INVERSE_2, self.final_value.unwrap());
-                    // assert_eq!(self.final_value.unwrap(), sum);
+        }
+        let mut leaf_indices = transcript.challenge_indices(self.parameters.query_num);
+        // The leaves queried in every round, which place the opened values in the codewords
+        let mut round_indices = vec![];
+        for root in self.polynomial_roots.iter() {
+            leaf_indices = leaf_indices
+                .iter_mut()
+                .map(|v| *v % root.leave_number)
+                .collect();
+            leaf_indices.sort();
+            leaf_indices.dedup();
+            round_indices.push(leaf_indices.clone());
+        }
+        let leave_number = self.polynomial_roots[0].leave_number;
+        let polynomial_values = polynomial_proof.open_values(&round_indices[0], leave_number)?;
+        let folding_values = folding_proof
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                x.open_values(
+                    &round_indices[i + 1],
+                    self.polynomial_roots[i + 1].leave_number,
+                )
+            })
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let mask_values = match &self.mask {
+            Some(mask) => Some(mask.proof.open_values(&round_indices[0], leave_number)?),
+            None => None,
+        };
+        for r in 0..rounds {
+            let start = round_starts[r];
+            let leaf_indices = &round_indices[r];
+            let merkle_ok = if r == 0 {
+                polynomial_proof.verify_merkle_tree(leaf_indices, &self.polynomial_roots[0])
+            } else {
+                folding_proof[r - 1].verify_merkle_tree(leaf_indices, &self.polynomial_roots[r])
+            };
+            let mask_ok = match &self.mask {
+                Some(mask) if r == 0 => mask.proof.verify_merkle_tree(
+                    leaf_indices,
+                    &MerkleTreeVerifier::<H>::new(leave_number, &mask.root),
+                ),
+                _ => true,
+            };
+            if !merkle_ok || !mask_ok {
+                return Err(VerifyError::MerklePath { round: r });
+            }
+            let get_folding_value = |index: usize| {
+                if r == 0 {
+                    let value = polynomial_values.get_value(r, index).map(T::from)?;
+                    match &mask_values {
+                        Some(mask) => Ok(value + mask_weight * mask.get_value(r, index)?),
+                        None => Ok(value),
+                    }
                 } else {
-                    assert_eq!(v * T::INVERSE_2, polynomial_proof[i + 1].proof_values[j]);
+                    folding_values[r - 1].get_value(r, index)
+                }
+            };
+            let round_leave_number = self.polynomial_roots[r].leave_number;
+            for j in leaf_indices {
+                let j = *j;
+                let coset = (0..1 << (round_starts[r + 1] - start))
+                    .map(|t| get_folding_value(j + t * round_leave_number))
+                    .collect::<Result<Vec<_>, VerifyError>>()?;
+                let v = fold_leaf(&coset, j, self.code.size() >> start, |step, i, x, nx| {
+                    let challenge = folding_challenges[start + step];
+                    self.code.fold(start + step, i, challenge, x, nx)
+                });
+                if r == rounds - 1 {
+                    // The last folding lands in the code of the final polynomial
+                    if v != self.code.evaluate(folds, final_polynomial, j) {
+                        return Err(VerifyError::FinalValue { index: j });
+                    }
+                } else if v != folding_values[r].get_value(r + 1, j)? {
+                    return Err(VerifyError::FoldConsistency { round: r, index: j });
                 }
             }
         }
-        true
+        // The last sumcheck claim is f(r) * eq(z, r), and the final polynomial is f with
+        // the folded variables fixed
+        let final_value = MultilinearPolynomial::new(final_polynomial.clone())
+            .evaluate(&folding_challenges[folds..].to_vec());
+        if sum
+            != final_value
+                * EqMultilinear::new(self.open_point.clone()).evaluate(&folding_challenges)
+        {
+            return Err(VerifyError::Sumcheck {
+                round: self.total_round,
+            });
+        }
+        Ok(())
+    }
+}
+
+// The transcript an opening on its own starts from
+pub fn commitment_transcript(root: &[u8; MERKLE_ROOT_SIZE]) -> Transcript {
+    let mut transcript = Transcript::new(b"basefold");
+    transcript.append_root(root);
+    transcript
+}
+
+// Checks an opening of the commitment `root` that continued `transcript`
+pub fn verify<T: ExtensionOf<B>, B: Field, H: MerkleHasher, C: FoldableCode<T>>(
+    params: &BasefoldParams<T, H, C>,
+    root: &[u8; MERKLE_ROOT_SIZE],
+    point: &Vec<T>,
+    proof: &BasefoldProof<T, B>,
+    transcript: &mut Transcript,
+) -> Result<(), VerifyError> {
+    let total_round = params.variable_num + params.parameters.hiding() as usize;
+    let round_starts = params.parameters.round_starts(total_round);
+    if params.variable_num == 0
+        || point.len() != params.variable_num
+        || proof.folding_roots.len() != round_starts.len() - 2
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+    let mut verifier = Verifier::new(
+        params.variable_num,
+        &params.code,
+        *root,
+        point,
+        params.parameters,
+    );
+    verifier.set_evalutation(proof.evaluation);
+    for value in &proof.sumcheck_values {
+        verifier.receive_sumcheck_value(*value);
+    }
+    for (i, root) in proof.folding_roots.iter().enumerate() {
+        verifier.receive_folding_root(params.code.size() >> round_starts[i + 2], *root);
+    }
+    verifier.set_final_polynomial(proof.final_polynomial.clone());
+    verifier.set_pow_nonce(proof.pow_nonce);
+    if let Some(mask) = &proof.mask {
+        verifier.set_mask(mask.clone());
     }
+    verifier.verify_with_transcript(transcript, &proof.polynomial_proof, &proof.folding_proof)
 }
@@ -2,24 +2,24 @@ extern crate criterion;
 use criterion::*;
 
 use fri::{prover::Prover, verifier::Verifier};
-use util::{
-    algebra::{
-        coset::Coset,
-        field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
-        polynomial::Polynomial,
-    },
-    random_oracle::RandomOracle,
+use util::algebra::{
+    coset::Coset,
+    field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
+    polynomial::Polynomial,
 };
 
-use util::{CODE_RATE, SECURITY_BITS};
+use util::parameters::FriParameters;
 fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let degree = 1 << variable_num;
     let polynomial = Polynomial::random_polynomial(degree);
-    let mut interpolate_cosets = vec![Coset::new(1 << (variable_num + CODE_RATE), T::from_int(1))];
+    let mut interpolate_cosets = vec![Coset::new(
+        1 << (variable_num + parameters.log_blowup),
+        T::from_int(1),
+    )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
 
     criterion.bench_function(
         &format!("fri {} commit {}", T::FIELD_NAME, variable_num),
@@ -27,7 +27,8 @@ fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize) {
             b.iter_batched(
                 || polynomial.clone(),
                 |p| {
-                    let prover = Prover::new(variable_num, &interpolate_cosets, p, &oracle);
+                    let prover =
+                        Prover::new(variable_num, &interpolate_cosets, p, parameters).unwrap();
                     let _ = prover.commit_polynomial();
                 },
                 BatchSize::SmallInput,
@@ -44,17 +45,20 @@ fn bench_commit(c: &mut Criterion) {
 }
 
 fn open<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let degree = 1 << variable_num;
     let polynomial = Polynomial::random_polynomial(degree);
-    let mut interpolate_cosets = vec![Coset::new(1 << (variable_num + CODE_RATE), T::from_int(1))];
+    let mut interpolate_cosets = vec![Coset::new(
+        1 << (variable_num + parameters.log_blowup),
+        T::from_int(1),
+    )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let prover = Prover::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
     let commits = prover.commit_polynomial();
-    let verifier = Verifier::new(variable_num, &interpolate_cosets, commits, &oracle);
-    let point = verifier.get_open_point();
+    let point = T::random_element();
+    let verifier = Verifier::new(variable_num, &interpolate_cosets, commits, point, parameters);
 
     criterion.bench_function(
         &format!("fri {} open {}", T::FIELD_NAME, variable_num),
@@ -80,20 +84,21 @@ fn bench_open(c: &mut Criterion) {
 }
 
 fn verify<T: Field>(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let degree = 1 << variable_num;
     let polynomial = Polynomial::random_polynomial(degree);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::from_int(1),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let mut prover =
+        Prover::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
     let commits = prover.commit_polynomial();
-    let mut verifier = Verifier::new(variable_num, &interpolate_cosets, commits, &oracle);
-    let point = verifier.get_open_point();
+    let point = Mersenne61Ext::random_element();
+    let mut verifier = Verifier::new(variable_num, &interpolate_cosets, commits, point, parameters);
 
     let evaluation = prover.prove(point);
     prover.commit_foldings(&mut verifier);
@@ -103,7 +108,7 @@ fn verify<T: Field>(criterion: &mut Criterion, variable_num: usize) {
         &format!("fri {} verify {}", T::FIELD_NAME, variable_num),
         move |b| {
             b.iter(|| {
-                assert!(verifier.verify(&interpolation_proof, evaluation));
+                assert!(verifier.verify(&interpolation_proof, evaluation).is_ok());
             })
         },
     );
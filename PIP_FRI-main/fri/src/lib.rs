@@ -1,40 +1,51 @@
+pub mod proof;
 pub mod prover;
+pub mod scheme;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
-    use crate::{prover::Prover, verifier::Verifier};
+    use crate::{proof::FriParams, prover::Prover, scheme::Fri, verifier::Verifier};
     use util::{
         algebra::{
             coset::Coset,
-            field::{mersenne61_ext::Mersenne61Ext, Field},
+            field::{fp64::Fp64, mersenne61_ext::Mersenne61Ext, Field},
             polynomial::Polynomial,
         },
-        merkle_tree::MERKLE_ROOT_SIZE,
-        random_oracle::RandomOracle,
+        error::{ParameterError, VerifyError},
+        merkle_tree::{Keccak256Algorithm, MerkleHasher, Sha256Algorithm, MERKLE_ROOT_SIZE},
+        parameters::FriParameters,
+        pcs::PolynomialCommitmentScheme,
+        poseidon::PoseidonAlgorithm,
     };
-    use util::{CODE_RATE, SECURITY_BITS};
 
     fn output_proof_size(variable_num: usize) -> usize {
+        let parameters = FriParameters::default();
         let degree = 1 << variable_num;
         let polynomial = Polynomial::random_polynomial(degree);
         let mut interpolate_cosets = vec![Coset::new(
-            1 << (variable_num + CODE_RATE),
+            1 << (variable_num + parameters.log_blowup),
             Mersenne61Ext::from_int(1),
         )];
         for i in 1..variable_num {
             interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
         }
-        let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-        let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+        let mut prover =
+            Prover::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
         let commits = prover.commit_polynomial();
-        let mut verifier = Verifier::new(variable_num, &interpolate_cosets, commits, &oracle);
-        let point = verifier.get_open_point();
+        let point = Mersenne61Ext::random_element();
+        let mut verifier = Verifier::new(
+            variable_num,
+            &interpolate_cosets,
+            commits,
+            point,
+            parameters,
+        );
 
         let evaluation = prover.prove(point);
         prover.commit_foldings(&mut verifier);
         let interpolation_proof = prover.query();
-        assert!(verifier.verify(&interpolation_proof, evaluation));
+        assert!(verifier.verify(&interpolation_proof, evaluation).is_ok());
         interpolation_proof
             .iter()
             .map(|x| x.proof_size())
@@ -52,4 +63,88 @@ mod tests {
             );
         }
     }
+
+    fn commitment_scheme<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let params: FriParams<Mersenne61Ext, H> = Fri::setup(variable_num, parameters).unwrap();
+        let polynomial = Polynomial::random_polynomial(1 << variable_num);
+        let (commitment, prover_data) = Fri::commit(&params, polynomial.clone());
+        let point: Mersenne61Ext = Mersenne61Ext::random_element();
+        let (value, proof) = Fri::open(&params, prover_data, &point).unwrap();
+        assert_eq!(value, polynomial.evaluation_at(point));
+        assert!(Fri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+        // A caller that expects another value rejects the proof
+        let wrong_value = value + Mersenne61Ext::from_int(1);
+        assert_eq!(
+            Fri::verify(&params, &commitment, &point, &wrong_value, &proof),
+            Err(VerifyError::ClaimedValue)
+        );
+    }
+
+    #[test]
+    fn test_commitment_scheme() {
+        commitment_scheme(FriParameters::default());
+    }
+
+    #[test]
+    fn test_hiding_rejected() {
+        let parameters = FriParameters::default().with_hiding();
+        let params: Result<FriParams<Mersenne61Ext>, _> = Fri::setup(8, parameters);
+        assert_eq!(params.err(), Some(ParameterError::HidingUnsupported));
+        let interpolate_cosets = vec![Coset::new(1 << 10, Mersenne61Ext::from_int(1))];
+        let polynomial = Polynomial::random_polynomial(1 << 8);
+        let prover = Prover::new(8, &interpolate_cosets, polynomial, parameters);
+        assert_eq!(prover.err(), Some(ParameterError::HidingUnsupported));
+    }
+
+    #[test]
+    fn test_folding_arity() {
+        for log_arity in 2..5 {
+            commitment_scheme(FriParameters::default().with_folding_arity(log_arity));
+        }
+    }
+
+    #[test]
+    fn test_final_polynomial() {
+        for log_final_degree in [1, 3, 8] {
+            let parameters = FriParameters::default().with_final_degree(log_final_degree);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_folding_arity(2));
+        }
+    }
+
+    #[test]
+    fn test_deep() {
+        let parameters = FriParameters::default().with_deep();
+        commitment_scheme(parameters.with_folding_arity(3).with_final_degree(2));
+
+        let variable_num = 8;
+        let params: FriParams<Mersenne61Ext> = Fri::setup(variable_num, parameters).unwrap();
+        let polynomial = Polynomial::random_polynomial(1 << variable_num);
+        let (commitment, prover_data) = Fri::commit(&params, polynomial);
+        let point = Mersenne61Ext::random_element();
+        let (value, proof) = Fri::open(&params, prover_data, &point).unwrap();
+        assert_eq!(
+            proof.interpolation_proof[0].values.len(),
+            proof.deep.as_ref().unwrap().proof.values.len()
+        );
+        assert!(Fri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.deep.as_mut().unwrap().evaluations[0] += Mersenne61Ext::from_int(1);
+        assert!(Fri::verify(&params, &commitment, &point, &value, &tampered).is_err());
+        tampered.deep = None;
+        assert_eq!(
+            Fri::verify(&params, &commitment, &point, &value, &tampered),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_merkle_hashers() {
+        let parameters = FriParameters::default();
+        commitment_scheme(parameters.with_hasher::<Sha256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<Keccak256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<PoseidonAlgorithm<Fp64>>());
+    }
 }
@@ -1,42 +1,81 @@
+use super::proof::FriProof;
 use super::verifier::Verifier;
-use util::algebra::field::batch_inverse;
 use util::algebra::polynomial::Polynomial;
+use util::deep::{out_of_domain_point, quotient, DeepOpening};
 
-use util::merkle_tree::MERKLE_ROOT_SIZE;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
 use util::query_result::QueryResult;
 use util::{
     algebra::{coset::Coset, field::Field},
+    error::ParameterError,
     interpolation::InterpolateValue,
-    random_oracle::RandomOracle,
+    parameters::FriParameters,
+    transcript::Transcript,
 };
 
 #[derive(Clone)]
-pub struct Prover<T: Field> {
+pub struct Prover<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     polynomial: Polynomial<T>,
     interpolate_cosets: Vec<Coset<T>>,
-    interpolations: Vec<InterpolateValue<T>>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    // One committed codeword per round, each folding several times
+    interpolations: Vec<InterpolateValue<T, H>>,
+    round_starts: Vec<usize>,
+    transcript: Transcript,
+    parameters: FriParameters<H>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
+    deep: Option<InterpolateValue<T, H>>,
+    deep_evaluation: Option<T>,
+    deep_proof: Option<QueryResult<T>>,
 }
 
-impl<T: Field> Prover<T> {
+impl<T: Field, H: MerkleHasher> Prover<T, H> {
+    // Fails on hiding parameters, which the scheme does not support
     pub fn new(
         total_round: usize,
         interpolate_coset: &Vec<Coset<T>>,
         polynomial: Polynomial<T>,
-        oracle: &RandomOracle<T>,
-    ) -> Prover<T> {
-        let interpolate_polynomial =
-            InterpolateValue::new(interpolate_coset[0].fft(polynomial.coefficients().clone()));
+        parameters: FriParameters<H>,
+    ) -> Result<Prover<T, H>, ParameterError> {
+        parameters.reject_hiding()?;
+        Ok(Self::with_parameters(
+            total_round,
+            interpolate_coset,
+            polynomial,
+            parameters,
+        ))
+    }
+
+    // `new` for parameters already checked not to hide
+    pub(crate) fn with_parameters(
+        total_round: usize,
+        interpolate_coset: &[Coset<T>],
+        polynomial: Polynomial<T>,
+        parameters: FriParameters<H>,
+    ) -> Prover<T, H> {
+        let round_starts = parameters.round_starts(total_round);
+        let interpolate_polynomial = InterpolateValue::with_arity(
+            interpolate_coset[0].fft(polynomial.coefficients().clone()),
+            round_starts[1],
+            None,
+        );
+        let mut transcript = Transcript::new(b"fri");
+        transcript.append_root(&interpolate_polynomial.commit());
 
         Prover {
             total_round,
             polynomial,
-            interpolate_cosets: interpolate_coset.clone(),
+            interpolate_cosets: interpolate_coset.to_vec(),
             interpolations: vec![interpolate_polynomial],
-            oracle: oracle.clone(),
-            final_value: None,
+            round_starts,
+            transcript,
+            parameters,
+            final_polynomial: None,
+            pow_nonce: None,
+            deep: None,
+            deep_evaluation: None,
+            deep_proof: None,
         }
     }
 
@@ -44,12 +83,12 @@ impl<T: Field> Prover<T> {
         self.interpolations[0].commit()
     }
 
-    pub fn commit_foldings(&self, verifier: &mut Verifier<T>) {
-        for i in 1..self.total_round {
-            let interpolation = &self.interpolations[i];
+    pub fn commit_foldings(&self, verifier: &mut Verifier<T, H>) {
+        for interpolation in self.interpolations.iter().skip(1) {
             verifier.receive_interpolation_root(interpolation.leave_num(), interpolation.commit());
         }
-        verifier.set_final_value(self.final_value.unwrap());
+        verifier.set_final_polynomial(self.final_polynomial.clone().unwrap());
+        verifier.set_pow_nonce(self.pow_nonce.unwrap());
     }
 
     fn evaluation_next_domain(&self, folding_value: &Vec<T>, round: usize, challenge: T) -> Vec<T> {
@@ -66,51 +105,102 @@ impl<T: Field> Prover<T> {
     }
 
     pub fn prove(&mut self, point: T) -> T {
-        let mut res = None;
-        for i in 0..self.total_round {
-            let challenge = self.oracle.folding_challenges[i];
-            let next_evalutation = if i == 0 {
-                let inv = batch_inverse(
-                    &self.interpolate_cosets[0]
-                        .all_elements()
-                        .into_iter()
-                        .map(|x| x - point)
-                        .collect(),
-                );
-                res = Some(self.polynomial.evaluation_at(point));
-                let v = self.interpolations[0].value.clone();
-                self.evaluation_next_domain(
-                    &v.into_iter()
-                        .zip(inv.into_iter())
-                        .map(|(x, inv)| (x - res.unwrap()) * inv)
-                        .collect(),
-                    i,
-                    challenge,
-                )
+        let res = self.polynomial.evaluation_at(point);
+        self.transcript.append_field(&point);
+        self.transcript.append_field(&res);
+        let values = &self.interpolations[0].value;
+        let mut quotient_values = quotient(values, &self.interpolate_cosets[0], point, res);
+        // DEEP-FRI tests the quotient at an out-of-domain point along with the opening one
+        if self.parameters.deep() {
+            let z = out_of_domain_point(&mut self.transcript, &self.interpolate_cosets[0]);
+            let evaluation = self.polynomial.evaluation_at(z);
+            self.transcript.append_field(&evaluation);
+            let deep = InterpolateValue::with_arity(
+                quotient(values, &self.interpolate_cosets[0], z, evaluation),
+                self.round_starts[1],
+                None,
+            );
+            self.transcript.append_root(&deep.commit());
+            let beta: T = self.transcript.challenge_field();
+            for (x, q) in quotient_values.iter_mut().zip(deep.value.iter()) {
+                *x += beta * *q;
+            }
+            self.deep = Some(deep);
+            self.deep_evaluation = Some(evaluation);
+        }
+        let mut next_evalutation = vec![];
+        let folds = *self.round_starts.last().unwrap();
+        for i in 0..folds {
+            let challenge = self.transcript.challenge_field();
+            let folding_value = if i == 0 {
+                &quotient_values
             } else {
-                self.evaluation_next_domain(&self.interpolations[i].value, i, challenge)
+                &next_evalutation
             };
-            if i < self.total_round - 1 {
-                self.interpolations
-                    .push(InterpolateValue::new(next_evalutation));
+            next_evalutation = self.evaluation_next_domain(folding_value, i, challenge);
+            if i < folds - 1 {
+                // Only the codewords a round starts from are committed
+                if let Some(round) = self.round_starts.iter().position(|x| *x == i + 1) {
+                    let log_arity = self.round_starts[round + 1] - self.round_starts[round];
+                    let interpolation =
+                        InterpolateValue::with_arity(next_evalutation.clone(), log_arity, None);
+                    self.transcript.append_root(&interpolation.commit());
+                    self.interpolations.push(interpolation);
+                }
             } else {
-                self.final_value = Some(next_evalutation[0]);
+                // What is left is sent as the coefficients of a low-degree polynomial
+                let mut coefficients = self.interpolate_cosets[i]
+                    .pow(2)
+                    .ifft(next_evalutation.clone());
+                coefficients.truncate(1 << (self.total_round - folds));
+                self.transcript.append_fields(&coefficients);
+                self.final_polynomial = Some(coefficients);
+                self.pow_nonce = Some(self.transcript.grind(self.parameters.grinding_bits));
             }
         }
-        res.unwrap()
+        res
+    }
+
+    // Runs the whole opening non-interactively and packs every prover message into a proof
+    pub fn open(&mut self, point: T) -> FriProof<T> {
+        let evaluation = self.prove(point);
+        let interpolation_proof = self.query();
+        FriProof {
+            evaluation,
+            folding_roots: self.interpolations[1..]
+                .iter()
+                .map(|x| x.commit())
+                .collect(),
+            final_polynomial: self.final_polynomial.clone().unwrap(),
+            pow_nonce: self.pow_nonce.unwrap(),
+            interpolation_proof,
+            deep: self.deep_opening(),
+        }
     }
 
-    pub fn query(&self) -> Vec<QueryResult<T>> {
+    // The out-of-domain quotient of a DEEP opening, once its queries are answered
+    pub fn deep_opening(&self) -> Option<DeepOpening<T>> {
+        Some(DeepOpening {
+            root: self.deep.as_ref()?.commit(),
+            evaluations: vec![self.deep_evaluation?],
+            proof: self.deep_proof.clone()?,
+        })
+    }
+
+    pub fn query(&mut self) -> Vec<QueryResult<T>> {
         let mut folding_res = vec![];
-        let mut leaf_indices = self.oracle.query_list.clone();
+        let mut leaf_indices = self.transcript.challenge_indices(self.parameters.query_num);
 
-        for i in 0..self.total_round {
-            let len = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices.iter_mut().map(|v| *v % (len >> 1)).collect();
+        for (i, interpolation) in self.interpolations.iter().enumerate() {
+            let len = interpolation.leave_num();
+            leaf_indices = leaf_indices.iter_mut().map(|v| *v % len).collect();
             leaf_indices.sort();
             leaf_indices.dedup();
 
-            folding_res.push(self.interpolations[i].query(&leaf_indices));
+            folding_res.push(interpolation.query(&leaf_indices));
+            if i == 0 {
+                self.deep_proof = self.deep.as_ref().map(|x| x.query(&leaf_indices));
+            }
         }
         folding_res
     }
@@ -0,0 +1,94 @@
+use std::io;
+
+use rand::RngCore;
+use util::{
+    algebra::{field::Field, polynomial::Polynomial},
+    error::{ParameterError, VerifyError},
+    merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    pcs::PolynomialCommitmentScheme,
+};
+
+use crate::{
+    proof::{FriParams, FriProof},
+    prover::Prover,
+    verifier::Verifier,
+};
+
+pub struct Fri;
+
+impl<T: Field, H: MerkleHasher> PolynomialCommitmentScheme<T, H> for Fri {
+    type Params = FriParams<T, H>;
+    type Polynomial = Polynomial<T>;
+    type Point = T;
+    type Commitment = [u8; MERKLE_ROOT_SIZE];
+    type Evaluation = T;
+    type ProverData = Prover<T, H>;
+    type Proof = FriProof<T>;
+
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError> {
+        FriParams::new(variable_num, T::random_element_with(rng), parameters)
+    }
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let prover = Prover::with_parameters(
+            params.variable_num,
+            &params.interpolate_cosets,
+            polynomial,
+            params.parameters(),
+        );
+        (prover.commit_polynomial(), prover)
+    }
+
+    fn open(
+        _params: &Self::Params,
+        mut prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)> {
+        let proof = prover_data.open(*point);
+        Ok((proof.evaluation, proof))
+    }
+
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError> {
+        if proof.evaluation != *value {
+            return Err(VerifyError::ClaimedValue);
+        }
+        if params.variable_num == 0 {
+            return Err(VerifyError::MalformedProof);
+        }
+        let round_starts = params.parameters().round_starts(params.variable_num);
+        if proof.folding_roots.len() != round_starts.len() - 2 {
+            return Err(VerifyError::MalformedProof);
+        }
+        let mut verifier = Verifier::new(
+            params.variable_num,
+            &params.interpolate_cosets,
+            *commitment,
+            *point,
+            params.parameters(),
+        );
+        for (i, root) in proof.folding_roots.iter().enumerate() {
+            let leave_number = params.interpolate_cosets[0].size() >> round_starts[i + 2];
+            verifier.receive_interpolation_root(leave_number, *root);
+        }
+        verifier.set_final_polynomial(proof.final_polynomial.clone());
+        verifier.set_pow_nonce(proof.pow_nonce);
+        if let Some(deep) = &proof.deep {
+            verifier.set_deep(deep.clone());
+        }
+        verifier.verify(&proof.interpolation_proof, proof.evaluation)
+    }
+}
@@ -1,91 +1,208 @@
-use util::merkle_tree::MERKLE_ROOT_SIZE;
-use util::random_oracle::RandomOracle;
+use util::deep::{out_of_domain_point, DeepOpening};
+use util::error::VerifyError;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
+use util::transcript::Transcript;
 use util::{
     algebra::{coset::Coset, field::Field},
+    interpolation::fold_leaf,
     merkle_tree::MerkleTreeVerifier,
+    parameters::FriParameters,
     query_result::QueryResult,
 };
 
 #[derive(Clone)]
-pub struct Verifier<T: Field> {
+pub struct Verifier<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     interpolate_cosets: Vec<Coset<T>>,
-    interpolation_roots: Vec<MerkleTreeVerifier>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    interpolation_roots: Vec<MerkleTreeVerifier<H>>,
+    parameters: FriParameters<H>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
     open_point: T,
+    deep: Option<DeepOpening<T>>,
 }
 
-impl<T: Field> Verifier<T> {
+impl<T: Field, H: MerkleHasher> Verifier<T, H> {
     pub fn new(
         total_round: usize,
         coset: &Vec<Coset<T>>,
         commit: [u8; MERKLE_ROOT_SIZE],
-        oracle: &RandomOracle<T>,
+        open_point: T,
+        parameters: FriParameters<H>,
     ) -> Self {
         Verifier {
             total_round,
             interpolate_cosets: coset.clone(),
-            oracle: oracle.clone(),
-            interpolation_roots: vec![MerkleTreeVerifier::new(coset[0].size() / 2, &commit)],
-            final_value: None,
-            open_point: T::random_element(),
+            parameters,
+            interpolation_roots: vec![MerkleTreeVerifier::new(
+                coset[0].size() >> parameters.round_starts(total_round)[1],
+                &commit,
+            )],
+            final_polynomial: None,
+            pow_nonce: None,
+            open_point,
+            deep: None,
         }
     }
 
-    pub fn get_open_point(&self) -> T {
-        self.open_point
-    }
-
     pub fn receive_interpolation_root(
         &mut self,
         leave_number: usize,
         interpolation_root: [u8; MERKLE_ROOT_SIZE],
     ) {
-        self.interpolation_roots.push(MerkleTreeVerifier {
-            leave_number,
-            merkle_root: interpolation_root,
-        });
+        self.interpolation_roots
+            .push(MerkleTreeVerifier::new(leave_number, &interpolation_root));
     }
 
-    pub fn set_final_value(&mut self, value: T) {
-        self.final_value = Some(value);
+    pub fn set_final_polynomial(&mut self, coefficients: Vec<T>) {
+        self.final_polynomial = Some(coefficients);
     }
 
-    pub fn verify(&self, interpolation_proof: &Vec<QueryResult<T>>, evaluation: T) -> bool {
-        let mut leaf_indices = self.oracle.query_list.clone();
-        for i in 0..self.total_round {
-            let domain_size = self.interpolate_cosets[i].size();
+    pub fn set_pow_nonce(&mut self, nonce: u64) {
+        self.pow_nonce = Some(nonce);
+    }
+
+    pub fn set_deep(&mut self, deep: DeepOpening<T>) {
+        self.deep = Some(deep);
+    }
+
+    pub fn verify(
+        &self,
+        interpolation_proof: &Vec<QueryResult<T>>,
+        evaluation: T,
+    ) -> Result<(), VerifyError> {
+        let final_polynomial = self
+            .final_polynomial
+            .as_ref()
+            .ok_or(VerifyError::MalformedProof)?;
+        let round_starts = self.parameters.round_starts(self.total_round);
+        let rounds = round_starts.len() - 1;
+        let folds = round_starts[rounds];
+        if interpolation_proof.len() != rounds
+            || self.interpolation_roots.len() != rounds
+            || final_polynomial.len() != 1 << (self.total_round - folds)
+            || self.deep.is_some() != self.parameters.deep()
+            || self.deep.as_ref().is_some_and(|x| x.evaluations.len() != 1)
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        // Replay the prover's transcript to derive every challenge
+        let mut transcript = Transcript::new(b"fri");
+        transcript.append_root(&self.interpolation_roots[0].merkle_root);
+        transcript.append_field(&self.open_point);
+        transcript.append_field(&evaluation);
+        // A DEEP opening folds the opening quotient plus beta times the out-of-domain one
+        let mut deep_point = T::from_int(0);
+        let mut beta = T::from_int(0);
+        if let Some(deep) = &self.deep {
+            deep_point = out_of_domain_point(&mut transcript, &self.interpolate_cosets[0]);
+            transcript.append_field(&deep.evaluations[0]);
+            transcript.append_root(&deep.root);
+            beta = transcript.challenge_field();
+        }
+        let mut folding_challenges: Vec<T> = vec![];
+        for r in 0..rounds {
+            for _ in round_starts[r]..round_starts[r + 1] {
+                folding_challenges.push(transcript.challenge_field());
+            }
+            if r < rounds - 1 {
+                transcript.append_root(&self.interpolation_roots[r + 1].merkle_root);
+            }
+        }
+        transcript.append_fields(final_polynomial);
+        let pow_nonce = self.pow_nonce.ok_or(VerifyError::MalformedProof)?;
+        if !transcript.check_grinding(self.parameters.grinding_bits, pow_nonce) {
+            return Err(VerifyError::ProofOfWork);
+        }
+        let mut leaf_indices = transcript.challenge_indices(self.parameters.query_num);
+        // The leaves queried in every round, which place the opened values in the codewords
+        let mut round_indices = vec![];
+        for root in self.interpolation_roots.iter() {
             leaf_indices = leaf_indices
                 .iter_mut()
-                .map(|v| *v % (domain_size >> 1))
+                .map(|v| *v % root.leave_number)
                 .collect();
             leaf_indices.sort();
             leaf_indices.dedup();
+            round_indices.push(leaf_indices.clone());
+        }
+        let values = interpolation_proof
+            .iter()
+            .zip(round_indices.iter())
+            .zip(self.interpolation_roots.iter())
+            .map(|((x, indices), root)| x.open_values(indices, root.leave_number))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let leave_number = self.interpolation_roots[0].leave_number;
+        let deep_values = match &self.deep {
+            Some(deep) => {
+                let root = MerkleTreeVerifier::<H>::new(leave_number, &deep.root);
+                if !deep.proof.verify_merkle_tree(&round_indices[0], &root) {
+                    return Err(VerifyError::MerklePath { round: 0 });
+                }
+                Some(deep.proof.open_values(&round_indices[0], leave_number)?)
+            }
+            None => None,
+        };
+        for r in 0..rounds {
+            let start = round_starts[r];
+            let leaf_indices = &round_indices[r];
+            if !interpolation_proof[r]
+                .verify_merkle_tree(leaf_indices, &self.interpolation_roots[r])
+            {
+                return Err(VerifyError::MerklePath { round: r });
+            }
 
-            interpolation_proof[i].verify_merkle_tree(&leaf_indices, &self.interpolation_roots[i]);
-
-            let challenge = self.oracle.folding_challenges[i];
-            let get_folding_value: Box<dyn Fn(&usize) -> T> = if i == 0 {
+            let get_folding_value: Box<dyn Fn(usize) -> Result<T, VerifyError>> = if r == 0 {
                 Box::new(|x| {
-                    (interpolation_proof[0].proof_values[x] - evaluation)
-                        * (self.interpolate_cosets[0].element_at(*x) - self.open_point).inverse()
+                    let value = values[0].get_value(r, x)?;
+                    let element = self.interpolate_cosets[0].element_at(x);
+                    let res = (value - evaluation) * (element - self.open_point).inverse();
+                    match (&self.deep, &deep_values) {
+                        (Some(deep), Some(deep_values)) => {
+                            let q = deep_values.get_value(r, x)?;
+                            if q * (element - deep_point) != value - deep.evaluations[0] {
+                                return Err(VerifyError::Quotient { index: x });
+                            }
+                            Ok(res + beta * q)
+                        }
+                        _ => Ok(res),
+                    }
                 })
             } else {
-                Box::new(|x| interpolation_proof[i].proof_values[x])
+                Box::new(|x| values[r].get_value(r, x))
             };
-            for j in &leaf_indices {
-                let x = (*get_folding_value)(j);
-                let nx = (*get_folding_value)(&(j + domain_size / 2));
-                let v =
-                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                if i == self.total_round - 1 {
-                    assert_eq!(v, self.final_value.unwrap());
-                } else {
-                    assert_eq!(v, interpolation_proof[i + 1].proof_values[j]);
+            let leave_number = self.interpolation_roots[r].leave_number;
+            for j in leaf_indices {
+                let j = *j;
+                let coset = (0..1 << (round_starts[r + 1] - start))
+                    .map(|t| get_folding_value(j + t * leave_number))
+                    .collect::<Result<Vec<_>, VerifyError>>()?;
+                let v = fold_leaf(
+                    &coset,
+                    j,
+                    self.interpolate_cosets[start].size(),
+                    |step, i, x, nx| {
+                        let coset = &self.interpolate_cosets[start + step];
+                        x + nx
+                            + folding_challenges[start + step] * (x - nx) * coset.element_inv_at(i)
+                    },
+                );
+                if r == rounds - 1 {
+                    // The last folding lands on the domain squaring the one before it
+                    let x = self.interpolate_cosets[folds - 1].element_at(j).pow(2);
+                    let final_value = final_polynomial
+                        .iter()
+                        .rev()
+                        .fold(T::from_int(0), |acc, c| acc * x + *c);
+                    if v != final_value {
+                        return Err(VerifyError::FinalValue { index: j });
+                    }
+                } else if v != values[r + 1].get_value(r + 1, j)? {
+                    return Err(VerifyError::FoldConsistency { round: r, index: j });
                 }
             }
         }
-        true
+        Ok(())
     }
 }
@@ -3,27 +3,24 @@ extern crate criterion;
 use criterion::*;
 
 use gemini_fri::{prover::FriProver, verifier::FriVerifier};
-use util::{
-    algebra::{
-        coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
-        polynomial::MultilinearPolynomial,
-    },
-    random_oracle::RandomOracle,
+use util::algebra::{
+    coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
+    polynomial::MultilinearPolynomial,
 };
 
-use util::{CODE_RATE, SECURITY_BITS};
+use util::parameters::FriParameters;
 
 fn commit(variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
     prover.commit_first_polynomial();
 }
 
@@ -36,19 +33,27 @@ fn bench_commit(c: &mut Criterion) {
 }
 
 fn open(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
     let commitment = prover.commit_first_polynomial();
-    let mut verifier = FriVerifier::new(variable_num, &interpolate_cosets, commitment, &oracle);
-    let open_point = verifier.get_open_point();
+    let open_point = (0..variable_num)
+        .map(|_| Mersenne61Ext::random_element())
+        .collect();
+    let verifier = FriVerifier::new(
+        variable_num,
+        &interpolate_cosets,
+        commitment,
+        &open_point,
+        parameters,
+    );
     criterion.bench_function(&format!("gemini prove {}", variable_num), |b| {
         b.iter_batched(
             || (prover.clone(), verifier.clone()),
@@ -71,19 +76,28 @@ fn bench_open(c: &mut Criterion) {
 }
 
 fn verify(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let mut prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let mut prover =
+        FriProver::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
     let commitment = prover.commit_first_polynomial();
-    let mut verifier = FriVerifier::new(variable_num, &interpolate_cosets, commitment, &oracle);
-    let open_point = verifier.get_open_point();
+    let open_point = (0..variable_num)
+        .map(|_| Mersenne61Ext::random_element())
+        .collect();
+    let mut verifier = FriVerifier::new(
+        variable_num,
+        &interpolate_cosets,
+        commitment,
+        &open_point,
+        parameters,
+    );
     prover.commit_functions(&mut verifier, &open_point);
     let tuples = prover.compute_tuples();
     prover.prove();
@@ -94,7 +108,7 @@ fn verify(criterion: &mut Criterion, variable_num: usize) {
             || verifier.clone(),
             |mut v| {
                 v.set_tuples(&tuples);
-                assert!(v.verify(&folding_proofs, &function_proofs));
+                assert!(v.verify(&folding_proofs, &function_proofs).is_ok());
             },
             BatchSize::SmallInput,
         )
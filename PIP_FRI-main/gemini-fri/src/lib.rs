@@ -1,4 +1,6 @@
+pub mod proof;
 pub mod prover;
+pub mod scheme;
 pub mod verifier;
 
 use util::algebra::field::Field;
@@ -21,38 +23,51 @@ impl<T: Field> Tuple<T> {
 mod tests {
     use std::mem::size_of;
 
-    use crate::{prover::FriProver, verifier::FriVerifier, Tuple};
+    use crate::{
+        proof::GeminiParams, prover::FriProver, scheme::Gemini, verifier::FriVerifier, Tuple,
+    };
     use util::{
         algebra::{
-            coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
+            coset::Coset, field::fp64::Fp64, field::mersenne61_ext::Mersenne61Ext, field::Field,
             polynomial::MultilinearPolynomial,
         },
-        merkle_tree::MERKLE_ROOT_SIZE,
-        random_oracle::RandomOracle,
-        CODE_RATE, SECURITY_BITS,
+        error::{ParameterError, VerifyError},
+        merkle_tree::{Keccak256Algorithm, MerkleHasher, Sha256Algorithm, MERKLE_ROOT_SIZE},
+        parameters::FriParameters,
+        pcs::PolynomialCommitmentScheme,
+        poseidon::PoseidonAlgorithm,
     };
 
     fn output_proof_size(variable_num: usize) -> usize {
+        let parameters = FriParameters::default();
         let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
         let mut interpolate_cosets = vec![Coset::new(
-            1 << (variable_num + CODE_RATE),
+            1 << (variable_num + parameters.log_blowup),
             Mersenne61Ext::random_element(),
         )];
         for i in 1..variable_num {
             interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
         }
-        let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-        let mut prover = FriProver::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+        let mut prover =
+            FriProver::new(variable_num, &interpolate_cosets, polynomial, parameters).unwrap();
         let commitment = prover.commit_first_polynomial();
-        let mut verifier = FriVerifier::new(variable_num, &interpolate_cosets, commitment, &oracle);
-        let open_point = verifier.get_open_point();
+        let open_point = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let mut verifier = FriVerifier::new(
+            variable_num,
+            &interpolate_cosets,
+            commitment,
+            &open_point,
+            parameters,
+        );
         prover.commit_functions(&mut verifier, &open_point);
         let tuples = prover.compute_tuples();
         prover.prove();
         prover.commit_foldings(&mut verifier);
         let (folding_proofs, function_proofs) = prover.query();
         verifier.set_tuples(&tuples);
-        assert!(verifier.verify(&folding_proofs, &function_proofs));
+        assert!(verifier.verify(&folding_proofs, &function_proofs).is_ok());
         tuples.len() * size_of::<Tuple<Mersenne61Ext>>()
             + variable_num * MERKLE_ROOT_SIZE * 2
             + 2 * size_of::<Mersenne61Ext>()
@@ -73,4 +88,60 @@ mod tests {
             );
         }
     }
+
+    fn commitment_scheme<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let params: GeminiParams<Mersenne61Ext, H> =
+            Gemini::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let (commitment, prover_data) = Gemini::commit(&params, polynomial.clone());
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Gemini::open(&params, prover_data, &point).unwrap();
+        assert_eq!(value, polynomial.evaluate(&point));
+        assert!(Gemini::verify(&params, &commitment, &point, &value, &proof).is_ok());
+        // A caller that expects another value rejects the proof
+        let wrong_value = value + Mersenne61Ext::from_int(1);
+        assert_eq!(
+            Gemini::verify(&params, &commitment, &point, &wrong_value, &proof),
+            Err(VerifyError::ClaimedValue)
+        );
+    }
+
+    #[test]
+    fn test_commitment_scheme() {
+        commitment_scheme(FriParameters::default());
+    }
+
+    #[test]
+    fn test_hiding_rejected() {
+        let parameters = FriParameters::default().with_hiding();
+        let params: Result<GeminiParams<Mersenne61Ext>, _> = Gemini::setup(8, parameters);
+        assert_eq!(params.err(), Some(ParameterError::HidingUnsupported));
+    }
+
+    #[test]
+    fn test_folding_arity() {
+        for log_arity in 2..5 {
+            commitment_scheme(FriParameters::default().with_folding_arity(log_arity));
+        }
+    }
+
+    #[test]
+    fn test_final_polynomial() {
+        for log_final_degree in [1, 3, 8] {
+            let parameters = FriParameters::default().with_final_degree(log_final_degree);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_folding_arity(2));
+        }
+    }
+
+    #[test]
+    fn test_merkle_hashers() {
+        let parameters = FriParameters::default();
+        commitment_scheme(parameters.with_hasher::<Sha256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<Keccak256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<PoseidonAlgorithm<Fp64>>());
+    }
 }
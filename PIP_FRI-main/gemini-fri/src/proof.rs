@@ -0,0 +1,62 @@
+use super::Tuple;
+use util::{
+    algebra::{coset::Coset, field::Field},
+    error::ParameterError,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    query_result::QueryResult,
+};
+
+#[derive(Clone)]
+pub struct GeminiParams<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    pub variable_num: usize,
+    pub interpolate_cosets: Vec<Coset<T>>,
+    // Private, so that the parameters stay the checked ones
+    parameters: FriParameters<H>,
+}
+
+impl<T: Field, H: MerkleHasher> GeminiParams<T, H> {
+    pub fn new(
+        variable_num: usize,
+        shift: T,
+        parameters: FriParameters<H>,
+    ) -> Result<Self, ParameterError> {
+        // Nothing in a gemini opening is masked
+        parameters.reject_hiding()?;
+        let mut interpolate_cosets = vec![Coset::new(
+            1 << (variable_num + parameters.log_blowup),
+            shift,
+        )];
+        for i in 1..variable_num {
+            interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
+        }
+        Ok(GeminiParams {
+            variable_num,
+            interpolate_cosets,
+            parameters,
+        })
+    }
+
+    pub fn parameters(&self) -> FriParameters<H> {
+        self.parameters
+    }
+}
+
+// Everything the prover sends in a gemini opening, in transcript order
+#[derive(Clone)]
+pub struct GeminiProof<T: Field> {
+    pub function_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub tuples: Vec<Tuple<T>>,
+    pub folding_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub final_polynomial: Vec<T>,
+    pub pow_nonce: u64,
+    pub folding_proofs: Vec<QueryResult<T>>,
+    pub function_proofs: Vec<QueryResult<T>>,
+}
+
+impl<T: Field> GeminiProof<T> {
+    // The last folded polynomial is the constant f(open_point)
+    pub fn evaluation(&self) -> Option<T> {
+        self.tuples.last().map(|x| x.c)
+    }
+}
@@ -1,8 +1,11 @@
+use super::proof::GeminiProof;
 use super::verifier::FriVerifier;
 use util::{
     algebra::{field, polynomial::MultilinearPolynomial},
-    merkle_tree::MERKLE_ROOT_SIZE,
-    random_oracle::RandomOracle,
+    error::ParameterError,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    transcript::Transcript,
 };
 
 use util::query_result::QueryResult;
@@ -17,17 +20,23 @@ use util::{
 use super::Tuple;
 
 #[derive(Clone)]
-struct InterpolateValue<T: Field> {
+struct InterpolateValue<T: Field, H: MerkleHasher> {
     value: Vec<T>,
-    merkle_tree: MerkleTreeProver,
+    merkle_tree: MerkleTreeProver<H>,
 }
 
-impl<T: Field> InterpolateValue<T> {
-    fn new(value: Vec<T>) -> Self {
-        let len = value.len() / 2;
+impl<T: Field, H: MerkleHasher> InterpolateValue<T, H> {
+    // Leaf i holds value[i + t * len] for t < 2^log_arity, the coset a round folds into one
+    fn new(value: Vec<T>, log_arity: usize) -> Self {
+        let len = value.len() >> log_arity;
         let merkle_tree = MerkleTreeProver::new(
             (0..len)
-                .map(|i| as_bytes_vec(&[value[i], value[i + len]]))
+                .map(|i| {
+                    let leaf = (0..1 << log_arity)
+                        .map(|t| value[i + t * len])
+                        .collect::<Vec<_>>();
+                    as_bytes_vec(&leaf)
+                })
                 .collect(),
         );
         Self { value, merkle_tree }
@@ -41,74 +50,102 @@ impl<T: Field> InterpolateValue<T> {
         self.merkle_tree.commit()
     }
 
-    fn query(&self, leaf_indices: &Vec<usize>) -> QueryResult<T> {
-        let len = self.merkle_tree.leave_num();
-        let proof_values = leaf_indices
-            .iter()
-            .flat_map(|j| [(*j, self.value[*j]), (*j + len, self.value[*j + len])])
-            .collect();
-        let proof_bytes = self.merkle_tree.open(&leaf_indices);
-        QueryResult {
-            proof_bytes,
-            proof_values,
-        }
+    fn query(&self, leaf_indices: &[usize]) -> QueryResult<T> {
+        QueryResult::new(
+            leaf_indices,
+            self.merkle_tree.leave_num(),
+            self.value.len() / self.merkle_tree.leave_num(),
+            |i| self.value[i],
+            self.merkle_tree.open(leaf_indices),
+            vec![],
+        )
     }
 }
 
 #[derive(Clone)]
-pub struct Function<T: Field> {
-    interpolation: InterpolateValue<T>,
+pub struct Function<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    interpolation: InterpolateValue<T, H>,
     evaluations: Vec<(T, T)>,
 }
 
-impl<T: Field> Function<T> {
-    pub fn new(value: Vec<T>, evaluations: Vec<(T, T)>) -> Self {
+impl<T: Field, H: MerkleHasher> Function<T, H> {
+    pub fn new(value: Vec<T>, log_arity: usize, evaluations: Vec<(T, T)>) -> Self {
         Function {
-            interpolation: InterpolateValue::new(value),
+            interpolation: InterpolateValue::new(value, log_arity),
             evaluations,
         }
     }
 }
 
 #[derive(Clone)]
-pub struct FriProver<T: Field> {
+pub struct FriProver<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     interpolate_cosets: Vec<Coset<T>>,
-    functions: Vec<Function<T>>,
+    functions: Vec<Function<T, H>>,
     polynomials: Vec<MultilinearPolynomial<T>>,
-    foldings: Vec<InterpolateValue<T>>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    // The foldings a round starts from, the only ones committed
+    foldings: Vec<InterpolateValue<T, H>>,
+    round_starts: Vec<usize>,
+    transcript: Transcript,
+    parameters: FriParameters<H>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
 }
 
-impl<T: Field> FriProver<T> {
+impl<T: Field, H: MerkleHasher> FriProver<T, H> {
+    // Fails on hiding parameters, which the scheme does not support
     pub fn new(
         total_round: usize,
         interpolate_coset: &Vec<Coset<T>>,
         polynomial: MultilinearPolynomial<T>,
-        oracle: &RandomOracle<T>,
-    ) -> FriProver<T> {
+        parameters: FriParameters<H>,
+    ) -> Result<FriProver<T, H>, ParameterError> {
+        parameters.reject_hiding()?;
+        Ok(Self::with_parameters(
+            total_round,
+            interpolate_coset,
+            polynomial,
+            parameters,
+        ))
+    }
+
+    // `new` for parameters already checked not to hide
+    pub(crate) fn with_parameters(
+        total_round: usize,
+        interpolate_coset: &[Coset<T>],
+        polynomial: MultilinearPolynomial<T>,
+        parameters: FriParameters<H>,
+    ) -> FriProver<T, H> {
+        let round_starts = parameters.round_starts(total_round);
         let interpolation = interpolate_coset[0].fft(polynomial.coefficients().clone());
+        let function = Function::new(interpolation, round_starts[1], vec![]);
+        let mut transcript = Transcript::new(b"gemini");
+        transcript.append_root(&function.interpolation.commit());
         FriProver {
             total_round,
-            interpolate_cosets: interpolate_coset.clone(),
-            functions: vec![Function::new(interpolation, vec![])],
+            interpolate_cosets: interpolate_coset.to_vec(),
+            functions: vec![function],
             polynomials: vec![polynomial],
             foldings: vec![],
-            oracle: oracle.clone(),
-            final_value: None,
+            round_starts,
+            transcript,
+            parameters,
+            final_polynomial: None,
+            pow_nonce: None,
         }
     }
 
     pub fn compute_tuples(&mut self) -> Vec<Tuple<T>> {
-        let beta = self.oracle.beta;
+        let beta: T = self.transcript.challenge_field();
         let mut tuples = vec![];
         for i in 0..self.total_round {
-            tuples.push(Tuple {
+            let tuple = Tuple {
                 a: self.polynomials[i].evaluate_as_polynomial(beta),
                 b: self.polynomials[i].evaluate_as_polynomial(-beta),
                 c: self.polynomials[i + 1].evaluate_as_polynomial(beta * beta),
-            });
+            };
+            self.transcript.append_fields(&[tuple.a, tuple.b, tuple.c]);
+            tuples.push(tuple);
         }
         self.functions[0].evaluations = vec![(beta, tuples[0].a), (-beta, tuples[0].b)];
         for i in 1..tuples.len() {
@@ -125,8 +162,16 @@ impl<T: Field> FriProver<T> {
         self.functions[0].interpolation.commit()
     }
 
-    pub fn commit_functions(&mut self, verifier: &mut FriVerifier<T>, open_point: &Vec<T>) {
+    pub fn commit_functions(&mut self, verifier: &mut FriVerifier<T, H>, open_point: &Vec<T>) {
+        self.compute_functions(open_point);
+        for function in self.functions.iter().skip(1) {
+            verifier.append_function(function.interpolation.commit());
+        }
+    }
+
+    fn compute_functions(&mut self, open_point: &Vec<T>) {
         assert_eq!(open_point.len(), self.total_round);
+        self.transcript.append_fields(open_point);
         for i in 0..self.total_round {
             let last = self.polynomials.last().unwrap();
             let next_polynomial = last.folding(open_point[i]);
@@ -135,21 +180,45 @@ impl<T: Field> FriProver<T> {
         for i in 1..self.total_round {
             self.functions.push(Function::new(
                 self.interpolate_cosets[0].fft(self.polynomials[i].coefficients().clone()),
+                self.round_starts[1],
                 vec![],
             ));
-            verifier.append_function(self.functions[i].interpolation.commit());
+            let root = self.functions[i].interpolation.commit();
+            self.transcript.append_root(&root);
         }
     }
 
-    pub fn commit_foldings(&self, verifier: &mut FriVerifier<T>) {
-        for i in 0..(self.total_round - 1) {
-            verifier.receive_folding_root(self.foldings[i].leave_num(), self.foldings[i].commit());
+    // Runs the whole opening non-interactively and packs every prover message into a proof
+    pub fn open(&mut self, open_point: &Vec<T>) -> GeminiProof<T> {
+        self.compute_functions(open_point);
+        let tuples = self.compute_tuples();
+        self.prove();
+        let (folding_proofs, function_proofs) = self.query();
+        GeminiProof {
+            function_roots: self
+                .functions
+                .iter()
+                .skip(1)
+                .map(|x| x.interpolation.commit())
+                .collect(),
+            tuples,
+            folding_roots: self.foldings.iter().map(|x| x.commit()).collect(),
+            final_polynomial: self.final_polynomial.clone().unwrap(),
+            pow_nonce: self.pow_nonce.unwrap(),
+            folding_proofs,
+            function_proofs,
+        }
+    }
+
+    pub fn commit_foldings(&self, verifier: &mut FriVerifier<T, H>) {
+        for folding in &self.foldings {
+            verifier.receive_folding_root(folding.leave_num(), folding.commit());
         }
-        verifier.set_final_value(self.final_value.unwrap())
+        verifier.set_final_polynomial(self.final_polynomial.clone().unwrap());
+        verifier.set_pow_nonce(self.pow_nonce.unwrap());
     }
 
-    fn initial_interpolation(&self) -> Vec<T> {
-        let rlc = self.oracle.rlc;
+    fn initial_interpolation(&self, rlc: T) -> Vec<T> {
         let mut acc = rlc;
         let mut res = self.functions[0].interpolation.value.clone();
         for i in 0..self.functions.len() {
@@ -177,12 +246,19 @@ impl<T: Field> FriProver<T> {
         res
     }
 
-    fn evaluation_next_domain(&self, round: usize, challenge: T) -> Vec<T> {
+    // Folds the random combination in round 0 and `last_folding` after it
+    fn evaluation_next_domain(
+        &self,
+        round: usize,
+        challenge: T,
+        rlc: T,
+        last_folding: &[T],
+    ) -> Vec<T> {
         let mut res = vec![];
         let coset = &self.interpolate_cosets[round];
         let len = coset.size();
         if round == 0 {
-            let function = self.initial_interpolation();
+            let function = self.initial_interpolation(rlc);
             for i in 0..(len / 2) {
                 let x = function[i];
                 let nx = function[i + len / 2];
@@ -190,7 +266,6 @@ impl<T: Field> FriProver<T> {
                 res.push(new_v);
             }
         } else {
-            let last_folding = &self.foldings.last().unwrap().value;
             for i in 0..(len / 2) {
                 let x = last_folding[i];
                 let nx = last_folding[i + len / 2];
@@ -202,26 +277,47 @@ impl<T: Field> FriProver<T> {
     }
 
     pub fn prove(&mut self) {
-        for i in 0..self.total_round {
-            let challenge = self.oracle.folding_challenges[i];
-            let next_evalutation = self.evaluation_next_domain(i, challenge);
-            if i < self.total_round - 1 {
-                let interpolate_value = InterpolateValue::new(next_evalutation);
-                self.foldings.push(interpolate_value);
+        let rlc = self.transcript.challenge_field();
+        let mut next_evalutation = vec![];
+        let folds = *self.round_starts.last().unwrap();
+        for i in 0..folds {
+            let challenge = self.transcript.challenge_field();
+            next_evalutation = self.evaluation_next_domain(i, challenge, rlc, &next_evalutation);
+            if i < folds - 1 {
+                // Only the foldings a round starts from are committed
+                if let Some(round) = self.round_starts.iter().position(|x| *x == i + 1) {
+                    let interpolate_value = InterpolateValue::new(
+                        next_evalutation.clone(),
+                        self.round_starts[round + 1] - self.round_starts[round],
+                    );
+                    self.transcript.append_root(&interpolate_value.commit());
+                    self.foldings.push(interpolate_value);
+                }
             } else {
-                self.final_value = Some(next_evalutation[0]);
+                // What is left is sent as the coefficients of a low-degree polynomial
+                let mut coefficients = self.interpolate_cosets[i]
+                    .pow(2)
+                    .ifft(next_evalutation.clone());
+                coefficients.truncate(1 << (self.total_round - folds));
+                self.transcript.append_fields(&coefficients);
+                self.final_polynomial = Some(coefficients);
+                self.pow_nonce = Some(self.transcript.grind(self.parameters.grinding_bits));
             }
         }
     }
 
-    pub fn query(&self) -> (Vec<QueryResult<T>>, Vec<QueryResult<T>>) {
+    pub fn query(&mut self) -> (Vec<QueryResult<T>>, Vec<QueryResult<T>>) {
         let mut folding_res = vec![];
         let mut functions_res = None;
-        let mut leaf_indices = self.oracle.query_list.clone();
+        let mut leaf_indices = self.transcript.challenge_indices(self.parameters.query_num);
 
-        for i in 0..self.total_round {
-            let len = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices.iter_mut().map(|v| *v % (len >> 1)).collect();
+        for i in 0..self.round_starts.len() - 1 {
+            let len = if i == 0 {
+                self.functions[0].interpolation.leave_num()
+            } else {
+                self.foldings[i - 1].leave_num()
+            };
+            leaf_indices = leaf_indices.iter_mut().map(|v| *v % len).collect();
             leaf_indices.sort();
             leaf_indices.dedup();
 
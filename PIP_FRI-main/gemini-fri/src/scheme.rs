@@ -0,0 +1,97 @@
+use std::io;
+
+use rand::RngCore;
+use util::{
+    algebra::{field::Field, polynomial::MultilinearPolynomial},
+    error::{ParameterError, VerifyError},
+    merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    pcs::PolynomialCommitmentScheme,
+};
+
+use crate::{
+    proof::{GeminiParams, GeminiProof},
+    prover::FriProver,
+    verifier::FriVerifier,
+};
+
+pub struct Gemini;
+
+impl<T: Field, H: MerkleHasher> PolynomialCommitmentScheme<T, H> for Gemini {
+    type Params = GeminiParams<T, H>;
+    type Polynomial = MultilinearPolynomial<T>;
+    type Point = Vec<T>;
+    type Commitment = [u8; MERKLE_ROOT_SIZE];
+    type Evaluation = T;
+    type ProverData = FriProver<T, H>;
+    type Proof = GeminiProof<T>;
+
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError> {
+        GeminiParams::new(variable_num, T::random_element_with(rng), parameters)
+    }
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let prover = FriProver::with_parameters(
+            params.variable_num,
+            &params.interpolate_cosets,
+            polynomial,
+            params.parameters(),
+        );
+        (prover.commit_first_polynomial(), prover)
+    }
+
+    fn open(
+        _params: &Self::Params,
+        mut prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)> {
+        let proof = prover_data.open(point);
+        Ok((proof.evaluation().unwrap(), proof))
+    }
+
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError> {
+        if proof.evaluation() != Some(*value) {
+            return Err(VerifyError::ClaimedValue);
+        }
+        let round_starts = params.parameters().round_starts(params.variable_num);
+        if params.variable_num == 0
+            || point.len() != params.variable_num
+            || proof.folding_roots.len() != round_starts.len() - 2
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+        let mut verifier = FriVerifier::new(
+            params.variable_num,
+            &params.interpolate_cosets,
+            *commitment,
+            point,
+            params.parameters(),
+        );
+        for root in &proof.function_roots {
+            verifier.append_function(*root);
+        }
+        verifier.set_tuples(&proof.tuples);
+        for (i, root) in proof.folding_roots.iter().enumerate() {
+            verifier.receive_folding_root(
+                params.interpolate_cosets[0].size() >> round_starts[i + 2],
+                *root,
+            );
+        }
+        verifier.set_final_polynomial(proof.final_polynomial.clone());
+        verifier.set_pow_nonce(proof.pow_nonce);
+        verifier.verify(&proof.folding_proofs, &proof.function_proofs)
+    }
+}
@@ -1,75 +1,66 @@
 use super::Tuple;
-use util::merkle_tree::MERKLE_ROOT_SIZE;
+use util::error::VerifyError;
+use util::interpolation::fold_leaf;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
 use util::query_result::QueryResult;
-use util::random_oracle::RandomOracle;
+use util::transcript::Transcript;
 use util::{
     algebra::{coset::Coset, field::Field},
     merkle_tree::MerkleTreeVerifier,
+    parameters::FriParameters,
 };
 
 #[derive(Clone)]
-pub struct FriVerifier<T: Field> {
+pub struct FriVerifier<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     interpolate_cosets: Vec<Coset<T>>,
-    function_root: Vec<(MerkleTreeVerifier, Vec<(T, T)>)>,
-    folding_root: Vec<MerkleTreeVerifier>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
-    open_point: Option<Vec<T>>,
+    function_root: Vec<MerkleTreeVerifier<H>>,
+    folding_root: Vec<MerkleTreeVerifier<H>>,
+    parameters: FriParameters<H>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
+    open_point: Vec<T>,
+    tuples: Vec<Tuple<T>>,
 }
 
-impl<T: Field> FriVerifier<T> {
+impl<T: Field, H: MerkleHasher> FriVerifier<T, H> {
     pub fn new(
         total_round: usize,
         coset: &Vec<Coset<T>>,
         polynomial_commitment: [u8; MERKLE_ROOT_SIZE],
-        oracle: &RandomOracle<T>,
+        open_point: &Vec<T>,
+        parameters: FriParameters<H>,
     ) -> Self {
+        // The query count of DEEP parameters is only sound with the out-of-domain sample
+        assert!(
+            !parameters.deep(),
+            "DEEP-FRI is not supported by this scheme"
+        );
         FriVerifier {
             total_round,
             interpolate_cosets: coset.clone(),
-            function_root: vec![(
-                MerkleTreeVerifier {
-                    leave_number: coset[0].size() / 2,
-                    merkle_root: polynomial_commitment,
-                },
-                vec![],
+            function_root: vec![MerkleTreeVerifier::new(
+                coset[0].size() >> parameters.round_starts(total_round)[1],
+                &polynomial_commitment,
             )],
             folding_root: vec![],
-            oracle: oracle.clone(),
-            final_value: None,
-            open_point: None,
+            parameters,
+            final_polynomial: None,
+            pow_nonce: None,
+            open_point: open_point.clone(),
+            tuples: vec![],
         }
     }
 
-    pub fn get_open_point(&mut self) -> Vec<T> {
-        let point = (0..self.total_round)
-            .map(|_| T::random_element())
-            .collect::<Vec<T>>();
-        self.open_point = Some(point.clone());
-        point
-    }
-
     pub fn append_function(&mut self, function_root: [u8; MERKLE_ROOT_SIZE]) {
-        self.function_root.push((
-            MerkleTreeVerifier {
-                merkle_root: function_root,
-                leave_number: self.interpolate_cosets[0].size() / 2,
-            },
-            vec![],
+        self.function_root.push(MerkleTreeVerifier::new(
+            self.function_root[0].leave_number,
+            &function_root,
         ));
     }
 
     pub fn set_tuples(&mut self, tuples: &Vec<Tuple<T>>) {
-        let beta = self.oracle.beta;
-        for i in 0..tuples.len() {
-            assert!(tuples[i].verify(beta, self.open_point.as_ref().unwrap()[i]));
-            self.function_root[i].1.push((beta, tuples[i].a));
-            self.function_root[i].1.push((-beta, tuples[i].b));
-            if i < tuples.len() - 1 {
-                self.function_root[i + 1].1.push((beta * beta, tuples[i].c))
-            }
-        }
+        self.tuples = tuples.clone();
     }
 
     pub fn receive_folding_root(
@@ -77,80 +68,171 @@ impl<T: Field> FriVerifier<T> {
         leave_number: usize,
         folding_root: [u8; MERKLE_ROOT_SIZE],
     ) {
-        self.folding_root.push(MerkleTreeVerifier {
-            leave_number,
-            merkle_root: folding_root,
-        });
+        self.folding_root
+            .push(MerkleTreeVerifier::new(leave_number, &folding_root));
+    }
+
+    pub fn set_final_polynomial(&mut self, coefficients: Vec<T>) {
+        self.final_polynomial = Some(coefficients);
     }
 
-    pub fn set_final_value(&mut self, value: T) {
-        assert_ne!(value, T::from_int(0));
-        self.final_value = Some(value);
+    pub fn set_pow_nonce(&mut self, nonce: u64) {
+        self.pow_nonce = Some(nonce);
     }
 
     pub fn verify(
         &self,
         folding_proofs: &Vec<QueryResult<T>>,
         function_proofs: &Vec<QueryResult<T>>,
-    ) -> bool {
-        let mut leaf_indices = self.oracle.query_list.clone();
-        let rlc = self.oracle.rlc;
-        for i in 0..self.total_round {
-            let domain_size = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices
-                .iter_mut()
-                .map(|v| *v % (domain_size >> 1))
-                .collect();
+    ) -> Result<(), VerifyError> {
+        let final_polynomial = self
+            .final_polynomial
+            .as_ref()
+            .ok_or(VerifyError::MalformedProof)?;
+        let round_starts = self.parameters.round_starts(self.total_round);
+        let rounds = round_starts.len() - 1;
+        let folds = round_starts[rounds];
+        if self.total_round == 0
+            || final_polynomial.len() != 1 << (self.total_round - folds)
+            || folding_proofs.len() != rounds - 1
+            || self.folding_root.len() != rounds - 1
+            || function_proofs.len() != self.total_round
+            || self.function_root.len() != self.total_round
+            || self.tuples.len() != self.total_round
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        // Replay the prover's transcript to derive every challenge
+        let mut transcript = Transcript::new(b"gemini");
+        transcript.append_root(&self.function_root[0].merkle_root);
+        transcript.append_fields(&self.open_point);
+        for root in self.function_root.iter().skip(1) {
+            transcript.append_root(&root.merkle_root);
+        }
+        let beta: T = transcript.challenge_field();
+        for tuple in self.tuples.iter() {
+            transcript.append_fields(&[tuple.a, tuple.b, tuple.c]);
+        }
+        let rlc: T = transcript.challenge_field();
+        let mut folding_challenges: Vec<T> = vec![];
+        for r in 0..rounds {
+            for _ in round_starts[r]..round_starts[r + 1] {
+                folding_challenges.push(transcript.challenge_field());
+            }
+            if r < rounds - 1 {
+                transcript.append_root(&self.folding_root[r].merkle_root);
+            }
+        }
+        transcript.append_fields(final_polynomial);
+        let pow_nonce = self.pow_nonce.ok_or(VerifyError::MalformedProof)?;
+        if !transcript.check_grinding(self.parameters.grinding_bits, pow_nonce) {
+            return Err(VerifyError::ProofOfWork);
+        }
+        let mut leaf_indices = transcript.challenge_indices(self.parameters.query_num);
+
+        let mut evaluations = vec![vec![]; self.function_root.len()];
+        for i in 0..self.tuples.len() {
+            if !self.tuples[i].verify(beta, self.open_point[i]) {
+                return Err(VerifyError::Tuple { round: i });
+            }
+            evaluations[i].push((beta, self.tuples[i].a));
+            evaluations[i].push((-beta, self.tuples[i].b));
+            if i < self.tuples.len() - 1 {
+                evaluations[i + 1].push((beta * beta, self.tuples[i].c))
+            }
+        }
+        // The leaves queried in every round, which place the opened values in the codewords
+        let leave_numbers = std::iter::once(&self.function_root[0])
+            .chain(self.folding_root.iter())
+            .map(|x| x.leave_number)
+            .collect::<Vec<_>>();
+        let mut round_indices = vec![];
+        for leave_number in leave_numbers.iter() {
+            leaf_indices = leaf_indices.iter_mut().map(|v| *v % leave_number).collect();
             leaf_indices.sort();
             leaf_indices.dedup();
-
-            if i == 0 {
-                for j in 0..function_proofs.len() {
-                    assert!(function_proofs[j]
-                        .verify_merkle_tree(&leaf_indices, &self.function_root[j].0));
-                }
+            round_indices.push(leaf_indices.clone());
+        }
+        let function_values = function_proofs
+            .iter()
+            .zip(self.function_root.iter())
+            .map(|(x, root)| x.open_values(&round_indices[0], root.leave_number))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let folding_values = folding_proofs
+            .iter()
+            .zip(round_indices[1..].iter())
+            .zip(self.folding_root.iter())
+            .map(|((x, indices), root)| x.open_values(indices, root.leave_number))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        for r in 0..rounds {
+            let start = round_starts[r];
+            let leaf_indices = &round_indices[r];
+            let merkle_ok = if r == 0 {
+                function_proofs
+                    .iter()
+                    .zip(self.function_root.iter())
+                    .all(|(x, v)| x.verify_merkle_tree(leaf_indices, v))
             } else {
-                folding_proofs[i - 1].verify_merkle_tree(&leaf_indices, &self.folding_root[i - 1]);
+                folding_proofs[r - 1].verify_merkle_tree(leaf_indices, &self.folding_root[r - 1])
+            };
+            if !merkle_ok {
+                return Err(VerifyError::MerklePath { round: r });
             }
 
-            let challenge = self.oracle.folding_challenges[i];
-            let get_folding_value = |index: &usize| {
-                if i == 0 {
+            let get_folding_value = |index: usize| {
+                if r == 0 {
                     let mut tmp_rlc = T::from_int(1);
-                    let mut res = T::from_int(0); //function_proofs[0].proof_values[index];
+                    let mut res = T::from_int(0);
                     for f in 0..self.function_root.len() {
-                        let this_v = function_proofs[f].proof_values[index];
+                        let this_v = function_values[f].get_value(r, index)?;
                         res += this_v * tmp_rlc;
                         tmp_rlc *= rlc;
-                        for (x, y) in &self.function_root[f].1 {
+                        for (x, y) in &evaluations[f] {
                             res += tmp_rlc
                                 * (this_v - *y)
-                                * (self.interpolate_cosets[0].element_at(*index) - *x).inverse();
+                                * (self.interpolate_cosets[0].element_at(index) - *x).inverse();
                             tmp_rlc *= rlc
                         }
                     }
-                    res
+                    Ok(res)
                 } else {
-                    folding_proofs[i - 1].proof_values[index]
+                    folding_values[r - 1].get_value(r, index)
                 }
             };
 
-            for j in &leaf_indices {
-                let x = get_folding_value(j);
-                let nx = get_folding_value(&(j + domain_size / 2));
-                let v =
-                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                if i < self.total_round - 1 {
-                    if v != folding_proofs[i].proof_values[j] {
-                        return false;
+            for j in leaf_indices {
+                let j = *j;
+                let coset = (0..1 << (round_starts[r + 1] - start))
+                    .map(|t| get_folding_value(j + t * leave_numbers[r]))
+                    .collect::<Result<Vec<_>, VerifyError>>()?;
+                let v = fold_leaf(
+                    &coset,
+                    j,
+                    self.interpolate_cosets[start].size(),
+                    |step, i, x, nx| {
+                        let coset = &self.interpolate_cosets[start + step];
+                        x + nx
+                            + folding_challenges[start + step] * (x - nx) * coset.element_inv_at(i)
+                    },
+                );
+                if r < rounds - 1 {
+                    if v != folding_values[r].get_value(r + 1, j)? {
+                        return Err(VerifyError::FoldConsistency { round: r, index: j });
                     }
                 } else {
-                    if v != self.final_value.unwrap() {
-                        return false;
+                    // The last folding lands on the domain squaring the one before it
+                    let x = self.interpolate_cosets[folds - 1].element_at(j).pow(2);
+                    let final_value = final_polynomial
+                        .iter()
+                        .rev()
+                        .fold(T::from_int(0), |acc, c| acc * x + *c);
+                    if v != final_value {
+                        return Err(VerifyError::FinalValue { index: j });
                     }
                 }
             }
         }
-        true
+        Ok(())
     }
 }
@@ -2,29 +2,26 @@ extern crate criterion;
 use criterion::*;
 
 use pcs::{prover::Prover, verifier::Verifier};
-use util::{
-    algebra::{
-        coset::Coset,
-        field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
-        polynomial::MultilinearPolynomial,
-    },
-    random_oracle::RandomOracle,
+use util::algebra::{
+    coset::Coset,
+    field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
+    polynomial::MultilinearPolynomial,
 };
 
-use util::{CODE_RATE, SECURITY_BITS};
+use util::parameters::FriParameters;
 fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = (0..poly_num)
         .into_iter()
         .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
         .collect::<Vec<_>>();
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         T::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
 
     criterion.bench_function(
         &format!("newly {} commit {}", T::FIELD_NAME, variable_num),
@@ -32,7 +29,7 @@ fn commit<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: us
             b.iter_batched(
                 || polynomial.clone(),
                 |p| {
-                    let prover = Prover::new(variable_num, &interpolate_cosets, p, &oracle);
+                    let prover = Prover::new(variable_num, &interpolate_cosets, p, parameters);
                     prover.commit_polynomial();
                 },
                 BatchSize::SmallInput,
@@ -78,18 +75,18 @@ fn bench_commit(c: &mut Criterion) {
 }
 
 fn para_commit<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = (0..poly_num)
         .into_iter()
         .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
         .collect::<Vec<_>>();
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         T::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
 
     criterion.bench_function(
         &format!("newly {} para commit {}", T::FIELD_NAME, variable_num),
@@ -97,8 +94,7 @@ fn para_commit<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_nu
             b.iter_batched(
                 || polynomial.clone(),
                 |p| {
-                    let prover =
-                        Prover::new_parallel(variable_num, &interpolate_cosets, p, &oracle);
+                    let prover = Prover::new(variable_num, &interpolate_cosets, p, parameters);
                     prover.commit_polynomial();
                 },
                 BatchSize::SmallInput,
@@ -118,28 +114,31 @@ fn bench_para_commit(c: &mut Criterion) {
 }
 
 fn open<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = (0..poly_num)
         .into_iter()
         .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
         .collect::<Vec<_>>();
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         T::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let prover = Prover::new(variable_num, &interpolate_cosets, polynomial, parameters);
     let commits = prover.commit_polynomial();
+    let open_point = (0..variable_num)
+        .into_iter()
+        .map(|_| T::random_element())
+        .collect();
     let verifier = Verifier::new(
         variable_num,
-        poly_num,
         &interpolate_cosets,
         commits,
-        &oracle,
+        &open_point,
+        parameters,
     );
-    let (open_point, combination) = verifier.get_open_point();
 
     criterion.bench_function(
         &format!("newly {} open {}", T::FIELD_NAME, variable_num),
@@ -147,7 +146,7 @@ fn open<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: usiz
             b.iter_batched(
                 || (prover.clone(), verifier.clone()),
                 |(mut p, mut v)| {
-                    p.commit_functions(&open_point, &mut v, &combination);
+                    p.commit_functions(&open_point, &mut v);
                     p.prove();
                     p.commit_foldings(&mut v);
                     p.query();
@@ -169,30 +168,33 @@ fn bench_open(c: &mut Criterion) {
 }
 
 fn verify<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = (0..poly_num)
         .into_iter()
         .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
         .collect::<Vec<_>>();
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         T::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-    let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+    let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, parameters);
     let commits = prover.commit_polynomial();
+    let open_point = (0..variable_num)
+        .into_iter()
+        .map(|_| T::random_element())
+        .collect();
     let mut verifier = Verifier::new(
         variable_num,
-        poly_num,
         &interpolate_cosets,
         commits,
-        &oracle,
+        &open_point,
+        parameters,
     );
-    let (open_point, combination) = verifier.get_open_point();
 
-    prover.commit_functions(&open_point, &mut verifier, &combination);
+    prover.commit_functions(&open_point, &mut verifier);
     prover.prove();
     prover.commit_foldings(&mut verifier);
     let (polynomial_proof, folding_proof, function_proof) = prover.query();
@@ -201,7 +203,9 @@ fn verify<T: Field>(criterion: &mut Criterion, variable_num: usize, poly_num: us
         &format!("newly {} verify {}", T::FIELD_NAME, variable_num),
         move |b| {
             b.iter(|| {
-                verifier.verify(&polynomial_proof, &folding_proof, &function_proof);
+                assert!(verifier
+                    .verify(&polynomial_proof, &folding_proof, &function_proof)
+                    .is_ok());
             })
         },
     );
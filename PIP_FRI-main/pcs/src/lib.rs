@@ -1,49 +1,71 @@
+pub mod proof;
 pub mod prover;
+pub mod scheme;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
-    use crate::{prover::Prover, verifier::Verifier};
+    use crate::{
+        proof::{EvaluationClaim, NamefriBatchProof, NamefriParams, NamefriProof},
+        prover::Prover,
+        scheme::Namefri,
+        verifier::{self, Verifier},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
     use util::{
         algebra::{
             coset::Coset,
-            field::{ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field},
+            field::{
+                fp64::Fp64,
+                fp64_ext::{Fp64Ext2, Fp64Ext3},
+                mersenne61_ext::Mersenne61Ext,
+                ExtensionOf, Field,
+            },
             polynomial::MultilinearPolynomial,
         },
-        merkle_tree::MERKLE_ROOT_SIZE,
-        random_oracle::RandomOracle,
+        error::VerifyError,
+        mask::hiding_point,
+        merkle_tree::{Keccak256Algorithm, MerkleHasher, Sha256Algorithm, MERKLE_ROOT_SIZE},
+        parameters::FriParameters,
+        pcs::PolynomialCommitmentScheme,
+        poseidon::PoseidonAlgorithm,
+        transcript::Transcript,
     };
-    use util::{CODE_RATE, SECURITY_BITS};
 
     fn output_proof_size<T: Field>(variable_num: usize, poly_num: usize) -> usize {
+        let parameters = FriParameters::default();
         let polynomial = (0..poly_num)
             .into_iter()
             .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
             .collect::<Vec<_>>();
         let mut interpolate_cosets = vec![Coset::new(
-            1 << (variable_num + CODE_RATE),
+            1 << (variable_num + parameters.log_blowup),
             T::random_element(),
         )];
         for i in 1..variable_num {
             interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
         }
-        let oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
-        let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, &oracle);
+        let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, parameters);
         let commits = prover.commit_polynomial();
+        let open_point = (0..variable_num)
+            .into_iter()
+            .map(|_| T::random_element())
+            .collect();
         let mut verifier = Verifier::new(
             variable_num,
-            poly_num,
             &interpolate_cosets,
             commits,
-            &oracle,
+            &open_point,
+            parameters,
         );
-        let (open_point, combination) = verifier.get_open_point();
 
-        prover.commit_functions(&open_point, &mut verifier, &combination);
+        prover.commit_functions(&open_point, &mut verifier);
         prover.prove();
         prover.commit_foldings(&mut verifier);
         let (polynomial_proof, folding_proof, function_proof) = prover.query();
-        assert!(verifier.verify(&polynomial_proof, &folding_proof, &function_proof));
+        assert!(verifier
+            .verify(&polynomial_proof, &folding_proof, &function_proof)
+            .is_ok());
         folding_proof.iter().map(|x| x.proof_size()).sum::<usize>()
             + polynomial_proof
                 .iter()
@@ -126,4 +148,562 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_reject_invalid_proof() {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let polynomial = (0..2)
+            .into_iter()
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect::<Vec<_>>();
+        let mut interpolate_cosets = vec![Coset::new(
+            1 << (variable_num + parameters.log_blowup),
+            Mersenne61Ext::random_element(),
+        )];
+        for i in 1..variable_num {
+            interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
+        }
+        let mut prover = Prover::new(variable_num, &interpolate_cosets, polynomial, parameters);
+        let commits = prover.commit_polynomial();
+        let open_point = (0..variable_num)
+            .into_iter()
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let mut verifier = Verifier::new(
+            variable_num,
+            &interpolate_cosets,
+            commits,
+            &open_point,
+            parameters,
+        );
+        prover.commit_functions(&open_point, &mut verifier);
+        prover.prove();
+        prover.commit_foldings(&mut verifier);
+        let (mut polynomial_proof, folding_proof, function_proof) = prover.query();
+        assert!(verifier
+            .verify(&polynomial_proof, &folding_proof, &function_proof)
+            .is_ok());
+
+        // Wrong claimed evaluations are rejected
+        let mut wrong_verifier = verifier.clone();
+        wrong_verifier.set_evaluations(vec![Mersenne61Ext::random_element(); 2]);
+        assert!(wrong_verifier
+            .verify(&polynomial_proof, &folding_proof, &function_proof)
+            .is_err());
+
+        // So are missing layers and tampered values, without panicking
+        assert_eq!(
+            verifier.verify(&polynomial_proof, &folding_proof[1..].to_vec(), &function_proof),
+            Err(VerifyError::MalformedProof)
+        );
+        let value = &mut polynomial_proof[0].values[0];
+        *value += Mersenne61Ext::from_int(1);
+        assert_eq!(
+            verifier.verify(&polynomial_proof, &folding_proof, &function_proof),
+            Err(VerifyError::MerklePath { round: 0 })
+        );
+    }
+
+    #[test]
+    fn test_serialized_proof() {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let polynomial = (0..2)
+            .into_iter()
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect::<Vec<_>>();
+        let params = NamefriParams::new(variable_num, Mersenne61Ext::random_element(), parameters);
+        let mut prover = Prover::new(
+            variable_num,
+            &params.interpolate_cosets,
+            polynomial.clone(),
+            parameters,
+        );
+        let commits = prover.commit_polynomial();
+        let open_point = (0..variable_num)
+            .into_iter()
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let bytes = prover.open(&open_point).to_bytes();
+        let proof = NamefriProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert_eq!(proof.proof_size(), bytes.len());
+        assert!(verifier::verify(&params, &commits, &open_point, &proof).is_ok());
+
+        // The proof carries the value of every polynomial, each of which is checked
+        assert!(polynomial
+            .iter()
+            .zip(&proof.evaluations)
+            .all(|(x, v)| x.evaluate(&open_point) == *v));
+        let mut tampered = proof.clone();
+        tampered.evaluations[1] += Mersenne61Ext::from_int(1);
+        assert!(verifier::verify(&params, &commits, &open_point, &tampered).is_err());
+
+        // Truncated, padded and non-canonical encodings are rejected
+        assert!(NamefriProof::<Mersenne61Ext>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(NamefriProof::<Mersenne61Ext>::from_bytes(&padded).is_err());
+        let offset =
+            24 + (proof.function_roots.len() + proof.folding_roots.len()) * MERKLE_ROOT_SIZE;
+        let mut overflow = bytes.clone();
+        overflow[offset..offset + 8].copy_from_slice(&[0xff; 8]);
+        assert!(NamefriProof::<Mersenne61Ext>::from_bytes(&overflow).is_err());
+
+        // The stateless verifier checks the proof against the given commitments
+        assert!(verifier::verify(&params, &commits[1..], &open_point, &proof).is_err());
+
+        // and the point against the number of variables
+        let mut long_point = open_point.clone();
+        long_point.push(Mersenne61Ext::random_element());
+        assert_eq!(
+            verifier::verify(&params, &commits, &long_point, &proof),
+            Err(VerifyError::MalformedProof)
+        );
+        assert_eq!(
+            verifier::verify(&params, &commits, &open_point[1..].to_vec(), &proof),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    fn commitment_scheme<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let mut rng = StdRng::seed_from_u64(0);
+        let params: NamefriParams<Mersenne61Ext, H> =
+            Namefri::setup_with(variable_num, parameters, &mut rng).unwrap();
+        let polynomial = (0..2)
+            .map(|_| MultilinearPolynomial::random_polynomial_with(variable_num, &mut rng))
+            .collect::<Vec<_>>();
+        let (commitment, prover_data) = Namefri::commit(&params, polynomial.clone());
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element_with(&mut rng))
+            .collect();
+        let (value, proof) = Namefri::open(&params, prover_data, &point).unwrap();
+        assert!(polynomial
+            .iter()
+            .zip(&value)
+            .all(|(x, v)| x.evaluate(&point) == *v));
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+        // A caller that expects another value rejects the proof
+        let mut wrong_value = value.clone();
+        wrong_value[1] += Mersenne61Ext::from_int(1);
+        assert_eq!(
+            Namefri::verify(&params, &commitment, &point, &wrong_value, &proof),
+            Err(VerifyError::ClaimedValue)
+        );
+    }
+
+    #[test]
+    fn test_commitment_scheme() {
+        commitment_scheme(FriParameters::default());
+    }
+
+    #[test]
+    fn test_folding_arity() {
+        for log_arity in 2..5 {
+            let parameters = FriParameters::default().with_folding_arity(log_arity);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_hiding());
+        }
+    }
+
+    #[test]
+    fn test_final_polynomial() {
+        for log_final_degree in [1, 3, 8] {
+            let parameters = FriParameters::default().with_final_degree(log_final_degree);
+            commitment_scheme(parameters);
+            commitment_scheme(parameters.with_folding_arity(2).with_hiding());
+        }
+    }
+
+    #[test]
+    fn test_merkle_hashers() {
+        let parameters = FriParameters::default();
+        commitment_scheme(parameters.with_hasher::<Sha256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<Keccak256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<PoseidonAlgorithm<Fp64>>());
+    }
+
+    #[test]
+    fn test_proof_of_work() {
+        let parameters = FriParameters::new(FriParameters::default().log_blowup, 128, 8);
+        assert!(parameters.query_num < FriParameters::default().query_num);
+        let variable_num = 8;
+        let params: NamefriParams<Mersenne61Ext> =
+            Namefri::setup(variable_num, parameters).unwrap();
+        let polynomial = (0..2)
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect();
+        let (commitment, prover_data) = Namefri::commit(&params, polynomial);
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Namefri::open(&params, prover_data, &point).unwrap();
+        let bytes = proof.to_bytes();
+        let proof = NamefriProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+        // The nonce is the first one that passes, so every smaller one fails
+        for nonce in 0..proof.pow_nonce {
+            let mut forged = proof.clone();
+            forged.pow_nonce = nonce;
+            assert_eq!(
+                Namefri::verify(&params, &commitment, &point, &value, &forged),
+                Err(VerifyError::ProofOfWork)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hiding_opening() {
+        let parameters = FriParameters::default().with_hiding();
+        let variable_num = 8;
+        let params: NamefriParams<Mersenne61Ext> =
+            Namefri::setup(variable_num, parameters).unwrap();
+        let polynomial = (0..2)
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect();
+        let (commitment, prover_data) = Namefri::commit(&params, polynomial);
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Namefri::open(&params, prover_data, &point).unwrap();
+        let bytes = proof.to_bytes();
+        let proof = NamefriProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert_eq!(proof.proof_size(), bytes.len());
+        assert!(proof.polynomial_proof.iter().all(|x| !x.salts.is_empty()));
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.mask.as_mut().unwrap().evaluation += Mersenne61Ext::from_int(1);
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &tampered).is_err());
+
+        // Were the mask's evaluation left out of the transcript, the weights would not depend
+        // on it, and a change of a claimed evaluation could be cancelled in the mask
+        let mut tampered = proof.clone();
+        tampered.evaluations[0] += Mersenne61Ext::from_int(1);
+        let mut transcript = Transcript::new(b"namefri");
+        for root in commitment.iter().chain([&proof.mask.as_ref().unwrap().root]) {
+            transcript.append_root(root);
+        }
+        transcript.append_fields(&hiding_point(&point, true));
+        transcript.append_fields(&tampered.evaluations);
+        let combination: Vec<Mersenne61Ext> = transcript.challenge_fields(commitment.len() + 1);
+        tampered.mask.as_mut().unwrap().evaluation -=
+            combination[0] * combination[commitment.len()].inverse();
+        assert!(
+            Namefri::verify(&params, &commitment, &point, &tampered.evaluations, &tampered)
+                .is_err()
+        );
+        tampered = proof.clone();
+        tampered.mask = None;
+        assert_eq!(
+            Namefri::verify(&params, &commitment, &point, &value, &tampered),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    // Hiding proofs drawn from the same seed are the same
+    #[test]
+    fn test_seeded_hiding_opening() {
+        let parameters = FriParameters::default().with_hiding();
+        let variable_num = 8;
+        let params = NamefriParams::new(variable_num, Mersenne61Ext::random_element(), parameters);
+        let polynomial = (0..2)
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect::<Vec<_>>();
+        let open_point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let open = |seed| {
+            let mut prover = Prover::new_with(
+                variable_num,
+                &params.interpolate_cosets,
+                polynomial.clone(),
+                parameters,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            (
+                prover.commit_polynomial(),
+                prover.open(&open_point).to_bytes(),
+            )
+        };
+        let (commits, bytes) = open(7);
+        assert_eq!((commits.clone(), bytes.clone()), open(7));
+        assert_ne!(commits, open(8).0);
+        let proof = NamefriProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert!(verifier::verify(&params, &commits, &open_point, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_deep_opening() {
+        let parameters = FriParameters::default().with_deep();
+        commitment_scheme(parameters.with_hiding());
+        commitment_scheme(parameters.with_folding_arity(3).with_final_degree(2));
+
+        let variable_num = 8;
+        let params: NamefriParams<Mersenne61Ext> =
+            Namefri::setup(variable_num, parameters).unwrap();
+        let polynomials = (0..4)
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect::<Vec<_>>();
+        let claims = batch_claims(&polynomials, variable_num);
+        let (commitment, mut prover_data) = Namefri::commit(&params, polynomials);
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Namefri::open(&params, prover_data.clone(), &point).unwrap();
+        let bytes = proof.to_bytes();
+        let proof = NamefriProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            proof.deep.as_ref().unwrap().evaluations.len(),
+            commitment.len()
+        );
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.deep.as_mut().unwrap().evaluations[0] += Mersenne61Ext::from_int(1);
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &tampered).is_err());
+        tampered.deep = None;
+        assert_eq!(
+            Namefri::verify(&params, &commitment, &point, &value, &tampered),
+            Err(VerifyError::MalformedProof)
+        );
+
+        // Batch openings run the same low-degree test
+        let proof = prover_data.open_batch(&claims);
+        assert!(
+            verifier::verify_batch(&params, &commitment, &[variable_num; 4], &claims, &proof)
+                .is_ok()
+        );
+    }
+
+    // Four claims at three points, two of them on polynomial 0 and none on polynomial 2
+    fn batch_claims(
+        polynomials: &Vec<MultilinearPolynomial<Mersenne61Ext>>,
+        variable_num: usize,
+    ) -> Vec<EvaluationClaim<Mersenne61Ext>> {
+        let points = (0..3)
+            .map(|_| {
+                (0..variable_num)
+                    .map(|_| Mersenne61Ext::random_element())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        [(0, 0), (0, 1), (1, 1), (3, 2)]
+            .into_iter()
+            .map(|(polynomial, point)| EvaluationClaim {
+                polynomial,
+                point: points[point].clone(),
+                value: polynomials[polynomial].evaluate(&points[point]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_opening() {
+        let parameters = FriParameters::default();
+        let variable_num = 8;
+        let params = NamefriParams::new(variable_num, Mersenne61Ext::random_element(), parameters);
+        let polynomials = (0..4)
+            .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+            .collect::<Vec<_>>();
+        let claims = batch_claims(&polynomials, variable_num);
+        let mut prover = Prover::new(
+            variable_num,
+            &params.interpolate_cosets,
+            polynomials,
+            parameters,
+        );
+        let commits = prover.commit_polynomial();
+        let bytes = prover.open_batch(&claims).to_bytes();
+        let proof = NamefriBatchProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert!(
+            verifier::verify_batch(&params, &commits, &[variable_num; 4], &claims, &proof).is_ok()
+        );
+
+        // A wrong value, a claim the proof was not made for or a claim on a polynomial that
+        // was never committed is rejected
+        let mut wrong_claims = claims.clone();
+        wrong_claims[1].value += Mersenne61Ext::from_int(1);
+        assert_eq!(
+            verifier::verify_batch(&params, &commits, &[variable_num; 4], &wrong_claims, &proof),
+            Err(VerifyError::Sumcheck { round: 0 })
+        );
+        assert!(verifier::verify_batch(
+            &params,
+            &commits,
+            &[variable_num; 4],
+            &claims[1..],
+            &proof
+        )
+        .is_err());
+        wrong_claims = claims.clone();
+        wrong_claims[0].polynomial = commits.len();
+        assert_eq!(
+            verifier::verify_batch(&params, &commits, &[variable_num; 4], &wrong_claims, &proof),
+            Err(VerifyError::MalformedProof)
+        );
+
+        // So is a claim whose point does not have the polynomial's number of variables
+        wrong_claims = claims.clone();
+        wrong_claims[2].point.push(Mersenne61Ext::random_element());
+        assert_eq!(
+            verifier::verify_batch(&params, &commits, &[variable_num; 4], &wrong_claims, &proof),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    fn mixed_batch<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let variable_nums = [8, 5, 8, 3];
+        let params: NamefriParams<Mersenne61Ext, H> =
+            Namefri::setup(variable_num, parameters).unwrap();
+        let polynomials = variable_nums
+            .iter()
+            .map(|x| MultilinearPolynomial::random_polynomial(*x))
+            .collect::<Vec<_>>();
+        // Two claims on polynomial 1 and none on polynomial 2
+        let claims = [0, 1, 1, 3]
+            .into_iter()
+            .map(|polynomial| {
+                let point = (0..variable_nums[polynomial])
+                    .map(|_| Mersenne61Ext::random_element())
+                    .collect::<Vec<_>>();
+                EvaluationClaim {
+                    polynomial,
+                    value: polynomials[polynomial].evaluate(&point),
+                    point,
+                }
+            })
+            .collect::<Vec<_>>();
+        let (commits, mut prover) = Namefri::commit(&params, polynomials);
+        let bytes = prover.open_batch(&claims).to_bytes();
+        let proof = NamefriBatchProof::<Mersenne61Ext>::from_bytes(&bytes).unwrap();
+        assert!(verifier::verify_batch(&params, &commits, &variable_nums, &claims, &proof).is_ok());
+
+        let mut wrong_claims = claims.clone();
+        wrong_claims[3].value += Mersenne61Ext::from_int(1);
+        assert_eq!(
+            verifier::verify_batch(&params, &commits, &variable_nums, &wrong_claims, &proof),
+            Err(VerifyError::Sumcheck { round: 0 })
+        );
+        // The sizes are part of what the verifier knows about the commitments
+        assert_eq!(
+            verifier::verify_batch(&params, &commits, &[8, 5, 8, 8], &claims, &proof),
+            Err(VerifyError::MalformedProof)
+        );
+        assert!(verifier::verify_batch(&params, &commits, &[8, 5, 5, 3], &claims, &proof).is_err());
+        // The smallest polynomial is opened in a later round than the others
+        let mut tampered = proof.clone();
+        tampered.opening.polynomial_proof[3].values[0] += Mersenne61Ext::from_int(1);
+        assert!(
+            verifier::verify_batch(&params, &commits, &variable_nums, &claims, &tampered).is_err()
+        );
+    }
+
+    #[test]
+    fn test_mixed_sizes() {
+        let parameters = FriParameters::default();
+        mixed_batch(parameters);
+        // Polynomial 3 joins in the middle of the second round, polynomial 1 as it starts
+        mixed_batch(parameters.with_folding_arity(3).with_final_degree(2));
+        mixed_batch(parameters.with_folding_arity(2).with_deep());
+        mixed_batch(parameters.with_hasher::<Sha256Algorithm>().with_deep());
+        // The sumcheck and the opened combination are masked
+        mixed_batch(parameters.with_hiding());
+        mixed_batch(parameters.with_folding_arity(3).with_hiding());
+    }
+
+    #[test]
+    fn test_batch_proof_size() {
+        let parameters = FriParameters::default();
+        for variable_num in [10, 14] {
+            let params =
+                NamefriParams::new(variable_num, Mersenne61Ext::random_element(), parameters);
+            let polynomials = (0..4)
+                .map(|_| MultilinearPolynomial::random_polynomial(variable_num))
+                .collect::<Vec<_>>();
+            let claims = batch_claims(&polynomials, variable_num);
+            let prover = Prover::new(
+                variable_num,
+                &params.interpolate_cosets,
+                polynomials,
+                parameters,
+            );
+            let commits = prover.commit_polynomial();
+            let batch_proof = prover.clone().open_batch(&claims);
+            assert!(verifier::verify_batch(
+                &params,
+                &commits,
+                &[variable_num; 4],
+                &claims,
+                &batch_proof
+            )
+            .is_ok());
+
+            // Without batching every distinct point needs an opening of its own
+            let mut points = claims.iter().map(|x| x.point.clone()).collect::<Vec<_>>();
+            points.dedup();
+            let separate_size = points
+                .iter()
+                .map(|point| prover.clone().open(point).proof_size())
+                .sum::<usize>();
+            println!(
+                "namefri batch of {} claims at {} points over {} variables: {} bytes, {} bytes \
+                 with one opening per point",
+                claims.len(),
+                points.len(),
+                variable_num,
+                batch_proof.proof_size(),
+                separate_size
+            );
+            assert!(batch_proof.proof_size() < separate_size);
+        }
+    }
+
+    // Committed polynomials live in the base field, challenges in the extension
+    fn open_base_polynomial<T: ExtensionOf<Fp64>>(variable_num: usize) {
+        let parameters = FriParameters::default();
+        let polynomials = (0..2)
+            .map(|_| MultilinearPolynomial::<Fp64>::random_polynomial(variable_num))
+            .collect();
+        let params: NamefriParams<T> = Namefri::<Fp64>::setup(variable_num, parameters).unwrap();
+        assert!(ExtensionOf::<Fp64>::to_base(&params.interpolate_cosets[0].shift()).is_some());
+        let (commitment, prover_data) = Namefri::commit(&params, polynomials);
+        let point: Vec<T> = (0..variable_num).map(|_| T::random_element()).collect();
+        let (value, proof) = Namefri::open(&params, prover_data, &point).unwrap();
+        assert!(Namefri::verify(&params, &commitment, &point, &value, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_goldilocks_extension() {
+        open_base_polynomial::<Fp64Ext2>(8);
+        open_base_polynomial::<Fp64Ext3>(8);
+    }
+
+    fn commit_base_polynomial<T: ExtensionOf<Fp64>>(variable_num: usize) {
+        let parameters = FriParameters::default();
+        let polynomials = (0..2)
+            .map(|_| MultilinearPolynomial::<Fp64>::random_polynomial(variable_num))
+            .collect();
+        let params = NamefriParams::new(variable_num, T::from(Fp64::random_element()), parameters);
+        let mut prover = Prover::<T, Fp64>::new(
+            variable_num,
+            &params.interpolate_cosets,
+            polynomials,
+            parameters,
+        );
+        let commits = prover.commit_polynomial();
+        let open_point = (0..variable_num).map(|_| T::random_element()).collect();
+        let bytes = prover.open(&open_point).to_bytes();
+        let proof = NamefriProof::<T, Fp64>::from_bytes(&bytes).unwrap();
+        assert!(verifier::verify(&params, &commits, &open_point, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_base_field_commitment() {
+        commit_base_polynomial::<Fp64Ext2>(8);
+        commit_base_polynomial::<Fp64Ext3>(8);
+    }
 }
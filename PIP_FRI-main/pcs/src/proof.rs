@@ -0,0 +1,199 @@
+use util::{
+    algebra::{coset::Coset, field::Field},
+    deep::DeepOpening,
+    error::VerifyError,
+    mask::MaskOpening,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    query_result::QueryResult,
+    serialize::{write_field, write_u64, write_usize, ByteReader},
+};
+
+#[derive(Clone)]
+pub struct NamefriParams<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    pub variable_num: usize,
+    pub interpolate_cosets: Vec<Coset<T>>,
+    pub parameters: FriParameters<H>,
+}
+
+impl<T: Field, H: MerkleHasher> NamefriParams<T, H> {
+    pub fn new(variable_num: usize, shift: T, parameters: FriParameters<H>) -> Self {
+        // A hiding commitment has one more variable
+        let total_round = variable_num + parameters.hiding() as usize;
+        let mut interpolate_cosets = vec![Coset::new(
+            1 << (total_round + parameters.log_blowup),
+            shift,
+        )];
+        for i in 1..total_round {
+            interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
+        }
+        NamefriParams {
+            variable_num,
+            interpolate_cosets,
+            parameters,
+        }
+    }
+}
+
+// Everything the prover sends in a namefri opening, in transcript order. `evaluations` holds
+// the value of every committed polynomial at the opening point, and is empty in a batch
+// opening, whose claims carry the values.
+#[derive(Clone)]
+pub struct NamefriProof<T: Field, B: Field = T> {
+    pub function_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub final_function: Vec<T>,
+    pub evaluations: Vec<T>,
+    pub folding_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub final_polynomial: Vec<T>,
+    pub pow_nonce: u64,
+    pub polynomial_proof: Vec<QueryResult<B>>,
+    pub folding_proof: Vec<QueryResult<T>>,
+    pub function_proof: Vec<QueryResult<T>>,
+    pub mask: Option<MaskOpening<T>>,
+    pub deep: Option<DeepOpening<T>>,
+}
+
+fn read_proofs<T: Field>(reader: &mut ByteReader) -> Result<Vec<QueryResult<T>>, VerifyError> {
+    let len = reader.read_usize()?;
+    (0..len).map(|_| QueryResult::read_bytes(reader)).collect()
+}
+
+impl<T: Field, B: Field> NamefriProof<T, B> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        self.write_bytes(&mut buf);
+        buf
+    }
+
+    pub fn write_bytes(&self, buf: &mut Vec<u8>) {
+        for roots in [&self.function_roots, &self.folding_roots] {
+            write_usize(buf, roots.len());
+            for root in roots {
+                buf.extend_from_slice(root);
+            }
+        }
+        for coefficients in [&self.evaluations, &self.final_function, &self.final_polynomial] {
+            write_usize(buf, coefficients.len());
+            for x in coefficients {
+                write_field(buf, x);
+            }
+        }
+        write_u64(buf, self.pow_nonce);
+        write_usize(buf, self.polynomial_proof.len());
+        for proof in &self.polynomial_proof {
+            proof.write_bytes(buf);
+        }
+        for proofs in [&self.folding_proof, &self.function_proof] {
+            write_usize(buf, proofs.len());
+            for proof in proofs {
+                proof.write_bytes(buf);
+            }
+        }
+        MaskOpening::write_option(&self.mask, buf);
+        DeepOpening::write_option(&self.deep, buf);
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        let mut reader = ByteReader::new(bytes);
+        let proof = Self::read_bytes(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    pub fn read_bytes(reader: &mut ByteReader) -> Result<Self, VerifyError> {
+        let read_roots = |reader: &mut ByteReader| {
+            let len = reader.read_usize()?;
+            (0..len)
+                .map(|_| reader.read_root())
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let function_roots = read_roots(reader)?;
+        let folding_roots = read_roots(reader)?;
+        let read_coefficients = |reader: &mut ByteReader| {
+            let len = reader.read_usize()?;
+            (0..len)
+                .map(|_| reader.read_field())
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let evaluations = read_coefficients(reader)?;
+        let final_function = read_coefficients(reader)?;
+        let final_polynomial = read_coefficients(reader)?;
+        let pow_nonce = reader.read_u64()?;
+        let polynomial_proof = read_proofs(reader)?;
+        let folding_proof = read_proofs(reader)?;
+        let function_proof = read_proofs(reader)?;
+        let mask = MaskOpening::read_option(reader)?;
+        let deep = DeepOpening::read_option(reader)?;
+        Ok(NamefriProof {
+            function_roots,
+            final_function,
+            evaluations,
+            folding_roots,
+            final_polynomial,
+            pow_nonce,
+            polynomial_proof,
+            folding_proof,
+            function_proof,
+            mask,
+            deep,
+        })
+    }
+
+    pub fn proof_size(&self) -> usize {
+        self.to_bytes().len()
+    }
+}
+
+// The claim that committed polynomial number `polynomial` evaluates to `value` at `point`
+#[derive(Clone)]
+pub struct EvaluationClaim<T: Field> {
+    pub polynomial: usize,
+    pub point: Vec<T>,
+    pub value: T,
+}
+
+// A batch of evaluation claims: the sumcheck that reduces them to a single point, then the
+// namefri opening at that point
+#[derive(Clone)]
+pub struct NamefriBatchProof<T: Field, B: Field = T> {
+    pub sumcheck_values: Vec<(T, T, T)>,
+    pub opening: NamefriProof<T, B>,
+}
+
+impl<T: Field, B: Field> NamefriBatchProof<T, B> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        write_usize(&mut buf, self.sumcheck_values.len());
+        for (x_0, x_1, x_2) in &self.sumcheck_values {
+            for x in [x_0, x_1, x_2] {
+                write_field(&mut buf, x);
+            }
+        }
+        self.opening.write_bytes(&mut buf);
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        let mut reader = ByteReader::new(bytes);
+        let len = reader.read_usize()?;
+        let sumcheck_values = (0..len)
+            .map(|_| {
+                Ok((
+                    reader.read_field()?,
+                    reader.read_field()?,
+                    reader.read_field()?,
+                ))
+            })
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let opening = NamefriProof::read_bytes(&mut reader)?;
+        reader.finish()?;
+        Ok(NamefriBatchProof {
+            sumcheck_values,
+            opening,
+        })
+    }
+
+    pub fn proof_size(&self) -> usize {
+        self.to_bytes().len()
+    }
+}
@@ -1,106 +1,250 @@
-use super::verifier::Verifier;
-use util::algebra::polynomial::MultilinearPolynomial;
+use super::proof::{EvaluationClaim, NamefriBatchProof, NamefriProof};
+use super::verifier::{self, Verifier};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use util::algebra::polynomial::{EqMultilinear, MultilinearPolynomial};
 
-use util::merkle_tree::MERKLE_ROOT_SIZE;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
 use util::query_result::QueryResult;
 use util::{
-    algebra::{coset::Coset, field::Field},
+    algebra::{
+        coset::Coset,
+        field::{ExtensionOf, Field},
+    },
+    deep::{out_of_domain_point, quotient, DeepOpening},
     interpolation::InterpolateValue,
-    random_oracle::RandomOracle,
+    mask::{hiding_point, MaskOpening},
+    parameters::FriParameters,
+    transcript::Transcript,
 };
 
+// The query results of the committed polynomials, the foldings and the functions
+type QueryResults<T, B> = (Vec<QueryResult<B>>, Vec<QueryResult<T>>, Vec<QueryResult<T>>);
+
+// Polynomials are committed over the base field `B`; every challenge and later layer is in `T`
 #[derive(Clone)]
-pub struct Prover<T: Field> {
+pub struct Prover<T: Field, B: Field = T, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     combination: Option<Vec<T>>,
     interpolate_cosets: Vec<Coset<T>>,
-    interpolate_polynomials: Vec<InterpolateValue<T>>,
+    polynomials: Vec<MultilinearPolynomial<B>>,
+    interpolate_polynomials: Vec<InterpolateValue<B, H>>,
     rlc_polynomial: Vec<T>,
     combined_function: Option<Vec<T>>,
-    functions: Vec<InterpolateValue<T>>,
-    foldings: Vec<InterpolateValue<T>>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
+    // The function after every folding step, of which only the round starts are committed
+    function_values: Vec<Vec<T>>,
+    functions: Vec<InterpolateValue<T, H>>,
+    foldings: Vec<InterpolateValue<T, H>>,
+    round_starts: Vec<usize>,
+    transcript: Transcript,
+    parameters: FriParameters<H>,
+    final_function: Option<Vec<T>>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
+    mask: Option<InterpolateValue<T, H>>,
+    mask_evaluation: Option<T>,
+    mask_proof: Option<QueryResult<T>>,
+    deep: Option<InterpolateValue<T, H>>,
+    deep_evaluations: Option<Vec<T>>,
+    deep_proof: Option<QueryResult<T>>,
+    // The source of the masks and salts of a hiding opening
+    rng: StdRng,
+}
+
+// A hiding commitment is to a random extension of every polynomial in one more variable
+fn hiding_polynomials<B: Field, H: MerkleHasher, R: RngCore>(
+    polynomials: Vec<MultilinearPolynomial<B>>,
+    parameters: &FriParameters<H>,
+    rng: &mut R,
+) -> Vec<MultilinearPolynomial<B>> {
+    if !parameters.hiding() {
+        return polynomials;
+    }
+    polynomials
+        .iter()
+        .map(|x| x.random_extension_with(rng))
+        .collect()
+}
+
+// Every polynomial gets its own rng for the salts, so that the commitment does not depend
+// on the order the polynomials are encoded in
+fn salt_rngs<H: MerkleHasher, R: RngCore>(
+    polynomial_num: usize,
+    parameters: &FriParameters<H>,
+    rng: &mut R,
+) -> Vec<Option<StdRng>> {
+    (0..polynomial_num)
+        .map(|_| {
+            parameters
+                .hiding()
+                .then(|| StdRng::from_rng(&mut *rng).unwrap())
+        })
+        .collect()
+}
+
+// A polynomial in fewer variables than the others is encoded over the smaller domain the
+// folding reaches at the step it joins, with the leaves of the round that step belongs to
+fn encode<T: ExtensionOf<B>, B: Field, H: MerkleHasher>(
+    polynomial: &MultilinearPolynomial<B>,
+    interpolate_cosets: &[Coset<T>],
+    round_starts: &[usize],
+    salt_rng: Option<&mut dyn RngCore>,
+) -> InterpolateValue<B, H> {
+    let total_round = interpolate_cosets.len();
+    let step = total_round - polynomial.variable_num();
+    assert!(
+        step < *round_starts.last().unwrap(),
+        "a polynomial needs more variables than the final polynomial keeps"
+    );
+    let round = verifier::step_round(round_starts, step);
+    InterpolateValue::with_arity(
+        interpolate_cosets[step]
+            .to_base_coset()
+            .fft(polynomial.coefficients().clone()),
+        round_starts[round + 1] - step,
+        salt_rng,
+    )
 }
 
-use std::sync::mpsc;
-use std::thread;
-impl<T: Field> Prover<T> {
-    pub fn new_parallel(
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher> Prover<T, B, H> {
+    // The polynomials may have fewer variables than `total_round`, down to one more than the
+    // final polynomial keeps; a batch opening then covers them all
+    pub fn new(
         total_round: usize,
         interpolate_coset: &Vec<Coset<T>>,
-        polynomials: Vec<MultilinearPolynomial<T>>,
-        oracle: &RandomOracle<T>,
-    ) -> Prover<T> {
-        let (tx, rx) = mpsc::channel();
-        for i in 0..polynomials.len() {
-            let tx_clone = tx.clone();
-            let coset = interpolate_coset[0].clone();
-            let coeff = polynomials[i].coefficients().clone();
-            thread::spawn(move || {
-                tx_clone
-                    .send((i, InterpolateValue::new(coset.fft(coeff))))
-                    .unwrap();
-            });
-        }
-        drop(tx);
-        let mut data = vec![None; polynomials.len()];
-        for received in rx {
-            data[received.0] = Some(received.1);
-        }
-        let interpolate_polynomials = data.into_iter().map(|x| x.unwrap()).collect::<Vec<_>>();
-
-        let mut rlc_polynomial = interpolate_polynomials[0].value.clone();
-        for i in interpolate_polynomials.iter().skip(1) {
-            for j in 0..rlc_polynomial.len() {
-                rlc_polynomial[j] *= oracle.rlc;
-                rlc_polynomial[j] += i.value[j];
-            }
-        }
-
-        Prover {
+        polynomials: Vec<MultilinearPolynomial<B>>,
+        parameters: FriParameters<H>,
+    ) -> Prover<T, B, H> {
+        Self::new_with(
             total_round,
-            combination: None,
-            interpolate_cosets: interpolate_coset.clone(),
-            interpolate_polynomials,
-            rlc_polynomial,
-            combined_function: None,
-            functions: vec![],
-            foldings: vec![],
-            oracle: oracle.clone(),
-            final_value: None,
-        }
+            interpolate_coset,
+            polynomials,
+            parameters,
+            &mut rand::thread_rng(),
+        )
     }
 
-    pub fn new(
+    pub fn new_with<R: RngCore>(
         total_round: usize,
         interpolate_coset: &Vec<Coset<T>>,
-        polynomials: Vec<MultilinearPolynomial<T>>,
-        oracle: &RandomOracle<T>,
-    ) -> Prover<T> {
+        polynomials: Vec<MultilinearPolynomial<B>>,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Prover<T, B, H> {
+        let total_round = total_round + parameters.hiding() as usize;
+        let polynomials = hiding_polynomials(polynomials, &parameters, rng);
+        let round_starts = parameters.round_starts(total_round);
+        let salt_rngs = salt_rngs(polynomials.len(), &parameters, rng);
+        let encode_one = |(x, mut salt_rng): (&MultilinearPolynomial<B>, Option<StdRng>)| {
+            encode(
+                x,
+                interpolate_coset,
+                &round_starts,
+                salt_rng.as_mut().map(|x| x as &mut dyn RngCore),
+            )
+        };
+        // Every polynomial has its own salt rng, so they can be encoded in any order
+        #[cfg(not(feature = "parallel"))]
         let interpolate_polynomials = polynomials
             .iter()
-            .map(|x| InterpolateValue::new(interpolate_coset[0].fft(x.coefficients().clone())))
+            .zip(salt_rngs)
+            .map(encode_one)
             .collect::<Vec<_>>();
-        let mut rlc_polynomial = interpolate_polynomials[0].value.clone();
-        for i in interpolate_polynomials.iter().skip(1) {
-            for j in 0..rlc_polynomial.len() {
-                rlc_polynomial[j] *= oracle.rlc;
-                rlc_polynomial[j] += i.value[j];
-            }
+        #[cfg(feature = "parallel")]
+        let interpolate_polynomials = polynomials
+            .par_iter()
+            .zip(salt_rngs)
+            .map(encode_one)
+            .collect::<Vec<_>>();
+        Self::with_interpolations(
+            total_round,
+            interpolate_coset,
+            polynomials,
+            interpolate_polynomials,
+            parameters,
+            rng,
+        )
+    }
+
+    fn with_interpolations<R: RngCore>(
+        total_round: usize,
+        interpolate_coset: &[Coset<T>],
+        polynomials: Vec<MultilinearPolynomial<B>>,
+        interpolate_polynomials: Vec<InterpolateValue<B, H>>,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Prover<T, B, H> {
+        let mut transcript = Transcript::new(b"namefri");
+        for i in interpolate_polynomials.iter() {
+            transcript.append_root(&i.commit());
         }
 
         Prover {
             total_round,
             combination: None,
-            interpolate_cosets: interpolate_coset.clone(),
+            interpolate_cosets: interpolate_coset.to_vec(),
+            polynomials,
             interpolate_polynomials,
-            rlc_polynomial,
+            rlc_polynomial: vec![],
             combined_function: None,
+            function_values: vec![],
             functions: vec![],
             foldings: vec![],
-            oracle: oracle.clone(),
-            final_value: None,
+            round_starts: parameters.round_starts(total_round),
+            transcript,
+            parameters,
+            final_function: None,
+            final_polynomial: None,
+            pow_nonce: None,
+            mask: None,
+            mask_evaluation: None,
+            mask_proof: None,
+            deep: None,
+            deep_evaluations: None,
+            deep_proof: None,
+            rng: StdRng::from_rng(rng).unwrap(),
+        }
+    }
+
+    // The rng of the salts, which the trees of a hiding opening need
+    fn salt_rng(&mut self) -> Option<&mut dyn RngCore> {
+        if self.parameters.hiding() {
+            Some(&mut self.rng)
+        } else {
+            None
+        }
+    }
+
+    // The folding step at which polynomial j joins, where the domain has the size of its codeword
+    fn step(&self, j: usize) -> usize {
+        self.total_round - self.polynomials[j].variable_num()
+    }
+
+    // Adds the polynomials that join at `step`, weighted by `combination`, to the function
+    fn join_functions(&self, step: usize, combination: &[T], values: &mut [T]) {
+        for (j, (poly, c)) in self
+            .interpolate_polynomials
+            .iter()
+            .zip(combination)
+            .enumerate()
+        {
+            if self.step(j) == step {
+                for (x, v) in values.iter_mut().zip(poly.value.iter()) {
+                    *x += c.mul_base(*v);
+                }
+            }
+        }
+    }
+
+    // Absorbs the polynomials that join at `step` into the folding, as the rlc does at step 0
+    fn join_foldings(&self, step: usize, rlc: T, values: &mut [T]) {
+        for (j, poly) in self.interpolate_polynomials.iter().enumerate() {
+            if self.step(j) == step {
+                for (x, v) in values.iter_mut().zip(poly.value.iter()) {
+                    *x = *x * rlc + T::from(*v);
+                }
+            }
         }
     }
 
@@ -125,67 +269,304 @@ impl<T: Field> Prover<T> {
         res
     }
 
-    pub fn commit_functions(
-        &mut self,
-        open_point: &Vec<T>,
-        verifier: &mut Verifier<T>,
-        combination: &Vec<T>,
-    ) {
-        let mut evaluation = None;
-        let mut combined_function = (0..self.interpolate_cosets[0].size())
-            .into_iter()
-            .map(|i| combination[0] * self.interpolate_polynomials[0].value[i])
-            .collect::<Vec<T>>();
-        assert_eq!(combination.len(), self.interpolate_polynomials.len());
-        for (i, poly) in combination
+    pub fn commit_functions(&mut self, open_point: &Vec<T>, verifier: &mut Verifier<T, H>) {
+        let evaluations = self.compute_functions(open_point);
+        for function in self.functions.iter() {
+            verifier.set_function(function.leave_num(), &function.commit());
+        }
+        verifier.set_final_function(self.final_function.clone().unwrap());
+        verifier.set_evaluations(evaluations);
+    }
+
+    // Commits the folding of the combination along `open_point` and returns the value of
+    // every committed polynomial there, which fixes the combination
+    fn compute_functions(&mut self, open_point: &Vec<T>) -> Vec<T> {
+        assert!(
+            self.polynomials
+                .iter()
+                .all(|x| x.variable_num() == self.total_round),
+            "an opening at one point needs polynomials of one size"
+        );
+        let open_point = hiding_point(open_point, self.parameters.hiding());
+        if let Some(mask) = self.commit_mask() {
+            let mask_evaluation = mask.evaluate(&open_point);
+            self.transcript.append_field(&mask_evaluation);
+            self.mask_evaluation = Some(mask_evaluation);
+        }
+        let evaluations = self
+            .polynomials
             .iter()
-            .zip(self.interpolate_polynomials.iter())
-            .skip(1)
-        {
-            for j in 0..combined_function.len() {
-                combined_function[j] += i.clone() * poly.value[j];
+            .map(|x| {
+                MultilinearPolynomial::new(x.coefficients().iter().map(|c| T::from(*c)).collect())
+                    .evaluate(&open_point)
+            })
+            .collect::<Vec<_>>();
+        self.transcript.append_fields(&open_point);
+        self.transcript.append_fields(&evaluations);
+        let combination = self
+            .transcript
+            .challenge_fields(self.interpolate_polynomials.len() + self.mask.is_some() as usize);
+        let folding_point = verifier::folding_point(&self.interpolate_cosets, &open_point);
+        self.fold_functions(&folding_point, combination);
+        evaluations
+    }
+
+    // Commits a random polynomial to mask a hiding opening with, salted like the polynomials
+    fn commit_mask(&mut self) -> Option<MultilinearPolynomial<T>> {
+        if !self.parameters.hiding() {
+            return None;
+        }
+        let mask =
+            MultilinearPolynomial::<T>::random_polynomial_with(self.total_round, &mut self.rng);
+        let interpolation = InterpolateValue::with_arity(
+            self.interpolate_cosets[0].fft(mask.coefficients().clone()),
+            self.round_starts[1],
+            Some(&mut self.rng),
+        );
+        self.transcript.append_root(&interpolation.commit());
+        self.mask = Some(interpolation);
+        Some(mask)
+    }
+
+    // Commits the folding of sum_j combination[j] * f_j along `open_point`, and sends the
+    // evaluation it lands on
+    fn fold_functions(&mut self, open_point: &[T], combination: Vec<T>) {
+        assert_eq!(
+            combination.len(),
+            self.interpolate_polynomials.len() + self.mask.is_some() as usize
+        );
+        let mut combined_function = vec![T::from_int(0); self.interpolate_cosets[0].size()];
+        self.join_functions(0, &combination, &mut combined_function);
+        if let Some(mask) = &self.mask {
+            let c = combination[self.interpolate_polynomials.len()];
+            for (x, m) in combined_function.iter_mut().zip(mask.value.iter()) {
+                *x += c * *m;
             }
         }
         self.combined_function = Some(combined_function);
-        self.combination = Some(combination.clone());
-        for round in 0..self.total_round {
-            let next_evaluation = Self::fold(
+        self.combination = Some(combination);
+        let folds = *self.round_starts.last().unwrap();
+        for (round, parameter) in open_point[..folds].iter().enumerate() {
+            let mut next_evaluation = Self::fold(
                 if round == 0 {
                     self.combined_function.as_mut().unwrap()
                 } else {
-                    &self.functions[round - 1].value
+                    &self.function_values[round - 1]
                 },
-                open_point[round],
+                *parameter,
                 &self.interpolate_cosets[round],
             );
-            if round < self.total_round - 1 {
-                self.functions.push(InterpolateValue::new(next_evaluation));
+            if round < folds - 1 {
+                if let Some(r) = self.round_starts.iter().position(|x| *x == round + 1) {
+                    let function = InterpolateValue::with_arity(
+                        next_evaluation.clone(),
+                        self.round_starts[r + 1] - self.round_starts[r],
+                        self.salt_rng(),
+                    );
+                    self.functions.push(function);
+                }
+                // The committed function is the one before the polynomials joining now
+                self.join_functions(
+                    round + 1,
+                    self.combination.as_ref().unwrap(),
+                    &mut next_evaluation,
+                );
+                self.function_values.push(next_evaluation);
             } else {
-                evaluation = Some(next_evaluation[0]);
+                let mut coefficients = self.interpolate_cosets[round].pow(2).ifft(next_evaluation);
+                coefficients.truncate(1 << (self.total_round - folds));
+                self.final_function = Some(coefficients);
             }
         }
-        for i in 0..(self.total_round - 1) {
-            let function = &self.functions[i];
-            verifier.set_function(function.leave_num(), &function.commit());
+        // The variables left are evaluated on the final function, as folding would
+        let final_function = self.final_function.as_ref().unwrap();
+        let evaluation = MultilinearPolynomial::new(final_function.clone()).evaluate(
+            &verifier::evaluation_point(&self.interpolate_cosets[folds..], &open_point[folds..]),
+        );
+        for function in self.functions.iter() {
+            self.transcript.append_root(&function.commit());
+        }
+        self.transcript.append_fields(final_function);
+        self.transcript.append_field(&evaluation);
+    }
+
+    // Runs the whole opening non-interactively and packs every prover message into a proof
+    pub fn open(&mut self, open_point: &Vec<T>) -> NamefriProof<T, B> {
+        let evaluations = self.compute_functions(open_point);
+        self.prove();
+        let (polynomial_proof, folding_proof, function_proof) = self.query();
+        NamefriProof {
+            function_roots: self.functions.iter().map(|x| x.commit()).collect(),
+            final_function: self.final_function.clone().unwrap(),
+            evaluations,
+            folding_roots: self.foldings.iter().map(|x| x.commit()).collect(),
+            final_polynomial: self.final_polynomial.clone().unwrap(),
+            pow_nonce: self.pow_nonce.unwrap(),
+            polynomial_proof,
+            folding_proof,
+            function_proof,
+            mask: self.mask_opening(),
+            deep: self.deep_opening(),
         }
-        verifier.set_evaluation(evaluation.unwrap());
     }
 
-    pub fn commit_foldings(&self, verifier: &mut Verifier<T>) {
-        for i in 0..(self.total_round - 1) {
-            let interpolation = &self.foldings[i];
+    // The mask of a hiding opening, once its queries are answered
+    pub fn mask_opening(&self) -> Option<MaskOpening<T>> {
+        Some(MaskOpening {
+            root: self.mask.as_ref()?.commit(),
+            evaluation: self.mask_evaluation?,
+            proof: self.mask_proof.clone()?,
+        })
+    }
+
+    // The out-of-domain quotient of a DEEP opening, once its queries are answered
+    pub fn deep_opening(&self) -> Option<DeepOpening<T>> {
+        Some(DeepOpening {
+            root: self.deep.as_ref()?.commit(),
+            evaluations: self.deep_evaluations.clone()?,
+            proof: self.deep_proof.clone()?,
+        })
+    }
+
+    // Proves every claim in one opening. With alpha a challenge, a sumcheck reduces
+    // sum_k alpha^k f_{j_k}(z_k) = sum_j sum_b f_j(b) sum_{k: j_k = j} alpha^k eq(z_k, b)
+    // over the hypercube to a claim about sum_j c_j f_j(r) at the sumcheck point r, where
+    // the verifier computes every c_j itself. That claim is opened as usual. A hiding opening
+    // adds alpha^K g(b) for a random g to the sum, with its hypercube sum sent beforehand, so
+    // that the sumcheck messages and the opened combination are masked.
+    pub fn open_batch(&mut self, claims: &[EvaluationClaim<T>]) -> NamefriBatchProof<T, B> {
+        let mask = self.commit_mask().map(|x| {
+            let hypercube = x.evaluate_hypercube();
+            let sum = hypercube.iter().fold(T::from_int(0), |acc, x| acc + *x);
+            self.transcript.append_field(&sum);
+            self.mask_evaluation = Some(sum);
+            hypercube
+        });
+        verifier::append_claims(&mut self.transcript, claims);
+        let claims = verifier::hiding_claims(claims, self.parameters.hiding());
+        let alpha: T = self.transcript.challenge_field();
+        let size = 1 << self.total_round;
+        let mut eq_hypercubes = vec![vec![]; self.polynomials.len()];
+        let mut power = T::from_int(1);
+        for claim in claims.iter() {
+            assert_eq!(
+                claim.point.len(),
+                self.polynomials[claim.polynomial].variable_num()
+            );
+            let eq_hypercube = EqMultilinear::new(claim.point.clone()).evaluate_hypercube();
+            let sum = &mut eq_hypercubes[claim.polynomial];
+            sum.resize(eq_hypercube.len(), T::from_int(0));
+            for (x, e) in sum.iter_mut().zip(eq_hypercube) {
+                *x += power * e;
+            }
+            power *= alpha;
+        }
+        // Polynomials without a claim have no terms in the sum. One joining at step k does not
+        // depend on the first k variables, so its tables repeat 2^k times and the sum counts
+        // each of its terms 2^k times.
+        let (mut poly_hypercubes, mut eq_hypercubes): (Vec<Vec<T>>, Vec<Vec<T>>) = self
+            .polynomials
+            .iter()
+            .zip(eq_hypercubes)
+            .enumerate()
+            .filter(|(_, (_, eq_hypercube))| !eq_hypercube.is_empty())
+            .map(|(j, (poly, eq_hypercube))| {
+                let scale = T::from_int(1 << self.step(j)).inverse();
+                let poly_hypercube = poly.evaluate_hypercube();
+                (
+                    poly_hypercube
+                        .iter()
+                        .cycle()
+                        .take(size)
+                        .map(|x| T::from(*x))
+                        .collect(),
+                    eq_hypercube
+                        .iter()
+                        .cycle()
+                        .take(size)
+                        .map(|x| *x * scale)
+                        .collect(),
+                )
+            })
+            .unzip();
+        if let Some(mask) = mask {
+            poly_hypercubes.push(mask);
+            eq_hypercubes.push(vec![power; size]);
+        }
+
+        let mut sumcheck_values = vec![];
+        let mut point = vec![];
+        for i in 0..self.total_round {
+            let m = 1 << (self.total_round - i - 1);
+            let mut sums = (T::from_int(0), T::from_int(0), T::from_int(0));
+            for (poly_hypercube, eq_hypercube) in poly_hypercubes.iter().zip(eq_hypercubes.iter()) {
+                for x in 0..m {
+                    let p_0 = poly_hypercube[x];
+                    let p_1 = poly_hypercube[x + m];
+                    let e_0 = eq_hypercube[x];
+                    let e_1 = eq_hypercube[x + m];
+                    sums.0 += p_0 * e_0;
+                    sums.1 += p_1 * e_1;
+                    sums.2 += (p_1 + p_1 - p_0) * (e_1 + e_1 - e_0);
+                }
+            }
+            sumcheck_values.push(sums);
+            self.transcript.append_fields(&[sums.0, sums.1, sums.2]);
+            let challenge = self.transcript.challenge_field();
+            for hypercube in poly_hypercubes.iter_mut().chain(eq_hypercubes.iter_mut()) {
+                sumcheck_next_domain(hypercube, m, challenge);
+            }
+            point.push(challenge);
+        }
+
+        let variable_nums = self
+            .polynomials
+            .iter()
+            .map(|x| x.variable_num())
+            .collect::<Vec<_>>();
+        let mut combination = verifier::batch_combination(&claims, alpha, &point, &variable_nums);
+        if self.mask.is_some() {
+            combination.push(power);
+        }
+        let folding_point = verifier::folding_point(&self.interpolate_cosets, &point);
+        self.fold_functions(&folding_point, combination);
+        self.prove();
+        let (polynomial_proof, folding_proof, function_proof) = self.query();
+        NamefriBatchProof {
+            sumcheck_values,
+            opening: NamefriProof {
+                function_roots: self.functions.iter().map(|x| x.commit()).collect(),
+                final_function: self.final_function.clone().unwrap(),
+                // The claims carry the values, and the sumcheck the combination's
+                evaluations: vec![],
+                folding_roots: self.foldings.iter().map(|x| x.commit()).collect(),
+                final_polynomial: self.final_polynomial.clone().unwrap(),
+                pow_nonce: self.pow_nonce.unwrap(),
+                polynomial_proof,
+                folding_proof,
+                function_proof,
+                mask: self.mask_opening(),
+                deep: self.deep_opening(),
+            },
+        }
+    }
+
+    pub fn commit_foldings(&self, verifier: &mut Verifier<T, H>) {
+        for interpolation in &self.foldings {
             verifier.receive_folding_root(interpolation.leave_num(), interpolation.commit());
         }
-        verifier.set_final_value(self.final_value.unwrap());
+        verifier.set_final_polynomial(self.final_polynomial.clone().unwrap());
+        verifier.set_pow_nonce(self.pow_nonce.unwrap());
     }
 
-    fn evaluation_next_domain(&self, round: usize, challenge: T) -> Vec<T> {
+    // Folds the random combination in round 0 and `folding_value` after it
+    fn evaluation_next_domain(&self, round: usize, challenge: T, folding_value: &[T]) -> Vec<T> {
         let mut res = vec![];
         let len = self.interpolate_cosets[round].size();
         let get_folding_value = if round == 0 {
             &self.rlc_polynomial
         } else {
-            &self.foldings[round - 1].value
+            folding_value
         };
         let coset = &self.interpolate_cosets[round];
         for i in 0..(len / 2) {
@@ -195,7 +576,7 @@ impl<T: Field> Prover<T> {
             let fv = if round == 0 {
                 self.combined_function.as_ref().unwrap()
             } else {
-                &self.functions[round - 1].value
+                &self.function_values[round - 1]
             };
             let x = fv[i];
             let nx = fv[i + len / 2];
@@ -206,48 +587,128 @@ impl<T: Field> Prover<T> {
         res
     }
 
+    // Commits the quotient by X - z of the random combination of the committed polynomials,
+    // read as univariate ones, at an out-of-domain z. A polynomial p joining at step k is
+    // read as p(X^(2^k)), whose codeword over the first domain repeats that of p.
+    fn commit_deep(&mut self) {
+        let z = out_of_domain_point(&mut self.transcript, &self.interpolate_cosets[0]);
+        let evaluations = (0..self.polynomials.len())
+            .map(|j| {
+                let z = z.pow(1 << self.step(j));
+                self.polynomials[j]
+                    .coefficients()
+                    .iter()
+                    .rev()
+                    .fold(T::from_int(0), |acc, c| acc * z + T::from(*c))
+            })
+            .collect::<Vec<_>>();
+        self.transcript.append_fields(&evaluations);
+        let gamma: T = self.transcript.challenge_field();
+        let mut combined = vec![T::from_int(0); self.interpolate_cosets[0].size()];
+        for poly in self.interpolate_polynomials.iter() {
+            let len = poly.value.len();
+            for (i, x) in combined.iter_mut().enumerate() {
+                *x = *x * gamma + T::from(poly.value[i % len]);
+            }
+        }
+        let value = evaluations
+            .iter()
+            .fold(T::from_int(0), |acc, x| acc * gamma + *x);
+        let deep = InterpolateValue::with_arity(
+            quotient(&combined, &self.interpolate_cosets[0], z, value),
+            self.round_starts[1],
+            self.salt_rng(),
+        );
+        self.transcript.append_root(&deep.commit());
+        self.deep = Some(deep);
+        self.deep_evaluations = Some(evaluations);
+    }
+
     pub fn prove(&mut self) {
-        for i in 0..self.total_round {
-            let challenge = self.oracle.folding_challenges[i];
-            let next_evalutation = self.evaluation_next_domain(i, challenge);
-            if i < self.total_round - 1 {
-                self.foldings.push(InterpolateValue::new(next_evalutation));
+        if self.parameters.deep() {
+            self.commit_deep();
+        }
+        let rlc: T = self.transcript.challenge_field();
+        let mut rlc_polynomial = vec![T::from_int(0); self.interpolate_cosets[0].size()];
+        self.join_foldings(0, rlc, &mut rlc_polynomial);
+        if let Some(mask) = &self.mask {
+            for (x, m) in rlc_polynomial.iter_mut().zip(mask.value.iter()) {
+                *x = *x * rlc + *m;
+            }
+        }
+        if let Some(deep) = &self.deep {
+            for (x, q) in rlc_polynomial.iter_mut().zip(deep.value.iter()) {
+                *x = *x * rlc + *q;
+            }
+        }
+        self.rlc_polynomial = rlc_polynomial;
+
+        let mut next_evalutation = vec![];
+        let folds = *self.round_starts.last().unwrap();
+        for i in 0..folds {
+            let challenge = self.transcript.challenge_field();
+            next_evalutation = self.evaluation_next_domain(i, challenge, &next_evalutation);
+            if i < folds - 1 {
+                // Only the foldings a round starts from are committed
+                if let Some(round) = self.round_starts.iter().position(|x| *x == i + 1) {
+                    let folding = InterpolateValue::with_arity(
+                        next_evalutation.clone(),
+                        self.round_starts[round + 1] - self.round_starts[round],
+                        self.salt_rng(),
+                    );
+                    self.transcript.append_root(&folding.commit());
+                    self.foldings.push(folding);
+                }
+                self.join_foldings(i + 1, rlc, &mut next_evalutation);
             } else {
-                self.final_value = Some(next_evalutation[0]);
+                let mut coefficients = self.interpolate_cosets[i]
+                    .pow(2)
+                    .ifft(next_evalutation.clone());
+                coefficients.truncate(1 << (self.total_round - folds));
+                self.transcript.append_fields(&coefficients);
+                self.final_polynomial = Some(coefficients);
+                self.pow_nonce = Some(self.transcript.grind(self.parameters.grinding_bits));
             }
         }
     }
 
-    pub fn query(
-        &self,
-    ) -> (
-        Vec<QueryResult<T>>,
-        Vec<QueryResult<T>>,
-        Vec<QueryResult<T>>,
-    ) {
+    pub fn query(&mut self) -> QueryResults<T, B> {
         let mut folding_res = vec![];
         let mut functions_res = vec![];
-        let mut polynomial_res = None;
-        let mut leaf_indices = self.oracle.query_list.clone();
+        let mut leaf_indices = self.transcript.challenge_indices(self.parameters.query_num);
+        let mut round_indices = vec![];
 
-        for i in 0..self.total_round {
-            let len = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices.iter_mut().map(|v| *v % (len >> 1)).collect();
+        for i in 0..self.round_starts.len() - 1 {
+            let len = self.interpolate_cosets[0].size() >> self.round_starts[i + 1];
+            leaf_indices = leaf_indices.iter_mut().map(|v| *v % len).collect();
             leaf_indices.sort();
             leaf_indices.dedup();
 
             if i == 0 {
-                polynomial_res = Some(
-                    self.interpolate_polynomials
-                        .iter()
-                        .map(|x| x.query(&leaf_indices))
-                        .collect::<Vec<_>>(),
-                );
+                self.mask_proof = self.mask.as_ref().map(|x| x.query(&leaf_indices));
+                self.deep_proof = self.deep.as_ref().map(|x| x.query(&leaf_indices));
             } else {
                 functions_res.push(self.functions[i - 1].query(&leaf_indices));
                 folding_res.push(self.foldings[i - 1].query(&leaf_indices));
             }
+            round_indices.push(leaf_indices.clone());
         }
-        (polynomial_res.unwrap(), folding_res, functions_res)
+        // Every polynomial is opened in the round it joins
+        let polynomial_res = (0..self.polynomials.len())
+            .map(|j| {
+                let round = verifier::step_round(&self.round_starts, self.step(j));
+                self.interpolate_polynomials[j].query(&round_indices[round])
+            })
+            .collect();
+        (polynomial_res, folding_res, functions_res)
+    }
+}
+
+// Binds the leading variable of the hypercube table to `challenge`, in its first m entries
+fn sumcheck_next_domain<T: Field>(hypercube: &mut [T], m: usize, challenge: T) {
+    for i in 0..m {
+        hypercube[i] *= T::from_int(1) - challenge;
+        let tmp = hypercube[i + m] * challenge;
+        hypercube[i] += tmp;
     }
 }
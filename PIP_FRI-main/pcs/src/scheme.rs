@@ -0,0 +1,84 @@
+use std::io;
+use std::marker::PhantomData;
+
+use rand::RngCore;
+use util::{
+    algebra::{
+        field::{ExtensionOf, Field},
+        polynomial::MultilinearPolynomial,
+    },
+    error::{ParameterError, VerifyError},
+    merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    pcs::PolynomialCommitmentScheme,
+};
+
+use crate::{
+    proof::{NamefriParams, NamefriProof},
+    prover::Prover,
+    verifier,
+};
+
+// Namefri committing to polynomials over the subfield `B` of the challenges. `B` has to be
+// named to set up over a proper subfield.
+pub struct Namefri<B> {
+    field: PhantomData<B>,
+}
+
+impl<T: ExtensionOf<B>, B: Field, H: MerkleHasher> PolynomialCommitmentScheme<T, H> for Namefri<B> {
+    type Params = NamefriParams<T, H>;
+    type Polynomial = Vec<MultilinearPolynomial<B>>;
+    type Point = Vec<T>;
+    type Commitment = Vec<[u8; MERKLE_ROOT_SIZE]>;
+    type Evaluation = Vec<T>;
+    type ProverData = Prover<T, B, H>;
+    type Proof = NamefriProof<T, B>;
+
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError> {
+        // The committed codewords are over `B`, and so is the shift of their domain
+        Ok(NamefriParams::new(
+            variable_num,
+            T::from(B::random_element_with(rng)),
+            parameters,
+        ))
+    }
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let prover = Prover::new(
+            params.variable_num,
+            &params.interpolate_cosets,
+            polynomial,
+            params.parameters,
+        );
+        (prover.commit_polynomial(), prover)
+    }
+
+    fn open(
+        _params: &Self::Params,
+        mut prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)> {
+        let proof = prover_data.open(point);
+        Ok((proof.evaluations.clone(), proof))
+    }
+
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError> {
+        if proof.evaluations != *value {
+            return Err(VerifyError::ClaimedValue);
+        }
+        verifier::verify(params, commitment, point, proof)
+    }
+}
@@ -1,69 +1,73 @@
-use util::merkle_tree::MERKLE_ROOT_SIZE;
-use util::random_oracle::RandomOracle;
+use super::proof::{EvaluationClaim, NamefriBatchProof, NamefriParams, NamefriProof};
+use util::deep::{out_of_domain_point, DeepOpening};
+use util::error::VerifyError;
+use util::interpolation::fold_leaf;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
+use util::transcript::Transcript;
 use util::{
-    algebra::{coset::Coset, field::Field},
+    algebra::{
+        coset::Coset,
+        field::{ExtensionOf, Field},
+        polynomial::{EqMultilinear, MultilinearPolynomial},
+    },
+    mask::{hiding_point, MaskOpening},
     merkle_tree::MerkleTreeVerifier,
+    parameters::FriParameters,
     query_result::QueryResult,
 };
 
 #[derive(Clone)]
-pub struct Verifier<T: Field> {
+pub struct Verifier<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     interpolate_cosets: Vec<Coset<T>>,
-    polynomial_roots: Vec<MerkleTreeVerifier>,
-    function_root: Vec<MerkleTreeVerifier>,
-    folding_root: Vec<MerkleTreeVerifier>,
-    oracle: RandomOracle<T>,
-    final_value: Option<T>,
-    evaluation: Option<T>,
+    polynomial_roots: Vec<MerkleTreeVerifier<H>>,
+    function_root: Vec<MerkleTreeVerifier<H>>,
+    folding_root: Vec<MerkleTreeVerifier<H>>,
+    parameters: FriParameters<H>,
+    final_function: Option<Vec<T>>,
+    final_polynomial: Option<Vec<T>>,
+    pow_nonce: Option<u64>,
+    evaluations: Option<Vec<T>>,
+    mask: Option<MaskOpening<T>>,
+    deep: Option<DeepOpening<T>>,
     open_point: Vec<T>,
-    combination: Vec<T>,
 }
 
-impl<T: Field> Verifier<T> {
+impl<T: Field, H: MerkleHasher> Verifier<T, H> {
     pub fn new(
         total_round: usize,
-        combination_length: usize,
         coset: &Vec<Coset<T>>,
         commits: Vec<[u8; MERKLE_ROOT_SIZE]>,
-        oracle: &RandomOracle<T>,
+        open_point: &Vec<T>,
+        parameters: FriParameters<H>,
     ) -> Self {
         Verifier {
             total_round,
             interpolate_cosets: coset.clone(),
             function_root: vec![],
             folding_root: vec![],
-            oracle: oracle.clone(),
+            parameters,
             polynomial_roots: commits
                 .iter()
                 .map(|x| MerkleTreeVerifier::new(coset[0].size() / 2, x))
                 .collect(),
-            final_value: None,
-            evaluation: None,
-            open_point: (0..total_round)
-                .into_iter()
-                .map(|_| T::random_element())
-                .collect(),
-            combination: (0..combination_length)
-                .into_iter()
-                .map(|_| T::random_element())
-                .collect(),
+            final_function: None,
+            final_polynomial: None,
+            pow_nonce: None,
+            evaluations: None,
+            mask: None,
+            deep: None,
+            open_point: open_point.clone(),
         }
     }
 
-    pub fn get_open_point(&self) -> (Vec<T>, Vec<T>) {
-        (self.open_point.clone(), self.combination.clone())
-    }
-
-    pub fn set_evaluation(&mut self, evaluation: T) {
-        self.evaluation = Some(evaluation);
+    pub fn set_evaluations(&mut self, evaluations: Vec<T>) {
+        self.evaluations = Some(evaluations);
     }
 
     pub fn set_function(&mut self, leave_number: usize, function_root: &[u8; MERKLE_ROOT_SIZE]) {
-        self.function_root.push(MerkleTreeVerifier {
-            merkle_root: function_root.clone(),
-            leave_number,
-        });
+        self.function_root
+            .push(MerkleTreeVerifier::new(leave_number, function_root));
     }
 
     pub fn receive_folding_root(
@@ -71,93 +75,566 @@ impl<T: Field> Verifier<T> {
         leave_number: usize,
         folding_root: [u8; MERKLE_ROOT_SIZE],
     ) {
-        self.folding_root.push(MerkleTreeVerifier {
-            leave_number,
-            merkle_root: folding_root,
-        });
+        self.folding_root
+            .push(MerkleTreeVerifier::new(leave_number, &folding_root));
+    }
+
+    pub fn set_final_function(&mut self, coefficients: Vec<T>) {
+        self.final_function = Some(coefficients);
+    }
+
+    pub fn set_final_polynomial(&mut self, coefficients: Vec<T>) {
+        self.final_polynomial = Some(coefficients);
+    }
+
+    pub fn set_pow_nonce(&mut self, nonce: u64) {
+        self.pow_nonce = Some(nonce);
+    }
+
+    pub fn set_mask(&mut self, mask: MaskOpening<T>) {
+        self.mask = Some(mask);
     }
 
-    pub fn set_final_value(&mut self, value: T) {
-        self.final_value = Some(value);
+    pub fn set_deep(&mut self, deep: DeepOpening<T>) {
+        self.deep = Some(deep);
     }
 
-    pub fn verify(
+    pub fn verify<B: Field>(
         &self,
-        polynomial_proof: &Vec<QueryResult<T>>,
+        polynomial_proof: &Vec<QueryResult<B>>,
         folding_proof: &Vec<QueryResult<T>>,
         function_proof: &Vec<QueryResult<T>>,
-    ) -> bool {
-        let mut leaf_indices = self.oracle.query_list.clone();
-        for i in 0..self.total_round {
-            let domain_size = self.interpolate_cosets[i].size();
-            leaf_indices = leaf_indices
-                .iter_mut()
-                .map(|v| *v % (domain_size >> 1))
-                .collect();
-            leaf_indices.sort();
-            leaf_indices.dedup();
-
-            if i == 0 {
-                polynomial_proof
-                    .iter()
-                    .zip(self.polynomial_roots.iter())
-                    .all(|(x, v)| x.verify_merkle_tree(&leaf_indices, v));
-            } else {
-                function_proof[i - 1].verify_merkle_tree(&leaf_indices, &self.function_root[i - 1]);
-                folding_proof[i - 1].verify_merkle_tree(&leaf_indices, &self.folding_root[i - 1]);
+    ) -> Result<(), VerifyError>
+    where
+        T: ExtensionOf<B>,
+    {
+        let (evaluations, final_function, final_polynomial, pow_nonce) = match (
+            &self.evaluations,
+            &self.final_function,
+            &self.final_polynomial,
+            self.pow_nonce,
+        ) {
+            (Some(evaluations), Some(final_function), Some(final_polynomial), Some(pow_nonce)) => {
+                (evaluations, final_function, final_polynomial, pow_nonce)
             }
+            _ => return Err(VerifyError::MalformedProof),
+        };
+        let params = NamefriParams {
+            variable_num: self.total_round,
+            interpolate_cosets: self.interpolate_cosets.clone(),
+            parameters: self.parameters,
+        };
+        let commitments = self
+            .polynomial_roots
+            .iter()
+            .map(|x| x.merkle_root)
+            .collect::<Vec<_>>();
+        let proof = NamefriProof {
+            function_roots: self.function_root.iter().map(|x| x.merkle_root).collect(),
+            final_function: final_function.clone(),
+            evaluations: evaluations.clone(),
+            folding_roots: self.folding_root.iter().map(|x| x.merkle_root).collect(),
+            final_polynomial: final_polynomial.clone(),
+            pow_nonce,
+            polynomial_proof: polynomial_proof.clone(),
+            folding_proof: folding_proof.clone(),
+            function_proof: function_proof.clone(),
+            mask: self.mask.clone(),
+            deep: self.deep.clone(),
+        };
+        verify(&params, &commitments, &self.open_point, &proof)
+    }
+}
 
-            let challenge = self.oracle.folding_challenges[i];
-            let get_folding_value: Box<dyn Fn(&usize) -> T> = if i == 0 {
-                Box::new(|x| {
-                    let mut res = polynomial_proof[0].proof_values[x];
-                    for poly in polynomial_proof.iter().skip(1) {
-                        res *= self.oracle.rlc;
-                        res += poly.proof_values[x];
-                    }
-                    res
-                })
+fn commitment_transcript(commitments: &[[u8; MERKLE_ROOT_SIZE]]) -> Transcript {
+    let mut transcript = Transcript::new(b"namefri");
+    for root in commitments {
+        transcript.append_root(root);
+    }
+    transcript
+}
+
+// Checks that committed polynomial j evaluates to `proof.evaluations[j]` at `open_point`
+pub fn verify<T: ExtensionOf<B>, B: Field, H: MerkleHasher>(
+    params: &NamefriParams<T, H>,
+    commitments: &[[u8; MERKLE_ROOT_SIZE]],
+    open_point: &Vec<T>,
+    proof: &NamefriProof<T, B>,
+) -> Result<(), VerifyError> {
+    // A longer point would be cut short when it is zipped with the cosets
+    if proof.evaluations.len() != commitments.len() || open_point.len() != params.variable_num {
+        return Err(VerifyError::MalformedProof);
+    }
+    let open_point = hiding_point(open_point, params.parameters.hiding());
+    let mut transcript = commitment_transcript(commitments);
+    // The mask's evaluation is fixed before the combination, so that it cannot absorb a
+    // change of the claimed evaluation
+    if let Some(mask) = &proof.mask {
+        transcript.append_root(&mask.root);
+        transcript.append_field(&mask.evaluation);
+    }
+    transcript.append_fields(&open_point);
+    transcript.append_fields(&proof.evaluations);
+    let combination: Vec<T> =
+        transcript.challenge_fields(commitments.len() + proof.mask.is_some() as usize);
+    // The folding opens the combination of the values, with the mask added
+    let evaluation = proof
+        .evaluations
+        .iter()
+        .chain(proof.mask.as_ref().map(|x| &x.evaluation))
+        .zip(&combination)
+        .fold(T::from_int(0), |acc, (x, c)| acc + *x * *c);
+    verify_combination(
+        params,
+        commitments,
+        &vec![params.variable_num; commitments.len()],
+        &folding_point(&params.interpolate_cosets, &open_point),
+        &combination,
+        evaluation,
+        transcript,
+        proof,
+    )
+}
+
+pub(crate) fn append_claims<T: Field>(transcript: &mut Transcript, claims: &[EvaluationClaim<T>]) {
+    for claim in claims {
+        transcript.append_message(&(claim.polynomial as u64).to_le_bytes());
+        transcript.append_fields(&claim.point);
+        transcript.append_field(&claim.value);
+    }
+}
+
+// The claims about the polynomials of a hiding commitment, where the extra variable is 0
+pub(crate) fn hiding_claims<T: Field>(
+    claims: &[EvaluationClaim<T>],
+    hiding: bool,
+) -> Vec<EvaluationClaim<T>> {
+    claims
+        .iter()
+        .map(|x| EvaluationClaim {
+            polynomial: x.polynomial,
+            point: hiding_point(&x.point, hiding),
+            value: x.value,
+        })
+        .collect()
+}
+
+// c_j = sum_{k: j_k = j} alpha^k eq(z_k, point), the weight of polynomial j in the claim
+// the batch sumcheck ends with. A polynomial in fewer variables takes the last ones of
+// `point`, and its terms were counted once for every value of the others.
+pub(crate) fn batch_combination<T: Field>(
+    claims: &[EvaluationClaim<T>],
+    alpha: T,
+    point: &[T],
+    variable_nums: &[usize],
+) -> Vec<T> {
+    let mut combination = vec![T::from_int(0); variable_nums.len()];
+    let mut power = T::from_int(1);
+    for claim in claims {
+        let step = point.len() - variable_nums[claim.polynomial];
+        let scale = T::from_int(1 << step).inverse();
+        combination[claim.polynomial] += power
+            * scale
+            * EqMultilinear::new(claim.point.clone()).evaluate(&point[step..].to_vec());
+        power *= alpha;
+    }
+    combination
+}
+
+// The round that folds the codeword at `step`, the last one starting at or before it
+pub(crate) fn step_round(round_starts: &[usize], step: usize) -> usize {
+    round_starts.iter().rposition(|x| *x <= step).unwrap()
+}
+
+// Folding divides the odd part by element_inv_at(i) = shift^2 / x rather than by x, so
+// folding along `point` scaled by shift^-2 in every round evaluates at `point` itself
+pub(crate) fn folding_point<T: Field>(cosets: &[Coset<T>], point: &[T]) -> Vec<T> {
+    cosets
+        .iter()
+        .zip(point)
+        .map(|(coset, x)| *x * coset.shift().pow(2).inverse())
+        .collect()
+}
+
+// The point at which folding along `point` evaluates, the inverse of `folding_point`
+pub(crate) fn evaluation_point<T: Field>(cosets: &[Coset<T>], point: &[T]) -> Vec<T> {
+    cosets
+        .iter()
+        .zip(point)
+        .map(|(coset, x)| *x * coset.shift().pow(2))
+        .collect()
+}
+
+// Polynomial j of the batch has `variable_nums[j]` variables, at most `params.variable_num`
+pub fn verify_batch<T: ExtensionOf<B>, B: Field, H: MerkleHasher>(
+    params: &NamefriParams<T, H>,
+    commitments: &[[u8; MERKLE_ROOT_SIZE]],
+    variable_nums: &[usize],
+    claims: &[EvaluationClaim<T>],
+    proof: &NamefriBatchProof<T, B>,
+) -> Result<(), VerifyError> {
+    let hiding = params.parameters.hiding();
+    let total_round = params.variable_num + hiding as usize;
+    let mask = proof.opening.mask.as_ref();
+    if claims.is_empty()
+        || !proof.opening.evaluations.is_empty()
+        || mask.is_some() != hiding
+        || proof.sumcheck_values.len() != total_round
+        || variable_nums.len() != commitments.len()
+        || variable_nums.iter().any(|x| *x > total_round)
+        || claims.iter().any(|x| {
+            x.polynomial >= commitments.len() || x.point.len() != variable_nums[x.polynomial]
+        })
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+    let mut transcript = commitment_transcript(commitments);
+    if let Some(mask) = mask {
+        transcript.append_root(&mask.root);
+        transcript.append_field(&mask.evaluation);
+    }
+    append_claims(&mut transcript, claims);
+    let alpha: T = transcript.challenge_field();
+    // The mask's hypercube sum is the last term of the batch
+    let mut sum = claims
+        .iter()
+        .map(|x| x.value)
+        .chain(mask.map(|x| x.evaluation))
+        .rev()
+        .fold(T::from_int(0), |acc, x| acc * alpha + x);
+    let mut point = vec![];
+    for (i, &(x_0, x_1, x_2)) in proof.sumcheck_values.iter().enumerate() {
+        if sum != x_0 + x_1 {
+            return Err(VerifyError::Sumcheck { round: i });
+        }
+        transcript.append_fields(&[x_0, x_1, x_2]);
+        let challenge: T = transcript.challenge_field();
+        sum = x_0 * (T::from_int(1) - challenge) * (T::from_int(2) - challenge) * T::INVERSE_2
+            + x_1 * challenge * (T::from_int(2) - challenge)
+            + x_2 * challenge * (challenge - T::from_int(1)) * T::INVERSE_2;
+        point.push(challenge);
+    }
+    let hiding_variable_nums = variable_nums
+        .iter()
+        .map(|x| x + hiding as usize)
+        .collect::<Vec<_>>();
+    let mut combination = batch_combination(
+        &hiding_claims(claims, hiding),
+        alpha,
+        &point,
+        &hiding_variable_nums,
+    );
+    if mask.is_some() {
+        combination.push(alpha.pow(claims.len()));
+    }
+    verify_combination(
+        params,
+        commitments,
+        variable_nums,
+        &folding_point(&params.interpolate_cosets, &point),
+        &combination,
+        sum,
+        transcript,
+        &proof.opening,
+    )
+}
+
+// Checks that sum_j combination[j] * f_j, with the mask last, evaluates to `evaluation` at
+// `open_point`, continuing `transcript` from the point where the combination was fixed. A
+// polynomial in fewer variables joins the folding at the step its codeword has the size of
+// the domain.
+#[allow(clippy::too_many_arguments)]
+fn verify_combination<T: ExtensionOf<B>, B: Field, H: MerkleHasher>(
+    params: &NamefriParams<T, H>,
+    commitments: &[[u8; MERKLE_ROOT_SIZE]],
+    variable_nums: &[usize],
+    open_point: &[T],
+    combination: &[T],
+    evaluation: T,
+    mut transcript: Transcript,
+    proof: &NamefriProof<T, B>,
+) -> Result<(), VerifyError> {
+    let total_round = params.variable_num + params.parameters.hiding() as usize;
+    let cosets = &params.interpolate_cosets;
+    let mask = proof.mask.as_ref();
+    let deep = proof.deep.as_ref();
+    let final_function = &proof.final_function;
+    let final_polynomial = &proof.final_polynomial;
+    let polynomial_proof = &proof.polynomial_proof;
+    let folding_proof = &proof.folding_proof;
+    let function_proof = &proof.function_proof;
+    if total_round == 0 {
+        return Err(VerifyError::MalformedProof);
+    }
+    let round_starts = params.parameters.round_starts(total_round);
+    let rounds = round_starts.len() - 1;
+    let folds = round_starts[rounds];
+    let steps = variable_nums
+        .iter()
+        .map(|x| params.variable_num.saturating_sub(*x))
+        .collect::<Vec<_>>();
+    if cosets.len() != total_round
+        || open_point.len() != total_round
+        || commitments.is_empty()
+        || variable_nums.len() != commitments.len()
+        || variable_nums
+            .iter()
+            .zip(&steps)
+            .any(|(x, step)| *x > params.variable_num || *step >= folds)
+        || polynomial_proof.len() != commitments.len()
+        || folding_proof.len() != rounds - 1
+        || function_proof.len() != rounds - 1
+        || proof.folding_roots.len() != rounds - 1
+        || proof.function_roots.len() != rounds - 1
+        || final_function.len() != 1 << (total_round - folds)
+        || final_polynomial.len() != 1 << (total_round - folds)
+        || mask.is_some() != params.parameters.hiding()
+        || deep.is_some() != params.parameters.deep()
+        || deep.is_some_and(|x| x.evaluations.len() != commitments.len())
+        || combination.len() != commitments.len() + mask.is_some() as usize
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+    let step_rounds = steps
+        .iter()
+        .map(|x| step_round(&round_starts, *x))
+        .collect::<Vec<_>>();
+    // Round r commits codewords whose leaves hold the cosets it folds
+    let leave_numbers = round_starts[1..]
+        .iter()
+        .map(|x| cosets[0].size() >> x)
+        .collect::<Vec<_>>();
+    let leave_number = leave_numbers[0];
+    let mask_root = mask.map(|x| MerkleTreeVerifier::<H>::new(leave_number, &x.root));
+    let deep_root = deep.map(|x| MerkleTreeVerifier::<H>::new(leave_number, &x.root));
+    let polynomial_roots = commitments
+        .iter()
+        .zip(&step_rounds)
+        .map(|(x, r)| MerkleTreeVerifier::<H>::new(leave_numbers[*r], x))
+        .collect::<Vec<_>>();
+    let function_root = proof
+        .function_roots
+        .iter()
+        .enumerate()
+        .map(|(i, x)| MerkleTreeVerifier::<H>::new(leave_numbers[i + 1], x))
+        .collect::<Vec<_>>();
+    let folding_root = proof
+        .folding_roots
+        .iter()
+        .enumerate()
+        .map(|(i, x)| MerkleTreeVerifier::<H>::new(leave_numbers[i + 1], x))
+        .collect::<Vec<_>>();
+
+    // Replay the rest of the prover's transcript to derive every challenge
+    for root in function_root.iter() {
+        transcript.append_root(&root.merkle_root);
+    }
+    transcript.append_fields(final_function);
+    transcript.append_field(&evaluation);
+    // A DEEP opening checks the quotient by X - z of the combination of the polynomials
+    // with gamma, and folds it along with them
+    let mut deep_point = T::from_int(0);
+    let mut deep_value = T::from_int(0);
+    let mut gamma = T::from_int(0);
+    if let Some(deep) = deep {
+        deep_point = out_of_domain_point(&mut transcript, &cosets[0]);
+        transcript.append_fields(&deep.evaluations);
+        gamma = transcript.challenge_field();
+        deep_value = deep
+            .evaluations
+            .iter()
+            .fold(T::from_int(0), |acc, x| acc * gamma + *x);
+        transcript.append_root(&deep.root);
+    }
+    let rlc: T = transcript.challenge_field();
+    let mut folding_challenges: Vec<T> = vec![];
+    for r in 0..rounds {
+        for _ in round_starts[r]..round_starts[r + 1] {
+            folding_challenges.push(transcript.challenge_field());
+        }
+        if r < rounds - 1 {
+            transcript.append_root(&folding_root[r].merkle_root);
+        }
+    }
+    transcript.append_fields(final_polynomial);
+    if !transcript.check_grinding(params.parameters.grinding_bits, proof.pow_nonce) {
+        return Err(VerifyError::ProofOfWork);
+    }
+    let mut leaf_indices = transcript.challenge_indices(params.parameters.query_num);
+    // The leaves queried in every round, which place the opened values in the codewords
+    let mut round_indices = vec![];
+    for leave_number in leave_numbers.iter() {
+        leaf_indices = leaf_indices.iter_mut().map(|v| *v % leave_number).collect();
+        leaf_indices.sort();
+        leaf_indices.dedup();
+        round_indices.push(leaf_indices.clone());
+    }
+    let polynomial_values = polynomial_proof
+        .iter()
+        .zip(&step_rounds)
+        .map(|(x, r)| x.open_values(&round_indices[*r], leave_numbers[*r]))
+        .collect::<Result<Vec<_>, VerifyError>>()?;
+    let mask_values = match mask {
+        Some(mask) => Some(mask.proof.open_values(&round_indices[0], leave_number)?),
+        None => None,
+    };
+    let deep_values = match deep {
+        Some(deep) => Some(deep.proof.open_values(&round_indices[0], leave_number)?),
+        None => None,
+    };
+    let evaluate_at = |coefficients: &[T], x: T| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(T::from_int(0), |acc, c| acc * x + *c)
+    };
+    let open_rounds = |proofs: &[QueryResult<T>]| {
+        proofs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x.open_values(&round_indices[i + 1], leave_numbers[i + 1]))
+            .collect::<Result<Vec<_>, VerifyError>>()
+    };
+    let function_values = open_rounds(function_proof)?;
+    let folding_values = open_rounds(folding_proof)?;
+    for r in 0..rounds {
+        let start = round_starts[r];
+        let end = round_starts[r + 1];
+        let leaf_indices = &round_indices[r];
+        let joining = |j: &usize| step_rounds[*j] == r;
+        let merkle_ok = (0..commitments.len())
+            .filter(joining)
+            .all(|j| polynomial_proof[j].verify_merkle_tree(leaf_indices, &polynomial_roots[j]))
+            && if r == 0 {
+                mask.zip(mask_root.as_ref())
+                    .is_none_or(|(x, v)| x.proof.verify_merkle_tree(leaf_indices, v))
+                    && deep
+                        .zip(deep_root.as_ref())
+                        .is_none_or(|(x, v)| x.proof.verify_merkle_tree(leaf_indices, v))
             } else {
-                Box::new(|x| folding_proof[i - 1].proof_values[x])
+                function_proof[r - 1].verify_merkle_tree(leaf_indices, &function_root[r - 1])
+                    && folding_proof[r - 1].verify_merkle_tree(leaf_indices, &folding_root[r - 1])
             };
+        if !merkle_ok {
+            return Err(VerifyError::MerklePath { round: r });
+        }
 
-            let get_function_value: Box<dyn Fn(&usize) -> T> = if i == 0 {
-                Box::new(|x| {
-                    let mut res = T::from_int(0);
-                    for v in polynomial_proof.iter().zip(self.combination.iter()) {
-                        res += v.0.proof_values[x] * v.1.clone();
+        // The polynomials joining at `step` are absorbed into the folding as the rlc does
+        // and added to the function with their weights
+        let join = |step: usize, index: usize, (mut v, mut function): (T, T)| {
+            for j in (0..commitments.len()).filter(|j| steps[*j] == step) {
+                let x = polynomial_values[j].get_value(r, index)?;
+                v = v * rlc + T::from(x);
+                function += combination[j].mul_base(x);
+            }
+            Ok::<_, VerifyError>((v, function))
+        };
+        // The mask and the DEEP quotient join at step 0, after the polynomials
+        let join_first = |index: usize, (mut v, mut function): (T, T)| {
+            if let Some(mask) = &mask_values {
+                let m = mask.get_value(r, index)?;
+                v = v * rlc + m;
+                function += combination[commitments.len()] * m;
+            }
+            if let Some(deep) = &deep_values {
+                let q = deep.get_value(r, index)?;
+                let mut combined = T::from_int(0);
+                for (poly, step) in polynomial_values.iter().zip(&steps) {
+                    let x = poly.get_value(r, index % cosets[*step].size())?;
+                    combined = combined * gamma + T::from(x);
+                }
+                if q * (cosets[0].element_at(index) - deep_point) != combined - deep_value {
+                    return Err(VerifyError::Quotient { index });
+                }
+                v = v * rlc + q;
+            }
+            Ok((v, function))
+        };
+        // The folding and the function are folded together, the folding absorbing the
+        // function of every step
+        let fold_step = |step: usize, i: usize, (x, fx): (T, T), (nx, fnx): (T, T)| {
+            let inv = cosets[step].element_inv_at(i);
+            let challenge = folding_challenges[step];
+            let v = x + nx + challenge * (x - nx) * inv;
+            let v = (v * challenge + (fx + fnx)) * challenge + (fx - fnx) * inv;
+            let function = (fx + fnx + open_point[step] * (fx - fnx) * inv) * T::INVERSE_2;
+            (v, function)
+        };
+        // The round is folded in segments, between the steps at which polynomials join
+        let mut segments = steps
+            .iter()
+            .copied()
+            .filter(|x| start < *x && *x < end)
+            .collect::<Vec<_>>();
+        segments.push(end);
+        segments.sort();
+        segments.dedup();
+        for j in leaf_indices {
+            let j = *j;
+            let mut values = (0..1 << (end - start))
+                .map(|t| {
+                    let index = j + t * leave_numbers[r];
+                    if r == 0 {
+                        let value = join(start, index, (T::from_int(0), T::from_int(0)))?;
+                        join_first(index, value)
+                    } else {
+                        let value = (
+                            folding_values[r - 1].get_value(r, index)?,
+                            function_values[r - 1].get_value(r, index)?,
+                        );
+                        join(start, index, value)
                     }
-                    res
                 })
+                .collect::<Result<Vec<_>, VerifyError>>()?;
+            let mut step = start;
+            for next in segments.iter().copied() {
+                // Value t of the segment's end folds the values t + u * len of its start
+                let len = 1 << (end - next);
+                values = (0..len)
+                    .map(|t| {
+                        let leaf = (0..1 << (next - step))
+                            .map(|u| values[t + u * len])
+                            .collect::<Vec<_>>();
+                        let index = j + t * leave_numbers[r];
+                        let value = fold_leaf(&leaf, index, cosets[step].size(), |s, i, x, nx| {
+                            fold_step(step + s, i, x, nx)
+                        });
+                        if next < end {
+                            join(next, index, value)
+                        } else {
+                            Ok(value)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, VerifyError>>()?;
+                step = next;
+            }
+            let (v, function) = values[0];
+            if r < rounds - 1 {
+                if v != folding_values[r].get_value(r + 1, j)? {
+                    return Err(VerifyError::FoldConsistency { round: r, index: j });
+                }
+                if function != function_values[r].get_value(r + 1, j)? {
+                    return Err(VerifyError::Evaluation { round: r, index: j });
+                }
             } else {
-                Box::new(|x| function_proof[i - 1].proof_values[x])
-            };
-            for j in &leaf_indices {
-                let x = (*get_folding_value)(j);
-                let nx = (*get_folding_value)(&(j + domain_size / 2));
-                let v =
-                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                let x = (*get_function_value)(j);
-                let nx = (*get_function_value)(&(j + domain_size / 2));
-                let v = (v * challenge + (x + nx)) * challenge
-                    + (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                if i == self.total_round - 1 {
-                    assert_eq!(v, self.final_value.unwrap());
-                } else {
-                    assert_eq!(v, folding_proof[i].proof_values[j]);
+                // The last folding lands on the domain squaring the one before it
+                let x = cosets[folds - 1].element_at(j).pow(2);
+                if v != evaluate_at(final_polynomial, x) {
+                    return Err(VerifyError::FinalValue { index: j });
                 }
-                let x = (*get_function_value)(j);
-                let nx = (*get_function_value)(&(j + domain_size / 2));
-                let v = x
-                    + nx
-                    + self.open_point[i] * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
-                if i < self.total_round - 1 {
-                    assert_eq!(v, function_proof[i].proof_values[j] * T::from_int(2));
-                } else {
-                    assert_eq!(v, self.evaluation.unwrap() * T::from_int(2));
+                if function != evaluate_at(final_function, x) {
+                    return Err(VerifyError::Evaluation { round: r, index: j });
                 }
             }
         }
-        true
     }
+    // The variables the folding left are evaluated on the final function directly
+    let final_point = evaluation_point(&cosets[folds..], &open_point[folds..]);
+    if MultilinearPolynomial::new(final_function.clone()).evaluate(&final_point) != evaluation {
+        return Err(VerifyError::Evaluation {
+            round: rounds,
+            index: 0,
+        });
+    }
+    Ok(())
 }
@@ -1,96 +1,13 @@
-use super::{field::Field, polynomial::Polynomial};
-use crate::batch_bit_reverse;
-
-#[derive(Debug, Clone, Copy)]
-struct Radix2Domain<T: Field> {
-    order: usize,
-    omega: T,
-}
-
-impl<T: Field> Radix2Domain<T> {
-    #[inline]
-    pub fn new(order: usize, omega: T) -> Self {
-        Radix2Domain { order, omega }
-    }
-
-    #[inline]
-    pub fn order(&self) -> usize {
-        self.order
-    }
-
-    #[inline]
-    pub fn omega(&self) -> T {
-        self.omega
-    }
-
-    #[inline]
-    pub fn fft(&self, a: &mut Vec<T>) {
-        _fft(a, self.omega);
-    }
-
-    #[inline]
-    pub fn ifft(&self, a: &mut Vec<T>) {
-        _fft(a, self.omega.inverse());
-        let t = T::from_int(self.order as u64).inverse();
-        for i in a {
-            *i *= t;
-        }
-    }
-
-    #[inline]
-    pub fn coset_fft(&self, a: &mut Vec<T>, shift: T) {
-        multiply_by_coset(a, shift);
-        self.fft(a);
-    }
-
-    #[inline]
-    pub fn coset_ifft(&self, a: &mut Vec<T>, shift: T) {
-        self.ifft(a);
-        multiply_by_coset(a, shift.inverse());
-    }
-}
-
-fn multiply_by_coset<T: Field>(a: &mut Vec<T>, shift: T) {
-    let mut t = shift;
-    for i in 1..a.len() {
-        a[i] *= t;
-        t *= shift;
-    }
-}
-
-fn _fft<T: Field>(a: &mut Vec<T>, omega: T) {
-    let n = a.len();
-    let log_n = n.ilog2() as usize;
-    let rank = batch_bit_reverse(log_n);
-    for i in 0..n {
-        if i < rank[i] {
-            (a[i], a[rank[i]]) = (a[rank[i]], a[i]);
-        }
-    }
-    let mut log_m = 0usize;
-    for _i in 0..log_n {
-        let w_m = omega.pow(n >> (log_m + 1));
-        let m = 1 << log_m;
-        for j in (0..n).step_by(m * 2) {
-            let mut w = T::from_int(1);
-            for k in 0..m {
-                let t = w * a[j + k + m];
-                a[j + k + m] = a[j + k] - t;
-                a[j + k] += t;
-                w *= w_m;
-            }
-        }
-        log_m += 1;
-    }
-}
-
-use std::sync::Arc;
+use super::{
+    fft::FftPlan,
+    field::{ExtensionOf, Field},
+    polynomial::Polynomial,
+};
 
+// Elements are computed on demand from the shared twiddle table of the plan
 #[derive(Debug, Clone)]
 pub struct Coset<T: Field> {
-    elements: Arc<Vec<T>>,
-    elements_inv: Arc<Vec<T>>,
-    fft_eval_domain: Radix2Domain<T>,
+    fft_plan: FftPlan<T>,
     shift: T,
 }
 
@@ -104,73 +21,72 @@ impl<T: Field> Coset<T> {
             }
             d << 1
         };
-        let domain = Radix2Domain::new(degree, T::get_generator(degree));
+        let plan = FftPlan::new(degree);
         let mut coeff1 = poly1.coefficients().clone();
         let len = coeff1.len();
         coeff1.append(&mut (len..degree).into_iter().map(|_| T::from_int(0)).collect());
         let mut coeff2 = poly2.coefficients().clone();
         let len = coeff2.len();
         coeff2.append(&mut (len..degree).into_iter().map(|_| T::from_int(0)).collect());
-        domain.fft(&mut coeff1);
-        domain.fft(&mut coeff2);
+        plan.fft(&mut coeff1);
+        plan.fft(&mut coeff2);
         for i in 0..degree {
             coeff1[i] *= coeff2[i];
         }
-        domain.ifft(&mut coeff1);
+        plan.ifft(&mut coeff1);
         let poly = Polynomial::new(coeff1);
         poly
     }
 
     pub fn new(order: usize, shift: T) -> Self {
         assert!(!shift.is_zero());
-        let omega = T::get_generator(order);
-        let elements = std::iter::successors(Some(shift), |&last| Some(last * omega))
-            .take(order)
-            .collect();
-        let omega_inv = omega.pow(order - 1);
-        let elements_inv = std::iter::successors(Some(shift), |&last| Some(last * omega_inv))
-            .take(order)
-            .collect();
         Coset {
-            elements: Arc::new(elements),
-            elements_inv: Arc::new(elements_inv),
-            fft_eval_domain: Radix2Domain::new(order, omega),
+            fft_plan: FftPlan::new(order),
             shift,
         }
     }
 
     pub fn order(&self) -> usize {
-        self.fft_eval_domain.order
+        self.fft_plan.order()
     }
 
     pub fn pow(&self, index: usize) -> Coset<T> {
         assert_eq!(index & (index - 1), 0);
         let lowbit = (index as i64 & (-(index as i64))) as usize;
-        Coset::new(self.order() / lowbit, self.shift.pow(index))
+        Coset {
+            fft_plan: self.fft_plan.sub_plan(self.order() / lowbit),
+            shift: self.shift.pow(index),
+        }
     }
 
     pub fn generator(&self) -> T {
-        self.fft_eval_domain.omega
+        self.fft_plan.omega()
     }
 
     pub fn element_at(&self, index: usize) -> T {
-        self.elements[index]
+        self.shift * self.fft_plan.omega_pow(index)
     }
 
+    // shift * omega^(-index)
     pub fn element_inv_at(&self, index: usize) -> T {
-        self.elements_inv[index]
+        self.shift * self.fft_plan.omega_pow(self.order() - index)
+    }
+
+    // x is in the coset iff (x / shift)^size = 1
+    pub fn contains(&self, x: T) -> bool {
+        (x * self.shift.inverse()).pow(self.size()) == T::from_int(1)
     }
 
     pub fn all_elements_inv(&self) -> Vec<T> {
-        (*self.elements_inv).clone()
+        (0..self.size()).map(|i| self.element_inv_at(i)).collect()
     }
 
     pub fn all_elements(&self) -> Vec<T> {
-        (*self.elements).clone()
+        (0..self.size()).map(|i| self.element_at(i)).collect()
     }
 
     pub fn size(&self) -> usize {
-        self.fft_eval_domain.order()
+        self.fft_plan.order()
     }
 
     pub fn fft(&self, mut coeff: Vec<T>) -> Vec<T> {
@@ -178,7 +94,7 @@ impl<T: Field> Coset<T> {
         for _i in 0..n {
             coeff.push(T::from_int(0));
         }
-        self.fft_eval_domain.coset_fft(&mut coeff, self.shift);
+        self.fft_plan.coset_fft(&mut coeff, self.shift);
         coeff
     }
 
@@ -187,13 +103,27 @@ impl<T: Field> Coset<T> {
             return vec![evals[0]];
         };
         assert_eq!(self.size(), evals.len());
-        self.fft_eval_domain.coset_ifft(&mut evals, self.shift);
+        self.fft_plan.coset_ifft(&mut evals, self.shift);
         evals
     }
 
     pub fn shift(&self) -> T {
         self.shift
     }
+
+    // The same domain over a subfield `B`; the shift and the generator must both lie in `B`
+    pub fn to_base_coset<B: Field>(&self) -> Coset<B>
+    where
+        T: ExtensionOf<B>,
+    {
+        let shift = self
+            .shift
+            .to_base()
+            .expect("coset shift is not in the base field");
+        let coset = Coset::new(self.size(), shift);
+        assert!(T::from(coset.generator()) == self.generator());
+        coset
+    }
 }
 
 #[cfg(test)]
@@ -235,17 +165,32 @@ mod tests {
         assert_eq!(a, c);
     }
 
+    #[test]
+    fn fft_matches_evaluation() {
+        // Large enough for the parallel kernel to split single butterflies
+        let poly = Polynomial::random_polynomial(1 << 11);
+        let coset = Coset::new(1 << 11, Fp64::random_element());
+        let evals = coset.fft(poly.coefficients().clone());
+        for (i, v) in evals.iter().enumerate() {
+            assert_eq!(*v, poly.evaluation_at(coset.element_at(i)));
+        }
+        assert_eq!(coset.ifft(evals), *poly.coefficients());
+    }
+
     #[test]
     fn all_elements() {
         let r = Fp64::random_element();
         let coset = Coset::new(32, r);
         let elements = coset.all_elements();
         assert_eq!(elements[0], r);
-        let omega = coset.fft_eval_domain.omega();
+        let omega = coset.generator();
         for i in 0..elements.len() - 1 {
             assert_eq!(elements[i] * omega, elements[i + 1]);
         }
         assert_eq!(*elements.last().unwrap() * omega, elements[0]);
+        assert!(elements.iter().all(|x| coset.contains(*x)));
+        assert!(!coset.contains(r * r));
+        assert!(!coset.contains(Fp64::from_int(0)));
     }
 
     #[test]
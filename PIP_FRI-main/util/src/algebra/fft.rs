@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::field::Field;
+use crate::batch_bit_reverse;
+
+// Twiddles for every layer of a radix-2 domain. The layer with half-size m reads
+// `twiddles[m - 1..2 * m - 1]`, the first m powers of a primitive 2m-th root of unity,
+// so the plan of a subgroup is a prefix of the plan of the whole group and can share it.
+#[derive(Debug, Clone)]
+pub struct FftPlan<T: Field> {
+    order: usize,
+    omega: T,
+    twiddles: Arc<Vec<T>>,
+}
+
+impl<T: Field> FftPlan<T> {
+    pub fn new(order: usize) -> Self {
+        assert!(order.is_power_of_two());
+        let omega = T::get_generator(order);
+        let mut twiddles = vec![T::from_int(1); order - 1];
+        let half = order / 2;
+        let mut w = T::from_int(1);
+        for k in 0..half {
+            twiddles[half - 1 + k] = w;
+            w *= omega;
+        }
+        // A smaller layer takes every other twiddle of the layer above it
+        let mut m = half / 2;
+        while m > 0 {
+            for k in 0..m {
+                twiddles[m - 1 + k] = twiddles[2 * m - 1 + 2 * k];
+            }
+            m /= 2;
+        }
+        FftPlan {
+            order,
+            omega,
+            twiddles: Arc::new(twiddles),
+        }
+    }
+
+    // The plan of the subgroup of the given order, sharing the twiddle table
+    pub fn sub_plan(&self, order: usize) -> Self {
+        assert!(order.is_power_of_two() && order <= self.order);
+        FftPlan {
+            order,
+            omega: self.omega.pow(self.order / order),
+            twiddles: self.twiddles.clone(),
+        }
+    }
+
+    #[inline]
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    #[inline]
+    pub fn omega(&self) -> T {
+        self.omega
+    }
+
+    // omega^index, read from the table of the last layer without any multiplication
+    #[inline]
+    pub fn omega_pow(&self, index: usize) -> T {
+        if self.order == 1 {
+            return T::from_int(1);
+        }
+        let half = self.order / 2;
+        let index = index % self.order;
+        if index < half {
+            self.twiddles[half - 1 + index]
+        } else {
+            -self.twiddles[index - 1]
+        }
+    }
+
+    #[inline]
+    fn layer(&self, m: usize) -> &[T] {
+        &self.twiddles[m - 1..2 * m - 1]
+    }
+
+    // Evaluations at omega^i of the polynomial with coefficients `a`, in place
+    pub fn fft(&self, a: &mut [T]) {
+        let n = a.len();
+        assert_eq!(n, self.order);
+        if n <= 1 {
+            return;
+        }
+        let log_n = n.ilog2() as usize;
+        bit_reverse_permute(a, log_n);
+        let mut m = 1;
+        if log_n % 2 == 1 {
+            radix2_layer(a, self.layer(1));
+            m = 2;
+        }
+        while m < n {
+            radix4_layer(a, self.layer(m), self.layer(m * 2));
+            m <<= 2;
+        }
+    }
+
+    // The inverse transform reuses the forward twiddles: evaluating at omega^(-i)
+    // is evaluating at omega^(n - i)
+    pub fn ifft(&self, a: &mut [T]) {
+        self.fft(a);
+        a[1..].reverse();
+        scale(a, T::from_int(self.order as u64).inverse());
+    }
+
+    pub fn coset_fft(&self, a: &mut [T], shift: T) {
+        multiply_by_coset(a, shift);
+        self.fft(a);
+    }
+
+    pub fn coset_ifft(&self, a: &mut [T], shift: T) {
+        self.ifft(a);
+        multiply_by_coset(a, shift.inverse());
+    }
+}
+
+// Slices shorter than this are left to a single thread
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK: usize = 1 << 10;
+
+#[cfg(not(feature = "parallel"))]
+fn multiply_by_coset<T: Field>(a: &mut [T], shift: T) {
+    let mut t = shift;
+    for x in a.iter_mut().skip(1) {
+        *x *= t;
+        t *= shift;
+    }
+}
+
+// Every chunk starts from its own power of the shift
+#[cfg(feature = "parallel")]
+fn multiply_by_coset<T: Field>(a: &mut [T], shift: T) {
+    a.par_chunks_mut(PARALLEL_CHUNK)
+        .enumerate()
+        .for_each(|(c, chunk)| {
+            let mut t = shift.pow(c * PARALLEL_CHUNK);
+            for x in chunk {
+                *x *= t;
+                t *= shift;
+            }
+        });
+}
+
+#[cfg(not(feature = "parallel"))]
+fn scale<T: Field>(a: &mut [T], t: T) {
+    for i in a {
+        *i *= t;
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn scale<T: Field>(a: &mut [T], t: T) {
+    a.par_iter_mut().for_each(|i| *i *= t);
+}
+
+#[cfg(not(feature = "parallel"))]
+fn bit_reverse_permute<T: Field>(a: &mut [T], log_n: usize) {
+    let rank = batch_bit_reverse(log_n);
+    for i in 0..a.len() {
+        if i < rank[i] {
+            (a[i], a[rank[i]]) = (a[rank[i]], a[i]);
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn bit_reverse_permute<T: Field>(a: &mut [T], log_n: usize) {
+    let rank = batch_bit_reverse(log_n);
+    let permuted = rank.par_iter().map(|&r| a[r]).collect::<Vec<_>>();
+    a.copy_from_slice(&permuted);
+}
+
+#[inline]
+fn radix2<T: Field>(lo: &mut [T], hi: &mut [T], twiddles: &[T]) {
+    for ((x, y), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles) {
+        let t = *w * *y;
+        *y = *x - t;
+        *x += t;
+    }
+}
+
+// Two radix-2 layers fused, so every element is loaded and stored once per pair of layers.
+// `w1` are the twiddles of the half-size m layer, `w2` and `w3` the two halves of the 2m layer.
+#[inline]
+fn radix4<T: Field>(
+    a0: &mut [T],
+    a1: &mut [T],
+    a2: &mut [T],
+    a3: &mut [T],
+    w1: &[T],
+    w2: &[T],
+    w3: &[T],
+) {
+    for k in 0..a0.len() {
+        let t1 = w1[k] * a1[k];
+        let t3 = w1[k] * a3[k];
+        let (b0, b1) = (a0[k] + t1, a0[k] - t1);
+        let (b2, b3) = (a2[k] + t3, a2[k] - t3);
+        let t2 = w2[k] * b2;
+        let t3 = w3[k] * b3;
+        a0[k] = b0 + t2;
+        a2[k] = b0 - t2;
+        a1[k] = b1 + t3;
+        a3[k] = b1 - t3;
+    }
+}
+
+#[inline]
+fn radix4_block<T: Field>(chunk: &mut [T], w1: &[T], w2: &[T]) {
+    let m = w1.len();
+    let (lo, hi) = chunk.split_at_mut(m * 2);
+    let (a0, a1) = lo.split_at_mut(m);
+    let (a2, a3) = hi.split_at_mut(m);
+    radix4(a0, a1, a2, a3, w1, &w2[..m], &w2[m..]);
+}
+
+#[cfg(not(feature = "parallel"))]
+fn radix2_layer<T: Field>(a: &mut [T], twiddles: &[T]) {
+    let m = twiddles.len();
+    for chunk in a.chunks_mut(m * 2) {
+        let (lo, hi) = chunk.split_at_mut(m);
+        radix2(lo, hi, twiddles);
+    }
+}
+
+// The radix-2 layer only ever runs first, where m = 1
+#[cfg(feature = "parallel")]
+fn radix2_layer<T: Field>(a: &mut [T], twiddles: &[T]) {
+    let m = twiddles.len();
+    a.par_chunks_mut(PARALLEL_CHUNK.max(m * 2))
+        .for_each(|block| {
+            for chunk in block.chunks_mut(m * 2) {
+                let (lo, hi) = chunk.split_at_mut(m);
+                radix2(lo, hi, twiddles);
+            }
+        });
+}
+
+#[cfg(not(feature = "parallel"))]
+fn radix4_layer<T: Field>(a: &mut [T], w1: &[T], w2: &[T]) {
+    for chunk in a.chunks_mut(w1.len() * 4) {
+        radix4_block(chunk, w1, w2);
+    }
+}
+
+// Early layers split over blocks of butterflies, late layers over a single wide butterfly
+#[cfg(feature = "parallel")]
+fn radix4_layer<T: Field>(a: &mut [T], w1: &[T], w2: &[T]) {
+    let m = w1.len();
+    if m * 4 <= PARALLEL_CHUNK {
+        a.par_chunks_mut(PARALLEL_CHUNK).for_each(|block| {
+            for chunk in block.chunks_mut(m * 4) {
+                radix4_block(chunk, w1, w2);
+            }
+        });
+        return;
+    }
+    let c = PARALLEL_CHUNK / 4;
+    for chunk in a.chunks_mut(m * 4) {
+        let (lo, hi) = chunk.split_at_mut(m * 2);
+        let (a0, a1) = lo.split_at_mut(m);
+        let (a2, a3) = hi.split_at_mut(m);
+        (
+            a0.par_chunks_mut(c),
+            a1.par_chunks_mut(c),
+            a2.par_chunks_mut(c),
+            a3.par_chunks_mut(c),
+            w1.par_chunks(c),
+            w2[..m].par_chunks(c),
+            w2[m..].par_chunks(c),
+        )
+            .into_par_iter()
+            .for_each(|(a0, a1, a2, a3, w1, w2, w3)| radix4(a0, a1, a2, a3, w1, w2, w3));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::field::fp64::Fp64;
+
+    #[test]
+    fn fft_matches_dft() {
+        for log_n in 0..8 {
+            let n = 1 << log_n;
+            let plan = FftPlan::<Fp64>::new(n);
+            let a = (0..n).map(|_| Fp64::random_element()).collect::<Vec<_>>();
+            let mut b = a.clone();
+            plan.fft(&mut b);
+            for (i, v) in b.iter().enumerate() {
+                let x = plan.omega().pow(i);
+                let expected = a
+                    .iter()
+                    .rev()
+                    .fold(Fp64::from_int(0), |acc, c| acc * x + *c);
+                assert_eq!(*v, expected);
+            }
+            plan.ifft(&mut b);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn sub_plan_and_powers() {
+        let plan = FftPlan::<Fp64>::new(64);
+        for i in 0..128 {
+            assert_eq!(plan.omega_pow(i), plan.omega().pow(i));
+        }
+        let sub = plan.sub_plan(16);
+        assert_eq!(sub.omega(), Fp64::get_generator(16));
+        for i in 0..16 {
+            assert_eq!(sub.omega_pow(i), sub.omega().pow(i));
+        }
+    }
+}
@@ -1,7 +1,10 @@
 pub mod fp64;
+pub mod fp64_ext;
 pub mod ft255;
 pub mod mersenne61_ext;
 
+use rand::RngCore;
+
 pub trait Field:
     Sized
     + Clone
@@ -26,10 +29,18 @@ pub trait Field:
     const INVERSE_2: Self;
 
     fn from_int(x: u64) -> Self;
-    fn random_element() -> Self;
+    fn random_element() -> Self {
+        Self::random_element_with(&mut rand::thread_rng())
+    }
+    // Uniform element drawn from the given rng, so that seeded runs are reproducible
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self;
+    // Maps 32 uniformly random bytes (e.g. a transcript challenge) to a field element
+    fn from_hash(hash: [u8; 32]) -> Self;
     fn inverse(&self) -> Self;
     fn is_zero(&self) -> bool;
     fn to_bytes(&self) -> Vec<u8>;
+    // Inverse of `to_bytes`; rejects inputs of the wrong length or outside the field
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
 
     fn get_generator(order: usize) -> Self {
         if (order & (order - 1)) != 0 || order > (1 << Self::LOG_ORDER) {
@@ -59,6 +70,26 @@ pub trait Field:
     }
 }
 
+// `Self` is an extension of the field `B`, whose elements embed through `From`
+pub trait ExtensionOf<B: Field>: Field + From<B> {
+    // Product with a base-field element, cheaper than lifting it first
+    fn mul_base(&self, rhs: B) -> Self;
+    // The element as a member of `B`, if it lies in the base field
+    fn to_base(&self) -> Option<B>;
+}
+
+impl<T: Field> ExtensionOf<T> for T {
+    #[inline]
+    fn mul_base(&self, rhs: T) -> T {
+        *self * rhs
+    }
+
+    #[inline]
+    fn to_base(&self) -> Option<T> {
+        Some(*self)
+    }
+}
+
 #[inline]
 pub fn as_bytes_vec<T: Field>(s: &[T]) -> Vec<u8> {
     let mut res = vec![];
@@ -123,6 +154,17 @@ mod field_tests {
         }
     }
 
+    pub fn bytes_round_trip<T: Field>() {
+        for _i in 0..10 {
+            let a = T::random_element();
+            let bytes = a.to_bytes();
+            assert_eq!(T::from_bytes(&bytes), Some(a));
+            assert_eq!(T::from_bytes(&bytes[1..]), None);
+        }
+        let all_ones = vec![0xffu8; T::from_int(0).to_bytes().len()];
+        assert_eq!(T::from_bytes(&all_ones), None);
+    }
+
     pub fn pow_and_generator<T: Field>() {
         assert_eq!(T::get_generator(1), T::from_int(1));
         let x = T::get_generator(1 << 32);
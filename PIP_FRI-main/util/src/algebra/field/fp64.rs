@@ -101,7 +101,7 @@ impl std::fmt::Display for Fp64 {
 }
 
 use super::Field;
-use rand::Rng;
+use rand::{Rng, RngCore};
 
 impl Field for Fp64 {
     const FIELD_NAME: &'static str = "Fp64";
@@ -120,11 +120,17 @@ impl Field for Fp64 {
         Fp64 { real: x }
     }
 
-    fn random_element() -> Self {
-        let r: u64 = rand::thread_rng().gen_range(0..MOD);
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self {
+        let r: u64 = rng.gen_range(0..MOD);
         Fp64 { real: r }
     }
 
+    fn from_hash(hash: [u8; 32]) -> Self {
+        Fp64 {
+            real: u64::from_le_bytes(hash[..8].try_into().unwrap()) % MOD,
+        }
+    }
+
     fn inverse(&self) -> Self {
         let mut x_gcd = 0i128;
         let mut y_gcd = 0i128;
@@ -142,9 +148,19 @@ impl Field for Fp64 {
         let x = self.real.to_le_bytes().to_vec();
         x
     }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let real = u64::from_le_bytes(bytes.try_into().ok()?);
+        if real >= MOD {
+            return None;
+        }
+        Some(Fp64 { real })
+    }
 }
 
 impl Fp64 {
+    pub const ZERO: Fp64 = Fp64 { real: 0 };
+
     fn ex_gcd(a: u64, b: u64, x_gcd: &mut i128, y_gcd: &mut i128) {
         let mut gcd_m = 0i128;
         let mut gcd_n = 1i128;
@@ -179,5 +195,6 @@ mod tests {
         mult_and_inverse::<Fp64>();
         assigns::<Fp64>();
         pow_and_generator::<Fp64>();
+        bytes_round_trip::<Fp64>();
     }
 }
@@ -0,0 +1,391 @@
+use super::fp64::Fp64;
+use super::{ExtensionOf, Field};
+use rand::RngCore;
+
+// Extensions of the Goldilocks field, so that challenges can be drawn from a field of
+// more than 128 bits while the committed polynomials stay in `Fp64`.
+// Fp64Ext2 = Fp64[X] / (X^2 - 7), Fp64Ext3 = Fp64[X] / (X^3 - 2)
+const EXT2_W: u64 = 7;
+const EXT3_W: u64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fp64Ext2 {
+    c0: Fp64,
+    c1: Fp64,
+}
+
+impl Fp64Ext2 {
+    pub fn new(c0: Fp64, c1: Fp64) -> Self {
+        Fp64Ext2 { c0, c1 }
+    }
+
+    pub fn from_base(x: Fp64) -> Self {
+        Fp64Ext2 {
+            c0: x,
+            c1: Fp64::ZERO,
+        }
+    }
+}
+
+impl From<Fp64> for Fp64Ext2 {
+    fn from(x: Fp64) -> Self {
+        Self::from_base(x)
+    }
+}
+
+impl std::ops::Neg for Fp64Ext2 {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Fp64Ext2 {
+            c0: -self.c0,
+            c1: -self.c1,
+        }
+    }
+}
+
+impl std::ops::Add for Fp64Ext2 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Fp64Ext2 {
+            c0: self.c0 + rhs.c0,
+            c1: self.c1 + rhs.c1,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Fp64Ext2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub for Fp64Ext2 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Fp64Ext2 {
+            c0: self.c0 - rhs.c0,
+            c1: self.c1 - rhs.c1,
+        }
+    }
+}
+
+impl std::ops::SubAssign for Fp64Ext2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Mul for Fp64Ext2 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let w = Fp64::from_int(EXT2_W);
+        Fp64Ext2 {
+            c0: self.c0 * rhs.c0 + w * self.c1 * rhs.c1,
+            c1: self.c0 * rhs.c1 + self.c1 * rhs.c0,
+        }
+    }
+}
+
+impl std::ops::MulAssign for Fp64Ext2 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl std::fmt::Display for Fp64Ext2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} + {} * X", self.c0, self.c1)
+    }
+}
+
+impl Field for Fp64Ext2 {
+    const FIELD_NAME: &'static str = "Fp64Ext2";
+    // Evaluation domains are kept inside the base field
+    const LOG_ORDER: u64 = Fp64::LOG_ORDER;
+    const ROOT_OF_UNITY: Self = Fp64Ext2 {
+        c0: Fp64::ROOT_OF_UNITY,
+        c1: Fp64::ZERO,
+    };
+    const INVERSE_2: Self = Fp64Ext2 {
+        c0: Fp64::INVERSE_2,
+        c1: Fp64::ZERO,
+    };
+
+    fn from_int(x: u64) -> Self {
+        Self::from_base(Fp64::from_int(x))
+    }
+
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self {
+        Fp64Ext2 {
+            c0: Fp64::random_element_with(rng),
+            c1: Fp64::random_element_with(rng),
+        }
+    }
+
+    fn from_hash(hash: [u8; 32]) -> Self {
+        let mut c1 = [0; 32];
+        c1[..8].copy_from_slice(&hash[8..16]);
+        Fp64Ext2 {
+            c0: Fp64::from_hash(hash),
+            c1: Fp64::from_hash(c1),
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        // (c0 + c1 X)(c0 - c1 X) = c0^2 - W c1^2 lies in the base field
+        let norm = self.c0 * self.c0 - Fp64::from_int(EXT2_W) * self.c1 * self.c1;
+        let norm_inv = norm.inverse();
+        Fp64Ext2 {
+            c0: self.c0 * norm_inv,
+            c1: -self.c1 * norm_inv,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.c0.to_bytes();
+        x.extend(self.c1.to_bytes());
+        x
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 16 {
+            return None;
+        }
+        Some(Fp64Ext2 {
+            c0: Fp64::from_bytes(&bytes[..8])?,
+            c1: Fp64::from_bytes(&bytes[8..])?,
+        })
+    }
+}
+
+impl ExtensionOf<Fp64> for Fp64Ext2 {
+    fn mul_base(&self, rhs: Fp64) -> Self {
+        Fp64Ext2 {
+            c0: self.c0 * rhs,
+            c1: self.c1 * rhs,
+        }
+    }
+
+    fn to_base(&self) -> Option<Fp64> {
+        if self.c1.is_zero() {
+            Some(self.c0)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fp64Ext3 {
+    c0: Fp64,
+    c1: Fp64,
+    c2: Fp64,
+}
+
+impl Fp64Ext3 {
+    pub fn new(c0: Fp64, c1: Fp64, c2: Fp64) -> Self {
+        Fp64Ext3 { c0, c1, c2 }
+    }
+
+    pub fn from_base(x: Fp64) -> Self {
+        Fp64Ext3 {
+            c0: x,
+            c1: Fp64::ZERO,
+            c2: Fp64::ZERO,
+        }
+    }
+}
+
+impl From<Fp64> for Fp64Ext3 {
+    fn from(x: Fp64) -> Self {
+        Self::from_base(x)
+    }
+}
+
+impl std::ops::Neg for Fp64Ext3 {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Fp64Ext3 {
+            c0: -self.c0,
+            c1: -self.c1,
+            c2: -self.c2,
+        }
+    }
+}
+
+impl std::ops::Add for Fp64Ext3 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Fp64Ext3 {
+            c0: self.c0 + rhs.c0,
+            c1: self.c1 + rhs.c1,
+            c2: self.c2 + rhs.c2,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Fp64Ext3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub for Fp64Ext3 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Fp64Ext3 {
+            c0: self.c0 - rhs.c0,
+            c1: self.c1 - rhs.c1,
+            c2: self.c2 - rhs.c2,
+        }
+    }
+}
+
+impl std::ops::SubAssign for Fp64Ext3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Mul for Fp64Ext3 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let w = Fp64::from_int(EXT3_W);
+        Fp64Ext3 {
+            c0: self.c0 * rhs.c0 + w * (self.c1 * rhs.c2 + self.c2 * rhs.c1),
+            c1: self.c0 * rhs.c1 + self.c1 * rhs.c0 + w * self.c2 * rhs.c2,
+            c2: self.c0 * rhs.c2 + self.c1 * rhs.c1 + self.c2 * rhs.c0,
+        }
+    }
+}
+
+impl std::ops::MulAssign for Fp64Ext3 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl std::fmt::Display for Fp64Ext3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} + {} * X + {} * X^2", self.c0, self.c1, self.c2)
+    }
+}
+
+impl Field for Fp64Ext3 {
+    const FIELD_NAME: &'static str = "Fp64Ext3";
+    // Evaluation domains are kept inside the base field
+    const LOG_ORDER: u64 = Fp64::LOG_ORDER;
+    const ROOT_OF_UNITY: Self = Fp64Ext3 {
+        c0: Fp64::ROOT_OF_UNITY,
+        c1: Fp64::ZERO,
+        c2: Fp64::ZERO,
+    };
+    const INVERSE_2: Self = Fp64Ext3 {
+        c0: Fp64::INVERSE_2,
+        c1: Fp64::ZERO,
+        c2: Fp64::ZERO,
+    };
+
+    fn from_int(x: u64) -> Self {
+        Self::from_base(Fp64::from_int(x))
+    }
+
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self {
+        Fp64Ext3 {
+            c0: Fp64::random_element_with(rng),
+            c1: Fp64::random_element_with(rng),
+            c2: Fp64::random_element_with(rng),
+        }
+    }
+
+    fn from_hash(hash: [u8; 32]) -> Self {
+        let (mut c1, mut c2) = ([0; 32], [0; 32]);
+        c1[..8].copy_from_slice(&hash[8..16]);
+        c2[..8].copy_from_slice(&hash[16..24]);
+        Fp64Ext3 {
+            c0: Fp64::from_hash(hash),
+            c1: Fp64::from_hash(c1),
+            c2: Fp64::from_hash(c2),
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        // Adjugate of the multiplication-by-self matrix, divided by the norm
+        let w = Fp64::from_int(EXT3_W);
+        let t0 = self.c0 * self.c0 - w * self.c1 * self.c2;
+        let t1 = w * self.c2 * self.c2 - self.c0 * self.c1;
+        let t2 = self.c1 * self.c1 - self.c0 * self.c2;
+        let norm = self.c0 * t0 + w * (self.c2 * t1 + self.c1 * t2);
+        let norm_inv = norm.inverse();
+        Fp64Ext3 {
+            c0: t0 * norm_inv,
+            c1: t1 * norm_inv,
+            c2: t2 * norm_inv,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero() && self.c2.is_zero()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.c0.to_bytes();
+        x.extend(self.c1.to_bytes());
+        x.extend(self.c2.to_bytes());
+        x
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 24 {
+            return None;
+        }
+        Some(Fp64Ext3 {
+            c0: Fp64::from_bytes(&bytes[..8])?,
+            c1: Fp64::from_bytes(&bytes[8..16])?,
+            c2: Fp64::from_bytes(&bytes[16..])?,
+        })
+    }
+}
+
+impl ExtensionOf<Fp64> for Fp64Ext3 {
+    fn mul_base(&self, rhs: Fp64) -> Self {
+        Fp64Ext3 {
+            c0: self.c0 * rhs,
+            c1: self.c1 * rhs,
+            c2: self.c2 * rhs,
+        }
+    }
+
+    fn to_base(&self) -> Option<Fp64> {
+        if self.c1.is_zero() && self.c2.is_zero() {
+            Some(self.c0)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::field_tests::*;
+    use super::*;
+
+    #[test]
+    fn test() {
+        add_and_sub::<Fp64Ext2>();
+        mult_and_inverse::<Fp64Ext2>();
+        assigns::<Fp64Ext2>();
+        pow_and_generator::<Fp64Ext2>();
+        bytes_round_trip::<Fp64Ext2>();
+
+        add_and_sub::<Fp64Ext3>();
+        mult_and_inverse::<Fp64Ext3>();
+        assigns::<Fp64Ext3>();
+        pow_and_generator::<Fp64Ext3>();
+        bytes_round_trip::<Fp64Ext3>();
+    }
+}
@@ -1,5 +1,6 @@
 use ff::{Field as Fd, PrimeField};
 use ff_derive_num::Num;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use super::Field;
@@ -48,8 +49,13 @@ impl Field for Ft255 {
     }
 
     #[inline]
-    fn random_element() -> Self {
-        Ft255::random(rand::thread_rng())
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self {
+        Ft255::random(rng)
+    }
+
+    #[inline]
+    fn from_hash(hash: [u8; 32]) -> Self {
+        Ft255::random(StdRng::from_seed(hash))
     }
 
     fn inverse(&self) -> Self {
@@ -58,12 +64,12 @@ impl Field for Ft255 {
 
     #[inline]
     fn to_bytes(&self) -> Vec<u8> {
-        let x = self
-            .0
-            .iter()
-            .flat_map(|x| x.to_le_bytes().to_vec())
-            .collect();
-        x
+        self.to_repr().0.to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let repr = Ft255Repr(bytes.try_into().ok()?);
+        Ft255::from_repr(repr).into()
     }
 }
 
@@ -78,5 +84,6 @@ mod tests {
         mult_and_inverse::<Ft255>();
         assigns::<Ft255>();
         pow_and_generator::<Ft255>();
+        bytes_round_trip::<Ft255>();
     }
 }
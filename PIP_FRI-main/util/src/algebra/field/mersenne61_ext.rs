@@ -1,6 +1,7 @@
 use super::Field;
+#[cfg(all(target_arch = "x86_64", not(feature = "portable")))]
 use core::arch::x86_64::_mulx_u64;
-use rand::Rng;
+use rand::{Rng, RngCore};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Mersenne61Ext {
@@ -66,10 +67,26 @@ impl std::ops::SubAssign for Mersenne61Ext {
     }
 }
 
+// Full 128-bit product of x and y as (hi, lo)
+#[cfg(all(target_arch = "x86_64", not(feature = "portable")))]
 #[inline]
-fn my_mult(x: u64, y: u64) -> u64 {
+fn mul_wide(x: u64, y: u64) -> (u64, u64) {
     let mut hi = 0;
     let lo = unsafe { _mulx_u64(x, y, &mut hi) };
+    (hi, lo)
+}
+
+// Used on every other target, and on x86_64 with the `portable` feature
+#[cfg(any(not(target_arch = "x86_64"), feature = "portable"))]
+#[inline]
+fn mul_wide(x: u64, y: u64) -> (u64, u64) {
+    let res = (x as u128) * (y as u128);
+    ((res >> 64) as u64, res as u64)
+}
+
+#[inline]
+fn my_mult(x: u64, y: u64) -> u64 {
+    let (hi, lo) = mul_wide(x, y);
     ((hi << 3) | (lo >> 61)) + (lo & MOD)
 }
 
@@ -140,14 +157,21 @@ impl Field for Mersenne61Ext {
     }
 
     #[inline]
-    fn random_element() -> Self {
-        let mut rng = rand::thread_rng();
+    fn random_element_with<R: RngCore>(rng: &mut R) -> Self {
         Mersenne61Ext {
             real: rng.gen_range(0..MOD),
             image: rng.gen_range(0..MOD),
         }
     }
 
+    #[inline]
+    fn from_hash(hash: [u8; 32]) -> Self {
+        Mersenne61Ext {
+            real: u64::from_le_bytes(hash[..8].try_into().unwrap()) % MOD,
+            image: u64::from_le_bytes(hash[8..16].try_into().unwrap()) % MOD,
+        }
+    }
+
     fn inverse(&self) -> Self {
         let p = 2305843009213693951u128;
         let mut n = p * p - 2;
@@ -163,11 +187,25 @@ impl Field for Mersenne61Ext {
         ret
     }
 
+    // Both coordinates, so that Merkle leaves and the transcript bind the whole element
     #[inline]
     fn to_bytes(&self) -> Vec<u8> {
-        let x = self.real.to_le_bytes().to_vec();
+        let mut x = self.real.to_le_bytes().to_vec();
+        x.extend_from_slice(&self.image.to_le_bytes());
         x
     }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 16 {
+            return None;
+        }
+        let real = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let image = u64::from_le_bytes(bytes[8..].try_into().unwrap());
+        if real >= MOD || image >= MOD {
+            return None;
+        }
+        Some(Mersenne61Ext { real, image })
+    }
 }
 
 #[cfg(test)]
@@ -181,5 +219,26 @@ mod tests {
         mult_and_inverse::<Mersenne61Ext>();
         assigns::<Mersenne61Ext>();
         pow_and_generator::<Mersenne61Ext>();
+        bytes_round_trip::<Mersenne61Ext>();
+    }
+
+    #[test]
+    fn mult_matches_u128() {
+        let p = MOD as u128;
+        for _ in 0..1000 {
+            let x = Mersenne61Ext::random_element();
+            let y = Mersenne61Ext::random_element();
+            let (a, b) = (x.real as u128, x.image as u128);
+            let (c, d) = (y.real as u128, y.image as u128);
+            let real = (a * c % p + p - b * d % p) % p;
+            let image = (a * d + b * c) % p;
+            assert_eq!(
+                x * y,
+                Mersenne61Ext {
+                    real: real as u64,
+                    image: image as u64
+                }
+            );
+        }
     }
 }
@@ -1,3 +1,5 @@
+use rand::RngCore;
+
 use crate::batch_bit_reverse;
 
 use super::coset::Coset;
@@ -22,8 +24,12 @@ impl<T: Field> Polynomial<T> {
     }
 
     pub fn random_polynomial(degree: usize) -> Polynomial<T> {
+        Self::random_polynomial_with(degree, &mut rand::thread_rng())
+    }
+
+    pub fn random_polynomial_with<R: RngCore>(degree: usize, rng: &mut R) -> Polynomial<T> {
         Polynomial {
-            coefficients: (0..degree).map(|_| Field::random_element()).collect(),
+            coefficients: (0..degree).map(|_| T::random_element_with(rng)).collect(),
         }
     }
 
@@ -87,6 +93,38 @@ impl<T: Field> VanishingPolynomial<T> {
     }
 }
 
+// Turns the coefficients of a multilinear polynomial into its evaluations over the hypercube,
+// in place. Bit j of an index stands for variable j on both sides, so a chunk of aligned
+// entries can be transformed on its own in the variables of its low bits.
+pub fn hypercube_transform<T: Field>(values: &mut [T]) {
+    let n = values.len();
+    let mut m = 1;
+    while m < n {
+        for j in (0..n).step_by(m * 2) {
+            for k in 0..m {
+                let tmp = values[j + k];
+                values[j + k + m] += tmp;
+            }
+        }
+        m *= 2;
+    }
+}
+
+// Inverse of `hypercube_transform`
+pub fn inverse_hypercube_transform<T: Field>(values: &mut [T]) {
+    let n = values.len();
+    let mut m = 1;
+    while m < n {
+        for j in (0..n).step_by(m * 2) {
+            for k in 0..m {
+                let tmp = values[j + k];
+                values[j + k + m] -= tmp;
+            }
+        }
+        m *= 2;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MultilinearPolynomial<T: Field> {
     coefficients: Vec<T>,
@@ -107,16 +145,23 @@ impl<T: Field> MultilinearPolynomial<T> {
                 (res[i], res[rank[i]]) = (res[rank[i]], res[i]);
             }
         }
-        for i in 0..log_n {
-            let m = 1 << i;
-            for j in (0..n).step_by(m * 2) {
-                for k in 0..m {
-                    let tmp = res[j + k];
-                    res[j + k + m] += tmp;
-                }
+        hypercube_transform(&mut res);
+        res
+    }
+
+    // Inverse of `evaluate_hypercube`
+    pub fn from_hypercube(evaluations: Vec<T>) -> Self {
+        let n = evaluations.len();
+        let log_n = n.ilog2() as usize;
+        let mut res = evaluations;
+        inverse_hypercube_transform(&mut res);
+        let rank = batch_bit_reverse(log_n);
+        for i in 0..n {
+            if i < rank[i] {
+                (res[i], res[rank[i]]) = (res[rank[i]], res[i]);
             }
         }
-        res
+        MultilinearPolynomial::new(res)
     }
 
     pub fn new(coefficients: Vec<T>) -> Self {
@@ -141,13 +186,30 @@ impl<T: Field> MultilinearPolynomial<T> {
     }
 
     pub fn random_polynomial(variable_num: usize) -> Self {
+        Self::random_polynomial_with(variable_num, &mut rand::thread_rng())
+    }
+
+    pub fn random_polynomial_with<R: RngCore>(variable_num: usize, rng: &mut R) -> Self {
         MultilinearPolynomial {
             coefficients: (0..(1 << variable_num))
-                .map(|_| Field::random_element())
+                .map(|_| T::random_element_with(rng))
                 .collect(),
         }
     }
 
+    // f + x_n * r for a random r, which agrees with f wherever the new variable x_n is 0.
+    // Its univariate form is f(X) + X^(2^n) r(X), so any 2^n of its codeword values are
+    // uniformly random and reveal nothing about f.
+    pub fn random_extension(&self) -> Self {
+        self.random_extension_with(&mut rand::thread_rng())
+    }
+
+    pub fn random_extension_with<R: RngCore>(&self, rng: &mut R) -> Self {
+        let mut coefficients = self.coefficients.clone();
+        coefficients.extend((0..self.coefficients.len()).map(|_| T::random_element_with(rng)));
+        MultilinearPolynomial { coefficients }
+    }
+
     pub fn evaluate(&self, point: &Vec<T>) -> T {
         let len = self.coefficients.len();
         assert_eq!(1 << point.len(), self.coefficients.len());
@@ -217,6 +279,7 @@ mod test {
 
     use super::super::field::fp64::Fp64;
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn evaluation() {
@@ -265,6 +328,10 @@ mod test {
             point.reverse();
             assert_eq!(i.1, poly.evaluate(&point));
         }
+        assert_eq!(
+            MultilinearPolynomial::from_hypercube(poly.evaluate_hypercube()).coefficients,
+            poly.coefficients
+        );
 
         let point = (0..8).map(|_| Mersenne61Ext::random_element()).collect();
         let v = poly.evaluate(&point);
@@ -277,4 +344,20 @@ mod test {
         }
         assert_eq!(v, sum);
     }
+
+    #[test]
+    fn seeded_randomness() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let poly = MultilinearPolynomial::<Mersenne61Ext>::random_polynomial_with(8, &mut rng);
+        let univariate = Polynomial::<Fp64>::random_polynomial_with(32, &mut rng);
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            poly.coefficients,
+            MultilinearPolynomial::random_polynomial_with(8, &mut rng).coefficients
+        );
+        assert_eq!(
+            univariate.coefficients,
+            Polynomial::random_polynomial_with(32, &mut rng).coefficients
+        );
+    }
 }
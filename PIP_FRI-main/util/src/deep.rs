@@ -0,0 +1,88 @@
+use crate::algebra::{
+    coset::Coset,
+    field::{batch_inverse, Field},
+};
+use crate::error::VerifyError;
+use crate::merkle_tree::MERKLE_ROOT_SIZE;
+use crate::query_result::QueryResult;
+use crate::serialize::{write_field, write_usize, ByteReader};
+use crate::transcript::Transcript;
+
+// DEEP-FRI samples a point z outside the domain once the polynomials are committed. The
+// prover sends every f_j(z) and commits the quotient of the random combination of the f_j
+// by X - z, which the verifier checks against the f_j at its first-round queries and
+// tests for low degree along with everything else.
+#[derive(Clone)]
+pub struct DeepOpening<T: Field> {
+    pub root: [u8; MERKLE_ROOT_SIZE],
+    pub evaluations: Vec<T>,
+    pub proof: QueryResult<T>,
+}
+
+impl<T: Field> DeepOpening<T> {
+    pub fn write_option(deep: &Option<Self>, buf: &mut Vec<u8>) {
+        match deep {
+            None => buf.push(0),
+            Some(deep) => {
+                buf.push(1);
+                buf.extend_from_slice(&deep.root);
+                write_usize(buf, deep.evaluations.len());
+                for x in &deep.evaluations {
+                    write_field(buf, x);
+                }
+                deep.proof.write_bytes(buf);
+            }
+        }
+    }
+
+    pub fn read_option(reader: &mut ByteReader) -> Result<Option<Self>, VerifyError> {
+        match reader.read_bytes(1)?[0] {
+            0 => Ok(None),
+            1 => {
+                let root = reader.read_root()?;
+                let len = reader.read_usize()?;
+                let evaluations = (0..len)
+                    .map(|_| reader.read_field())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Some(DeepOpening {
+                    root,
+                    evaluations,
+                    proof: QueryResult::read_bytes(reader)?,
+                }))
+            }
+            _ => Err(VerifyError::MalformedProof),
+        }
+    }
+}
+
+// The out-of-domain point, squeezed from the transcript again for as long as it lands on the
+// coset. The prover and the verifier draw it the same way, so they agree on it.
+pub fn out_of_domain_point<T: Field>(transcript: &mut Transcript, coset: &Coset<T>) -> T {
+    loop {
+        let point = transcript.challenge_field();
+        if !coset.contains(point) {
+            return point;
+        }
+    }
+}
+
+// The evaluations of (f(X) - value) / (X - point) over the coset, from those of f. The point
+// has to be outside the coset, where X - point never vanishes.
+pub fn quotient<T: Field>(values: &[T], coset: &Coset<T>, point: T, value: T) -> Vec<T> {
+    assert!(
+        !coset.contains(point),
+        "the quotient point lies on the evaluation coset"
+    );
+    let inv = batch_inverse(
+        &coset
+            .all_elements()
+            .into_iter()
+            .map(|x| x - point)
+            .collect(),
+    );
+    values
+        .iter()
+        .zip(inv)
+        .map(|(x, inv)| (*x - value) * inv)
+        .collect()
+}
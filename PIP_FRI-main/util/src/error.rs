@@ -0,0 +1,78 @@
+use std::fmt;
+
+// Reasons a verifier rejects a proof. `round` is the folding round and `index` the
+// queried leaf index in that round's domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    MalformedProof,
+    MerklePath { round: usize },
+    MissingValue { round: usize, index: usize },
+    FoldConsistency { round: usize, index: usize },
+    FinalValue { index: usize },
+    Evaluation { round: usize, index: usize },
+    Sumcheck { round: usize },
+    Tuple { round: usize },
+    Quotient { index: usize },
+    ProofOfWork,
+    ClaimedValue,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::MalformedProof => write!(f, "malformed proof"),
+            VerifyError::MerklePath { round } => {
+                write!(f, "invalid Merkle path in round {}", round)
+            }
+            VerifyError::MissingValue { round, index } => {
+                write!(f, "missing value at index {} in round {}", index, round)
+            }
+            VerifyError::FoldConsistency { round, index } => {
+                write!(
+                    f,
+                    "inconsistent folding at index {} in round {}",
+                    index, round
+                )
+            }
+            VerifyError::FinalValue { index } => {
+                write!(f, "final value mismatch at index {}", index)
+            }
+            VerifyError::Evaluation { round, index } => {
+                write!(
+                    f,
+                    "evaluation mismatch at index {} in round {}",
+                    index, round
+                )
+            }
+            VerifyError::Sumcheck { round } => {
+                write!(f, "sumcheck mismatch in round {}", round)
+            }
+            VerifyError::Tuple { round } => {
+                write!(f, "inconsistent evaluation tuple in round {}", round)
+            }
+            VerifyError::Quotient { index } => {
+                write!(f, "out-of-domain quotient mismatch at index {}", index)
+            }
+            VerifyError::ProofOfWork => write!(f, "invalid proof of work"),
+            VerifyError::ClaimedValue => write!(f, "the proof opens to another value"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// Reasons a scheme refuses the parameters it is set up with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterError {
+    HidingUnsupported,
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterError::HidingUnsupported => write!(f, "the scheme does not hide"),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
@@ -0,0 +1,324 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::algebra::{
+    coset::Coset,
+    field::{batch_inverse, ExtensionOf, Field},
+};
+
+// A linear code built level by level. A message m of 2^d elements is encoded at a level
+// as (l + t * h, l - t * h), where l and h are the codewords of its even and odd halves
+// one level down and t is the diagonal of the level; a single element is repeated.
+// Folding x = l + t * h and nx = l - t * h into l + c * h gives the codeword of
+// even + c * odd, the multilinear polynomial m with its first variable fixed to c.
+pub trait FoldableCode<T: Field>: Clone {
+    // The code for messages in `variable_num` variables at rate 2^-log_blowup
+    fn setup(variable_num: usize, log_blowup: usize) -> Self;
+
+    // The same code with its coset shift or seed drawn from `rng`, and its diagonals in a
+    // subfield `B`, so that it encodes messages over `B` too
+    fn setup_with<B: Field, R: RngCore>(
+        variable_num: usize,
+        log_blowup: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        T: ExtensionOf<B>;
+
+    // The length of the top-level codewords; those of `level` are `size() >> level` long
+    fn size(&self) -> usize;
+
+    // Entry `index` of the diagonal of `level`, which has half as many entries as its codewords
+    fn diagonal(&self, level: usize, index: usize) -> T;
+
+    fn diagonal_inv(&self, level: usize, index: usize) -> T {
+        self.diagonal(level, index).inverse()
+    }
+
+    // The top-level codeword of `message`, over a subfield `B` the diagonals lie in
+    fn encode<B: Field>(&self, message: Vec<B>) -> Vec<B>
+    where
+        T: ExtensionOf<B>,
+    {
+        let levels = message.len().ilog2() as usize;
+        let repetition = self.size() >> levels;
+        let mut codewords = message
+            .into_iter()
+            .flat_map(|x| vec![x; repetition])
+            .collect::<Vec<_>>();
+        // The codewords of a level are indexed by the message entries they cover mod 2^level
+        for level in (0..levels).rev() {
+            let n = self.size() >> (level + 1);
+            let diagonal = (0..n)
+                .map(|i| {
+                    self.diagonal(level, i)
+                        .to_base()
+                        .expect("diagonal is not in the base field")
+                })
+                .collect::<Vec<_>>();
+            let mut next = Vec::with_capacity(codewords.len());
+            for s in 0..1 << level {
+                let l = &codewords[s * n..(s + 1) * n];
+                let h = &codewords[(s + (1 << level)) * n..(s + (1 << level) + 1) * n];
+                next.extend(
+                    l.iter()
+                        .zip(h)
+                        .zip(&diagonal)
+                        .map(|((l, h), t)| *l + *t * *h),
+                );
+                next.extend(
+                    l.iter()
+                        .zip(h)
+                        .zip(&diagonal)
+                        .map(|((l, h), t)| *l - *t * *h),
+                );
+            }
+            codewords = next;
+        }
+        codewords
+    }
+
+    // Folds the entries `index` and `index + n` of a codeword of `level` with size 2n
+    fn fold(&self, level: usize, index: usize, challenge: T, x: T, nx: T) -> T {
+        (x + nx + challenge * (x - nx) * self.diagonal_inv(level, index)) * T::INVERSE_2
+    }
+
+    // Entry `index` of the codeword of `message` at `level`
+    fn evaluate(&self, level: usize, message: &[T], index: usize) -> T {
+        if message.len() == 1 {
+            return message[0];
+        }
+        let n = self.size() >> (level + 1);
+        let even = message.iter().step_by(2).copied().collect::<Vec<_>>();
+        let odd = message
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>();
+        let l = self.evaluate(level + 1, &even, index % n);
+        let h = self.diagonal(level, index % n) * self.evaluate(level + 1, &odd, index % n);
+        if index < n {
+            l + h
+        } else {
+            l - h
+        }
+    }
+}
+
+// Evaluations over a coset and its squares, whose diagonals are the coset elements
+#[derive(Debug, Clone)]
+pub struct ReedSolomonCode<T: Field> {
+    cosets: Vec<Coset<T>>,
+    // shift^-2 for the coset of every level, which turns `element_inv_at`, shift * omega^-i,
+    // into the inverse of the diagonal entry
+    shift_inv_squares: Vec<T>,
+}
+
+impl<T: Field> ReedSolomonCode<T> {
+    pub fn new(variable_num: usize, log_blowup: usize, shift: T) -> Self {
+        let mut cosets = vec![Coset::new(1 << (variable_num + log_blowup), shift)];
+        for i in 1..variable_num {
+            cosets.push(cosets[i - 1].pow(2));
+        }
+        let shift_inv_squares = cosets.iter().map(|x| x.shift().inverse().pow(2)).collect();
+        ReedSolomonCode {
+            cosets,
+            shift_inv_squares,
+        }
+    }
+
+    pub fn cosets(&self) -> &Vec<Coset<T>> {
+        &self.cosets
+    }
+}
+
+impl<T: Field> FoldableCode<T> for ReedSolomonCode<T> {
+    fn setup(variable_num: usize, log_blowup: usize) -> Self {
+        ReedSolomonCode::new(variable_num, log_blowup, T::from_int(1))
+    }
+
+    fn setup_with<B: Field, R: RngCore>(variable_num: usize, log_blowup: usize, rng: &mut R) -> Self
+    where
+        T: ExtensionOf<B>,
+    {
+        let shift = loop {
+            let shift = B::random_element_with(rng);
+            if !shift.is_zero() {
+                break T::from(shift);
+            }
+        };
+        ReedSolomonCode::new(variable_num, log_blowup, shift)
+    }
+
+    fn size(&self) -> usize {
+        self.cosets[0].size()
+    }
+
+    fn diagonal(&self, level: usize, index: usize) -> T {
+        self.cosets[level].element_at(index)
+    }
+
+    fn diagonal_inv(&self, level: usize, index: usize) -> T {
+        self.shift_inv_squares[level] * self.cosets[level].element_inv_at(index)
+    }
+
+    fn encode<B: Field>(&self, message: Vec<B>) -> Vec<B>
+    where
+        T: ExtensionOf<B>,
+    {
+        self.cosets[0].to_base_coset::<B>().fft(message)
+    }
+
+    // The message is a univariate polynomial, evaluated at the element of the level's coset
+    fn evaluate(&self, level: usize, message: &[T], index: usize) -> T {
+        match self.cosets.get(level) {
+            Some(coset) => {
+                let x = coset.element_at(index);
+                message
+                    .iter()
+                    .rev()
+                    .fold(T::from_int(0), |acc, c| acc * x + *c)
+            }
+            None => message[0],
+        }
+    }
+}
+
+// The random foldable codes of Basefold, which need no FFT-friendly field. The diagonals
+// are drawn from an rng seeded per level, so that anyone can rebuild them from the seed.
+#[derive(Debug, Clone)]
+pub struct RandomFoldableCode<T: Field> {
+    size: usize,
+    diagonals: Vec<Vec<T>>,
+    diagonals_inv: Vec<Vec<T>>,
+}
+
+impl<T: Field> RandomFoldableCode<T> {
+    pub fn new(variable_num: usize, log_blowup: usize, seed: &[u8]) -> Self {
+        Self::over_subfield::<T>(variable_num, log_blowup, seed)
+    }
+
+    // Diagonals drawn from `B`, so that messages over `B` have their codewords over `B`
+    pub fn over_subfield<B: Field>(variable_num: usize, log_blowup: usize, seed: &[u8]) -> Self
+    where
+        T: ExtensionOf<B>,
+    {
+        let size = 1 << (variable_num + log_blowup);
+        let diagonals = (0..variable_num)
+            .map(|level| {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(b"foldable code");
+                hasher.update(seed);
+                hasher.update(&(level as u64).to_le_bytes());
+                let mut rng = StdRng::from_seed(hasher.finalize().into());
+                (0..size >> (level + 1))
+                    .map(|_| loop {
+                        let t = B::random_element_with(&mut rng);
+                        if !t.is_zero() {
+                            break T::from(t);
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        RandomFoldableCode {
+            size,
+            diagonals_inv: diagonals.iter().map(batch_inverse).collect(),
+            diagonals,
+        }
+    }
+}
+
+impl<T: Field> FoldableCode<T> for RandomFoldableCode<T> {
+    fn setup(variable_num: usize, log_blowup: usize) -> Self {
+        RandomFoldableCode::new(variable_num, log_blowup, T::FIELD_NAME.as_bytes())
+    }
+
+    fn setup_with<B: Field, R: RngCore>(variable_num: usize, log_blowup: usize, rng: &mut R) -> Self
+    where
+        T: ExtensionOf<B>,
+    {
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        RandomFoldableCode::over_subfield::<B>(variable_num, log_blowup, &seed)
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn diagonal(&self, level: usize, index: usize) -> T {
+        self.diagonals[level][index]
+    }
+
+    fn diagonal_inv(&self, level: usize, index: usize) -> T {
+        self.diagonals_inv[level][index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::field::{
+        fp64::Fp64, fp64_ext::Fp64Ext2, ft255::Ft255, mersenne61_ext::Mersenne61Ext,
+    };
+
+    // Folding a codeword gives the codeword of the folded message one level down
+    fn folds_to_folded_message<T: Field, C: FoldableCode<T>>(
+        code: &C,
+        variable_num: usize,
+        rng: &mut StdRng,
+    ) {
+        let message = (0..1 << variable_num)
+            .map(|_| T::random_element_with(rng))
+            .collect::<Vec<_>>();
+        let codeword = code.encode(message.clone());
+        assert_eq!(codeword.len(), code.size());
+        for (i, v) in codeword.iter().enumerate() {
+            assert_eq!(*v, code.evaluate(0, &message, i));
+        }
+        let challenge = T::random_element_with(rng);
+        let folded = message
+            .chunks(2)
+            .map(|x| x[0] + challenge * x[1])
+            .collect::<Vec<_>>();
+        let n = code.size() / 2;
+        for i in 0..n {
+            let v = code.fold(0, i, challenge, codeword[i], codeword[i + n]);
+            assert_eq!(v, code.evaluate(1, &folded, i));
+        }
+    }
+
+    #[test]
+    fn foldable_codes() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let code = ReedSolomonCode::<Mersenne61Ext>::setup(6, 2);
+        folds_to_folded_message(&code, 6, &mut rng);
+        let code = ReedSolomonCode::<Mersenne61Ext>::setup_with::<Mersenne61Ext, _>(6, 2, &mut rng);
+        assert_ne!(code.cosets()[0].shift(), Mersenne61Ext::from_int(1));
+        folds_to_folded_message(&code, 6, &mut rng);
+        for level in 0..6 {
+            assert_eq!(
+                code.diagonal_inv(level, 5),
+                code.diagonal(level, 5).inverse()
+            );
+        }
+        let code = RandomFoldableCode::<Ft255>::setup(6, 2);
+        folds_to_folded_message(&code, 6, &mut rng);
+        let code = RandomFoldableCode::<Ft255>::setup_with::<Ft255, _>(6, 2, &mut rng);
+        folds_to_folded_message(&code, 6, &mut rng);
+        // The shift of a code set up over a subfield lies in it
+        let code = ReedSolomonCode::<Fp64Ext2>::setup_with::<Fp64, _>(6, 2, &mut rng);
+        assert!(ExtensionOf::<Fp64>::to_base(&code.cosets()[0].shift()).is_some());
+        folds_to_folded_message(&code, 6, &mut rng);
+        let code = RandomFoldableCode::<Fp64Ext2>::over_subfield::<Fp64>(6, 2, b"seed");
+        folds_to_folded_message(&code, 6, &mut rng);
+        // A message over the subfield is encoded as its lift is
+        let message = (0..1 << 6)
+            .map(|_| Fp64::random_element_with(&mut rng))
+            .collect::<Vec<_>>();
+        let lifted = message.iter().map(|x| Fp64Ext2::from(*x)).collect();
+        let codeword = code.encode(message).into_iter().map(Fp64Ext2::from);
+        assert!(codeword.eq(code.encode(lifted)));
+    }
+}
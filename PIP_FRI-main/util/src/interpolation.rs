@@ -1,24 +1,48 @@
-use crate::merkle_tree::MERKLE_ROOT_SIZE;
+use crate::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
 use crate::query_result::QueryResult;
 use crate::{
     algebra::field::{as_bytes_vec, Field},
     merkle_tree::MerkleTreeProver,
 };
+use rand::RngCore;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 #[derive(Clone)]
-pub struct InterpolateValue<T: Field> {
+pub struct InterpolateValue<T: Field, H: MerkleHasher = Blake3Algorithm> {
     pub value: Vec<T>,
-    merkle_tree: MerkleTreeProver,
+    merkle_tree: MerkleTreeProver<H>,
 }
 
-impl<T: Field> InterpolateValue<T> {
+impl<T: Field, H: MerkleHasher> InterpolateValue<T, H> {
     pub fn new(value: Vec<T>) -> Self {
-        let len = value.len() / 2;
-        let merkle_tree = MerkleTreeProver::new(
-            (0..len)
-                .map(|i| as_bytes_vec(&[value[i], value[i + len]]))
-                .collect(),
-        );
+        Self::with_salts(value, None)
+    }
+
+    // Salted leaves keep the commitment from revealing the values that are never queried.
+    // The salts are drawn from `salt_rng`, and the leaves are unsalted without one.
+    pub fn with_salts(value: Vec<T>, salt_rng: Option<&mut dyn RngCore>) -> Self {
+        Self::with_arity(value, 1, salt_rng)
+    }
+
+    // Leaf i holds the 2^log_arity values at i + t * value.len() / 2^log_arity, the coset
+    // that a round folding 2^log_arity to 1 takes to the single value at i
+    pub fn with_arity(value: Vec<T>, log_arity: usize, salt_rng: Option<&mut dyn RngCore>) -> Self {
+        let len = value.len() >> log_arity;
+        let leaf = |i: usize| {
+            let coset = (0..1 << log_arity)
+                .map(|t| value[i + t * len])
+                .collect::<Vec<_>>();
+            as_bytes_vec(&coset)
+        };
+        #[cfg(not(feature = "parallel"))]
+        let leaves = (0..len).map(leaf).collect();
+        #[cfg(feature = "parallel")]
+        let leaves = (0..len).into_par_iter().map(leaf).collect();
+        let merkle_tree = match salt_rng {
+            Some(rng) => MerkleTreeProver::new_salted_with(leaves, rng),
+            None => MerkleTreeProver::new(leaves),
+        };
         Self { value, merkle_tree }
     }
 
@@ -30,16 +54,41 @@ impl<T: Field> InterpolateValue<T> {
         self.merkle_tree.commit()
     }
 
-    pub fn query(&self, leaf_indices: &Vec<usize>) -> QueryResult<T> {
-        let len = self.merkle_tree.leave_num();
-        let proof_values = leaf_indices
-            .iter()
-            .flat_map(|j| [(*j, self.value[*j]), (*j + len, self.value[*j + len])])
+    // `leaf_indices` have to be increasing, the order the values are sent in
+    pub fn query(&self, leaf_indices: &[usize]) -> QueryResult<T> {
+        let leave_num = self.merkle_tree.leave_num();
+        QueryResult::new(
+            leaf_indices,
+            leave_num,
+            self.value.len() / leave_num,
+            |i| self.value[i],
+            self.merkle_tree.open(leaf_indices),
+            self.merkle_tree.open_salts(leaf_indices),
+        )
+    }
+}
+
+// Folds the values one leaf of a codeword of size `size` holds, at index + t * size / k for
+// t < k, down to the value at `index` of the codeword k times smaller. `fold(step, i, x, nx)`
+// is one binary fold, from the values at i and i + size / 2^(step + 1) of the codeword
+// `step` folds after the first to the value at i of the next one.
+pub fn fold_leaf<V: Copy>(
+    values: &[V],
+    index: usize,
+    size: usize,
+    fold: impl Fn(usize, usize, V, V) -> V,
+) -> V {
+    let mut values = values.to_vec();
+    let mut size = size;
+    let mut step = 0;
+    while values.len() > 1 {
+        let half = values.len() / 2;
+        let stride = size / values.len();
+        values = (0..half)
+            .map(|t| fold(step, index + t * stride, values[t], values[t + half]))
             .collect();
-        let proof_bytes = self.merkle_tree.open(&leaf_indices);
-        QueryResult {
-            proof_bytes,
-            proof_values,
-        }
+        size /= 2;
+        step += 1;
     }
+    values[0]
 }
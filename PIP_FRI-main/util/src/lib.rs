@@ -1,15 +1,22 @@
 pub mod algebra {
     pub mod coset;
+    pub mod fft;
     pub mod field;
     pub mod polynomial;
 }
+pub mod deep;
+pub mod error;
+pub mod foldable_code;
 pub mod interpolation;
+pub mod mask;
 pub mod merkle_tree;
+pub mod parameters;
+pub mod pcs;
+pub mod poseidon;
 pub mod query_result;
-pub mod random_oracle;
-
-pub const CODE_RATE: usize = 3;
-pub const SECURITY_BITS: usize = 128;
+pub mod serialize;
+pub mod stream;
+pub mod transcript;
 
 pub fn split_n(mut n: usize) -> Vec<usize> {
     let mut res = vec![];
@@ -26,7 +33,11 @@ pub fn split_n(mut n: usize) -> Vec<usize> {
     res
 }
 
+#[cfg(not(feature = "parallel"))]
 fn batch_bit_reverse(log_n: usize) -> Vec<usize> {
+    if log_n == 0 {
+        return vec![0];
+    }
     let n = 1 << log_n;
     let mut res = (0..n).into_iter().map(|_| 0).collect::<Vec<usize>>();
     for i in 0..n {
@@ -34,3 +45,15 @@ fn batch_bit_reverse(log_n: usize) -> Vec<usize> {
     }
     res
 }
+
+#[cfg(feature = "parallel")]
+fn batch_bit_reverse(log_n: usize) -> Vec<usize> {
+    use rayon::prelude::*;
+    if log_n == 0 {
+        return vec![0];
+    }
+    (0..1usize << log_n)
+        .into_par_iter()
+        .map(|i| i.reverse_bits() >> (usize::BITS as usize - log_n))
+        .collect()
+}
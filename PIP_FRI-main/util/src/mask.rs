@@ -0,0 +1,51 @@
+use crate::algebra::field::Field;
+use crate::error::VerifyError;
+use crate::merkle_tree::MERKLE_ROOT_SIZE;
+use crate::query_result::QueryResult;
+use crate::serialize::{write_field, ByteReader};
+
+// A hiding opening adds a random polynomial, committed with salted leaves, to the committed
+// ones before folding, so that every later layer and sumcheck message is masked. The
+// verifier gets its root, its evaluation at the opening point and its first-round queries.
+#[derive(Clone)]
+pub struct MaskOpening<T: Field> {
+    pub root: [u8; MERKLE_ROOT_SIZE],
+    pub evaluation: T,
+    pub proof: QueryResult<T>,
+}
+
+impl<T: Field> MaskOpening<T> {
+    pub fn write_option(mask: &Option<Self>, buf: &mut Vec<u8>) {
+        match mask {
+            None => buf.push(0),
+            Some(mask) => {
+                buf.push(1);
+                buf.extend_from_slice(&mask.root);
+                write_field(buf, &mask.evaluation);
+                mask.proof.write_bytes(buf);
+            }
+        }
+    }
+
+    pub fn read_option(reader: &mut ByteReader) -> Result<Option<Self>, VerifyError> {
+        match reader.read_bytes(1)?[0] {
+            0 => Ok(None),
+            1 => Ok(Some(MaskOpening {
+                root: reader.read_root()?,
+                evaluation: reader.read_field()?,
+                proof: QueryResult::read_bytes(reader)?,
+            })),
+            _ => Err(VerifyError::MalformedProof),
+        }
+    }
+}
+
+// A hiding commitment is to the random extension of the polynomial in one more variable,
+// which is opened where that variable is 0
+pub fn hiding_point<T: Field>(point: &[T], hiding: bool) -> Vec<T> {
+    let mut point = point.to_vec();
+    if hiding {
+        point.push(T::from_int(0));
+    }
+    point
+}
@@ -1,6 +1,32 @@
-use rs_merkle::{Hasher, MerkleProof, MerkleTree};
+use std::marker::PhantomData;
 
-#[derive(Debug, Clone)]
+use rand::{Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rs_merkle::Hasher;
+use sha2::Digest;
+
+use crate::error::VerifyError;
+
+// A hash the Merkle trees can be built with. Every digest is MERKLE_ROOT_SIZE bytes, so
+// roots and authentication paths have the same encoding whichever hash is chosen.
+pub trait MerkleHasher:
+    Hasher<Hash = [u8; MERKLE_ROOT_SIZE]> + Copy + Send + Sync + 'static
+{
+    // Whether the hash can output `digest`; the verifier rejects any other node of a proof
+    // before hashing it
+    fn is_digest(_digest: &[u8; MERKLE_ROOT_SIZE]) -> bool {
+        true
+    }
+}
+
+impl MerkleHasher for Blake3Algorithm {}
+
+impl MerkleHasher for Sha256Algorithm {}
+
+impl MerkleHasher for Keccak256Algorithm {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Blake3Algorithm {}
 
 impl Hasher for Blake3Algorithm {
@@ -11,75 +37,279 @@ impl Hasher for Blake3Algorithm {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256Algorithm {}
+
+impl Hasher for Sha256Algorithm {
+    type Hash = [u8; MERKLE_ROOT_SIZE];
+
+    fn hash(data: &[u8]) -> [u8; MERKLE_ROOT_SIZE] {
+        sha2::Sha256::digest(data).into()
+    }
+}
+
+// The original Keccak padding, as used by Ethereum, rather than the SHA-3 one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keccak256Algorithm {}
+
+impl Hasher for Keccak256Algorithm {
+    type Hash = [u8; MERKLE_ROOT_SIZE];
+
+    fn hash(data: &[u8]) -> [u8; MERKLE_ROOT_SIZE] {
+        sha3::Keccak256::digest(data).into()
+    }
+}
+
 pub const MERKLE_ROOT_SIZE: usize = 32;
-#[derive(Clone)]
-pub struct MerkleTreeProver {
-    pub merkle_tree: MerkleTree<Blake3Algorithm>,
+pub const SALT_SIZE: usize = 16;
+
+// The (layer, index) of every node a batched opening of `leaf_indices` has to send, in the
+// order it is sent: layer by layer from the leaves up, each layer from left to right. The
+// paths of the leaves are merged, so a sibling that is itself on a path is recomputed by
+// the verifier rather than sent, and the paths stop at the cap, the layer holding
+// `cap_size` nodes. A node without a sibling is carried up unchanged, as in rs_merkle.
+pub fn opening_nodes(
+    leaf_indices: &[usize],
     leave_num: usize,
+    cap_size: usize,
+) -> Vec<(usize, usize)> {
+    let mut current = leaf_indices.to_vec();
+    current.sort();
+    current.dedup();
+    let mut nodes = vec![];
+    let mut layer_len = leave_num;
+    let mut layer = 0;
+    while layer_len > cap_size {
+        for (k, i) in current.iter().enumerate() {
+            let sibling = i ^ 1;
+            let known = if i % 2 == 0 {
+                current.get(k + 1) == Some(&sibling)
+            } else {
+                k > 0 && current[k - 1] == sibling
+            };
+            if !known && sibling < layer_len {
+                nodes.push((layer, sibling));
+            }
+        }
+        current = current.iter().map(|i| i / 2).collect();
+        current.dedup();
+        layer_len = layer_len.div_ceil(2);
+        layer += 1;
+    }
+    nodes
+}
+
+#[derive(Clone)]
+pub struct MerkleTreeProver<H: MerkleHasher = Blake3Algorithm> {
+    // From the leaf hashes up to the root
+    layers: Vec<Vec<[u8; MERKLE_ROOT_SIZE]>>,
+    // One per leaf, appended to it before hashing; empty for an unsalted tree
+    salts: Vec<[u8; SALT_SIZE]>,
+    _marker: PhantomData<H>,
 }
 
 #[derive(Debug, Clone)]
-pub struct MerkleTreeVerifier {
+pub struct MerkleTreeVerifier<H: MerkleHasher = Blake3Algorithm> {
     pub merkle_root: [u8; MERKLE_ROOT_SIZE],
     pub leave_number: usize,
+    // The nodes the openings are checked against, just the root unless a cap is stored
+    cap: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    _marker: PhantomData<H>,
 }
 
-impl MerkleTreeProver {
+impl<H: MerkleHasher> MerkleTreeProver<H> {
     pub fn new(leaf_values: Vec<Vec<u8>>) -> Self {
+        Self::with_salts(leaf_values, vec![])
+    }
+
+    // A fresh random salt in every leaf keeps the root and the authentication paths from
+    // revealing anything about the leaves that are never opened
+    pub fn new_salted(leaf_values: Vec<Vec<u8>>) -> Self {
+        Self::new_salted_with(leaf_values, &mut rand::thread_rng())
+    }
+
+    // Salts drawn from the given rng, so that seeded runs commit to the same root
+    pub fn new_salted_with<R: RngCore + ?Sized>(leaf_values: Vec<Vec<u8>>, rng: &mut R) -> Self {
+        let salts = (0..leaf_values.len()).map(|_| rng.gen()).collect();
+        Self::with_salts(leaf_values, salts)
+    }
+
+    fn with_salts(mut leaf_values: Vec<Vec<u8>>, salts: Vec<[u8; SALT_SIZE]>) -> Self {
+        assert!(!leaf_values.is_empty());
+        for (leaf, salt) in leaf_values.iter_mut().zip(salts.iter()) {
+            leaf.extend_from_slice(salt);
+        }
+        #[cfg(not(feature = "parallel"))]
+        let leaves = leaf_values.iter().map(|x| H::hash(x)).collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
         let leaves = leaf_values
-            .iter()
-            .map(|x| Blake3Algorithm::hash(x))
+            .par_iter()
+            .map(|x| H::hash(x))
             .collect::<Vec<_>>();
-        let merkle_tree = MerkleTree::<Blake3Algorithm>::from_leaves(&leaves);
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers.last().unwrap();
+            #[cfg(not(feature = "parallel"))]
+            let next = layer
+                .chunks(2)
+                .map(|x| H::concat_and_hash(&x[0], x.get(1)))
+                .collect();
+            #[cfg(feature = "parallel")]
+            let next = layer
+                .par_chunks(2)
+                .map(|x| H::concat_and_hash(&x[0], x.get(1)))
+                .collect();
+            layers.push(next);
+        }
         Self {
-            merkle_tree,
-            leave_num: leaf_values.len(),
+            layers,
+            salts,
+            _marker: PhantomData,
         }
     }
 
     pub fn leave_num(&self) -> usize {
-        self.leave_num
+        self.layers[0].len()
     }
 
     pub fn commit(&self) -> [u8; MERKLE_ROOT_SIZE] {
-        self.merkle_tree.root().unwrap()
+        self.layers.last().unwrap()[0]
+    }
+
+    // The layer 2^cap_height nodes below the root, or the leaves of a smaller tree
+    pub fn cap(&self, cap_height: usize) -> Vec<[u8; MERKLE_ROOT_SIZE]> {
+        let layer = self.layers.len().saturating_sub(cap_height + 1);
+        self.layers[layer].clone()
+    }
+
+    pub fn open(&self, leaf_indices: &[usize]) -> Vec<u8> {
+        self.open_to_cap(leaf_indices, 0)
+    }
+
+    // An opening checked against `cap(cap_height)` instead of the root
+    pub fn open_to_cap(&self, leaf_indices: &[usize], cap_height: usize) -> Vec<u8> {
+        let cap_size = self.cap(cap_height).len();
+        opening_nodes(leaf_indices, self.leave_num(), cap_size)
+            .into_iter()
+            .flat_map(|(layer, index)| self.layers[layer][index])
+            .collect()
     }
 
-    pub fn open(&self, leaf_indices: &Vec<usize>) -> Vec<u8> {
-        self.merkle_tree.proof(leaf_indices).to_bytes()
+    // The salts of the opened leaves, which the verifier needs to rehash them
+    pub fn open_salts(&self, leaf_indices: &[usize]) -> Vec<[u8; SALT_SIZE]> {
+        if self.salts.is_empty() {
+            return vec![];
+        }
+        leaf_indices.iter().map(|i| self.salts[*i]).collect()
     }
 }
 
-impl MerkleTreeVerifier {
+impl<H: MerkleHasher> MerkleTreeVerifier<H> {
     pub fn new(leave_number: usize, merkle_root: &[u8; MERKLE_ROOT_SIZE]) -> Self {
         Self {
             leave_number,
-            merkle_root: merkle_root.clone(),
-        }
-    }
-
-    pub fn verify(
-        &self,
-        proof_bytes: Vec<u8>,
-        indices: &Vec<usize>,
-        leaves: &Vec<Vec<u8>>,
-    ) -> bool {
-        let proof = MerkleProof::<Blake3Algorithm>::try_from(proof_bytes).unwrap();
-        let leaves_to_prove: Vec<[u8; MERKLE_ROOT_SIZE]> =
-            leaves.iter().map(|x| Blake3Algorithm::hash(x)).collect();
-        proof.verify(
-            self.merkle_root,
-            indices,
-            &leaves_to_prove,
-            self.leave_number,
-        )
+            merkle_root: *merkle_root,
+            cap: vec![*merkle_root],
+            _marker: PhantomData,
+        }
+    }
+
+    // Stores a cap sent in place of the root, which is malformed unless it has the length of
+    // a layer of the tree. The root is still derived from it, for the transcript.
+    pub fn with_cap(
+        leave_number: usize,
+        cap: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    ) -> Result<Self, VerifyError> {
+        // Layers only shrink down to one node, so no layer is empty or wider than the leaves
+        if cap.is_empty() || cap.len() > leave_number || !cap.iter().all(H::is_digest) {
+            return Err(VerifyError::MalformedProof);
+        }
+        let mut layer_len = leave_number;
+        while layer_len > cap.len() {
+            layer_len = layer_len.div_ceil(2);
+        }
+        if layer_len != cap.len() {
+            return Err(VerifyError::MalformedProof);
+        }
+        let mut layer = cap.clone();
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|x| H::concat_and_hash(&x[0], x.get(1)))
+                .collect();
+        }
+        Ok(Self {
+            leave_number,
+            merkle_root: layer[0],
+            cap,
+            _marker: PhantomData,
+        })
+    }
+
+    // `indices` have to be increasing, with `leaves` in the same order
+    pub fn verify(&self, proof_bytes: &[u8], indices: &[usize], leaves: &[Vec<u8>]) -> bool {
+        if indices.is_empty()
+            || indices.len() != leaves.len()
+            || indices.windows(2).any(|x| x[0] >= x[1])
+            || indices[indices.len() - 1] >= self.leave_number
+        {
+            return false;
+        }
+        if !proof_bytes.len().is_multiple_of(MERKLE_ROOT_SIZE) {
+            return false;
+        }
+        let mut helpers = proof_bytes
+            .chunks(MERKLE_ROOT_SIZE)
+            .map(|x| -> [u8; MERKLE_ROOT_SIZE] { x.try_into().unwrap() });
+        if !helpers.clone().all(|x| H::is_digest(&x)) {
+            return false;
+        }
+        let mut current = indices
+            .iter()
+            .zip(leaves)
+            .map(|(i, x)| (*i, H::hash(x)))
+            .collect::<Vec<_>>();
+        let mut layer_len = self.leave_number;
+        while layer_len > self.cap.len() {
+            let mut next = vec![];
+            let mut k = 0;
+            while k < current.len() {
+                let (i, hash) = current[k];
+                let parent = if i % 2 == 1 {
+                    match helpers.next() {
+                        Some(left) => H::concat_and_hash(&left, Some(&hash)),
+                        None => return false,
+                    }
+                } else if current.get(k + 1).is_some_and(|x| x.0 == i + 1) {
+                    k += 1;
+                    H::concat_and_hash(&hash, Some(&current[k].1))
+                } else if i + 1 < layer_len {
+                    match helpers.next() {
+                        Some(right) => H::concat_and_hash(&hash, Some(&right)),
+                        None => return false,
+                    }
+                } else {
+                    H::concat_and_hash(&hash, None)
+                };
+                next.push((i / 2, parent));
+                k += 1;
+            }
+            current = next;
+            layer_len = layer_len.div_ceil(2);
+        }
+        helpers.next().is_none()
+            && layer_len == self.cap.len()
+            && current.iter().all(|(i, hash)| self.cap[*i] == *hash)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::algebra::field::{as_bytes_vec, mersenne61_ext::Mersenne61Ext, Field};
+    use crate::algebra::field::{
+        as_bytes_vec, fp64::Fp64, ft255::Ft255, mersenne61_ext::Mersenne61Ext, Field,
+    };
+    use crate::poseidon::PoseidonAlgorithm;
 
     #[test]
     fn commit_and_open() {
@@ -93,16 +323,165 @@ mod tests {
             as_bytes_vec(&[Mersenne61Ext::from_int(13), Mersenne61Ext::from_int(14)]),
         ];
         let leave_number = leaf_values.len();
-        let prover = MerkleTreeProver::new(leaf_values);
+        let prover: MerkleTreeProver = MerkleTreeProver::new(leaf_values);
         let root = prover.commit();
-        let verifier = MerkleTreeVerifier::new(leave_number, &root);
+        let verifier: MerkleTreeVerifier = MerkleTreeVerifier::new(leave_number, &root);
         let leaf_indices = vec![2, 3];
         let proof_bytes = prover.open(&leaf_indices);
         let open_values = vec![
             as_bytes_vec(&[Mersenne61Ext::from_int(5), Mersenne61Ext::from_int(6)]),
             as_bytes_vec(&[Mersenne61Ext::from_int(7), Mersenne61Ext::from_int(8)]),
         ];
-        assert!(verifier.verify(proof_bytes, &leaf_indices, &open_values));
+        assert!(verifier.verify(&proof_bytes, &leaf_indices, &open_values));
+    }
+
+    #[test]
+    fn salted_leaves() {
+        let leaf_values = (0..8u64)
+            .map(|i| as_bytes_vec(&[Mersenne61Ext::from_int(i)]))
+            .collect::<Vec<_>>();
+        let prover: MerkleTreeProver = MerkleTreeProver::new_salted(leaf_values.clone());
+        // The same leaves commit to a different root every time
+        assert_ne!(
+            prover.commit(),
+            MerkleTreeProver::<Blake3Algorithm>::new_salted(leaf_values.clone()).commit()
+        );
+        let verifier: MerkleTreeVerifier =
+            MerkleTreeVerifier::new(leaf_values.len(), &prover.commit());
+        let leaf_indices = vec![1, 6];
+        let salts = prover.open_salts(&leaf_indices);
+        let mut open_values = leaf_indices
+            .iter()
+            .zip(salts.iter())
+            .map(|(i, salt)| [leaf_values[*i].as_slice(), salt].concat())
+            .collect::<Vec<_>>();
+        assert!(verifier.verify(&prover.open(&leaf_indices), &leaf_indices, &open_values));
+        open_values[0].truncate(leaf_values[1].len());
+        assert!(!verifier.verify(&prover.open(&leaf_indices), &leaf_indices, &open_values));
+    }
+
+    fn open_with<H: MerkleHasher>() {
+        let leaf_values = (0..8u64)
+            .map(|i| as_bytes_vec(&[Mersenne61Ext::from_int(i)]))
+            .collect::<Vec<_>>();
+        let prover = MerkleTreeProver::<H>::new(leaf_values.clone());
+        let verifier = MerkleTreeVerifier::<H>::new(leaf_values.len(), &prover.commit());
+        let leaf_indices = vec![0, 5];
+        let open_values = leaf_indices
+            .iter()
+            .map(|i| leaf_values[*i].clone())
+            .collect::<Vec<_>>();
+        assert!(verifier.verify(&prover.open(&leaf_indices), &leaf_indices, &open_values));
+        // The paths of one hash do not verify under another
+        let other = MerkleTreeVerifier::<Blake3Algorithm>::new(leaf_values.len(), &prover.commit());
+        assert!(!other.verify(&prover.open(&leaf_indices), &leaf_indices, &open_values));
+    }
+
+    #[test]
+    fn batched_openings() {
+        for leave_number in [1, 7, 16, 33] {
+            let leaf_values = (0..leave_number as u64)
+                .map(|i| as_bytes_vec(&[Mersenne61Ext::from_int(i)]))
+                .collect::<Vec<_>>();
+            let prover: MerkleTreeProver = MerkleTreeProver::new(leaf_values.clone());
+            let verifier: MerkleTreeVerifier =
+                MerkleTreeVerifier::new(leave_number, &prover.commit());
+            let depth = leave_number.next_power_of_two().ilog2() as usize;
+            for leaf_indices in [vec![0], vec![leave_number - 1], (0..leave_number).collect()] {
+                let open_values = leaf_indices
+                    .iter()
+                    .map(|i| leaf_values[*i].clone())
+                    .collect::<Vec<_>>();
+                let proof_bytes = prover.open(&leaf_indices);
+                assert!(verifier.verify(&proof_bytes, &leaf_indices, &open_values));
+                // Opening every leaf needs no helper at all
+                if leaf_indices.len() == leave_number {
+                    assert!(proof_bytes.is_empty());
+                }
+                assert!(proof_bytes.len() <= depth * MERKLE_ROOT_SIZE);
+                // Nothing may be left over, missing, or out of order
+                let longer = [proof_bytes.as_slice(), &[0; MERKLE_ROOT_SIZE]].concat();
+                assert!(!verifier.verify(&longer, &leaf_indices, &open_values));
+                if !proof_bytes.is_empty() {
+                    let shorter = &proof_bytes[MERKLE_ROOT_SIZE..];
+                    assert!(!verifier.verify(shorter, &leaf_indices, &open_values));
+                }
+            }
+            if leave_number > 2 {
+                let leaf_indices = vec![1, 0];
+                let open_values = vec![leaf_values[1].clone(), leaf_values[0].clone()];
+                let proof_bytes = prover.open(&leaf_indices);
+                assert!(!verifier.verify(&proof_bytes, &leaf_indices, &open_values));
+            }
+        }
+
+        // Merged paths share their upper nodes, so a batch is smaller than separate paths
+        let leaf_values = (0..1024u64)
+            .map(|i| as_bytes_vec(&[Mersenne61Ext::from_int(i)]))
+            .collect::<Vec<_>>();
+        let prover: MerkleTreeProver = MerkleTreeProver::new(leaf_values.clone());
+        let leaf_indices = vec![3, 4, 5, 100, 101, 700];
+        let separate = leaf_indices
+            .iter()
+            .map(|i| prover.open(&[*i]).len())
+            .sum::<usize>();
+        let batched = prover.open(&leaf_indices).len();
+        assert_eq!(separate, leaf_indices.len() * 10 * MERKLE_ROOT_SIZE);
+        assert!(batched < separate / 2);
+        let open_values = leaf_indices
+            .iter()
+            .map(|i| leaf_values[*i].clone())
+            .collect::<Vec<_>>();
+
+        // With a cap the paths stop below the root
+        for cap_height in [0, 1, 4, 10] {
+            let cap = prover.cap(cap_height);
+            assert_eq!(cap.len(), 1 << cap_height);
+            let verifier: MerkleTreeVerifier =
+                MerkleTreeVerifier::with_cap(leaf_values.len(), cap.clone()).unwrap();
+            assert_eq!(verifier.merkle_root, prover.commit());
+            let proof_bytes = prover.open_to_cap(&leaf_indices, cap_height);
+            assert!(verifier.verify(&proof_bytes, &leaf_indices, &open_values));
+            // A cap as wide as the tree leaves nothing to send
+            assert!(proof_bytes.len() <= batched);
+            assert_eq!(proof_bytes.is_empty(), cap_height == 10);
+            if proof_bytes.len() < batched {
+                assert!(!verifier.verify(&prover.open(&leaf_indices), &leaf_indices, &open_values));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_caps() {
+        for (leave_number, cap_len) in [(1024, 3), (1024, 0), (0, 0), (0, 1), (4, 8), (6, 4)] {
+            assert_eq!(
+                MerkleTreeVerifier::<Blake3Algorithm>::with_cap(
+                    leave_number,
+                    vec![[0; MERKLE_ROOT_SIZE]; cap_len]
+                )
+                .err(),
+                Some(VerifyError::MalformedProof)
+            );
+        }
+        // Layers of a tree whose width is not a power of two round up
+        assert!(MerkleTreeVerifier::<Blake3Algorithm>::with_cap(6, vec![[0; 32]; 3]).is_ok());
+        assert!(MerkleTreeVerifier::<Blake3Algorithm>::with_cap(6, vec![[0; 32]; 6]).is_ok());
+    }
+
+    #[test]
+    fn pluggable_hashers() {
+        open_with::<Sha256Algorithm>();
+        open_with::<Keccak256Algorithm>();
+        open_with::<PoseidonAlgorithm<Fp64>>();
+        open_with::<PoseidonAlgorithm<Ft255>>();
+        assert_eq!(
+            "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7",
+            hex::encode(Sha256Algorithm::hash("data".as_bytes()))
+        );
+        assert_eq!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            hex::encode(Keccak256Algorithm::hash(&[]))
+        );
     }
 
     #[test]
@@ -0,0 +1,298 @@
+use std::marker::PhantomData;
+
+use crate::error::ParameterError;
+use crate::merkle_tree::{Blake3Algorithm, MerkleHasher};
+use crate::transcript::MAX_GRINDING_BITS;
+
+// Runtime parameters of a FRI-style low-degree test over a code of rate 2^-log_blowup.
+// The hash the Merkle trees are built with is chosen through `H`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FriParameters<H: MerkleHasher = Blake3Algorithm> {
+    pub log_blowup: usize,
+    pub query_num: usize,
+    // Proof-of-work bits demanded before the query indices are drawn
+    pub grinding_bits: usize,
+    // The security level `query_num` was chosen for
+    pub security_bits: usize,
+    // Whether the commitments and openings hide the polynomial. Only Namefri and Basefold
+    // support it; FRI, Gemini-FRI and Virgo return an error when set up with it.
+    hiding: bool,
+    // Every round folds the codeword 2^log_folding_arity to 1, so that the Merkle leaves
+    // hold 2^log_folding_arity values and fewer trees are committed and opened
+    pub log_folding_arity: usize,
+    // Folding stops once 2^log_final_degree coefficients are left, and the prover sends
+    // them instead of the last foldings
+    pub log_final_degree: usize,
+    // Whether the schemes that support it add an out-of-domain sample and its quotient
+    deep: bool,
+    // Whether `query_num` was given rather than fitted to `security_bits`
+    fixed_query_num: bool,
+    // Whether queries are counted with proven bounds rather than the conjectured one
+    proven: bool,
+    hasher: PhantomData<H>,
+}
+
+impl FriParameters {
+    // The fewest queries that, together with the grinding, reach `security_bits`
+    pub fn new(log_blowup: usize, security_bits: usize, grinding_bits: usize) -> Self {
+        assert!(grinding_bits <= MAX_GRINDING_BITS);
+        let mut parameters = FriParameters {
+            log_blowup,
+            query_num: 0,
+            grinding_bits,
+            security_bits,
+            hiding: false,
+            log_folding_arity: 1,
+            log_final_degree: 0,
+            deep: false,
+            fixed_query_num: false,
+            proven: false,
+            hasher: PhantomData,
+        };
+        parameters.fit_query_num();
+        parameters
+    }
+
+    // A fixed query count, with the security level it actually provides
+    pub fn with_query_num(log_blowup: usize, query_num: usize, grinding_bits: usize) -> Self {
+        assert!(grinding_bits <= MAX_GRINDING_BITS);
+        let mut parameters = FriParameters {
+            log_blowup,
+            query_num,
+            grinding_bits,
+            security_bits: 0,
+            hiding: false,
+            log_folding_arity: 1,
+            log_final_degree: 0,
+            deep: false,
+            fixed_query_num: true,
+            proven: false,
+            hasher: PhantomData,
+        };
+        parameters.security_bits = parameters.query_bits();
+        parameters
+    }
+}
+
+impl<H: MerkleHasher> FriParameters<H> {
+    pub fn hiding(&self) -> bool {
+        self.hiding
+    }
+
+    pub fn deep(&self) -> bool {
+        self.deep
+    }
+
+    pub fn with_hiding(mut self) -> Self {
+        self.hiding = true;
+        self
+    }
+
+    // The check of the schemes that cannot hide
+    pub fn reject_hiding(&self) -> Result<(), ParameterError> {
+        if self.hiding {
+            return Err(ParameterError::HidingUnsupported);
+        }
+        Ok(())
+    }
+
+    pub fn with_folding_arity(mut self, log_folding_arity: usize) -> Self {
+        assert!(log_folding_arity > 0);
+        self.log_folding_arity = log_folding_arity;
+        self
+    }
+
+    pub fn with_final_degree(mut self, log_final_degree: usize) -> Self {
+        self.log_final_degree = log_final_degree;
+        self
+    }
+
+    // DEEP-FRI, which keeps the security level with the fewer queries it needs under proven
+    // bounds. A fixed query count is kept and provides more security instead.
+    pub fn with_deep(mut self) -> Self {
+        self.deep = true;
+        self.refit();
+        self
+    }
+
+    // Counts the queries with proven bounds only instead of the conjectured one, which takes
+    // about four times as many queries at rate 1/8. A fixed query count is kept and provides
+    // less security instead.
+    pub fn with_proven_bounds(mut self) -> Self {
+        self.proven = true;
+        self.refit();
+        self
+    }
+
+    fn refit(&mut self) {
+        if self.fixed_query_num {
+            self.security_bits = self.query_bits();
+        } else {
+            self.fit_query_num();
+        }
+    }
+
+    // The fewest queries that, together with the grinding, reach `security_bits`
+    fn fit_query_num(&mut self) {
+        let query_bits = self.security_bits.saturating_sub(self.grinding_bits) as f64;
+        self.query_num = (query_bits / self.bits_per_query()).ceil() as usize;
+    }
+
+    pub fn with_hasher<G: MerkleHasher>(self) -> FriParameters<G> {
+        FriParameters {
+            log_blowup: self.log_blowup,
+            query_num: self.query_num,
+            grinding_bits: self.grinding_bits,
+            security_bits: self.security_bits,
+            hiding: self.hiding,
+            log_folding_arity: self.log_folding_arity,
+            log_final_degree: self.log_final_degree,
+            deep: self.deep,
+            fixed_query_num: self.fixed_query_num,
+            proven: self.proven,
+            hasher: PhantomData,
+        }
+    }
+
+    // The binary foldings of a polynomial in `variable_num` variables, at least one
+    pub fn folds(&self, variable_num: usize) -> usize {
+        variable_num - self.log_final_degree.min(variable_num.saturating_sub(1))
+    }
+
+    // The log arity of every round of the foldings: the full arity while enough foldings
+    // are left, whatever remains in the last round
+    pub fn folding_schedule(&self, variable_num: usize) -> Vec<usize> {
+        let mut schedule = vec![];
+        let mut left = self.folds(variable_num);
+        while left > 0 {
+            let log_arity = self.log_folding_arity.min(left);
+            schedule.push(log_arity);
+            left -= log_arity;
+        }
+        schedule
+    }
+
+    // The binary foldings done before every round starts, and all of them at the end
+    pub fn round_starts(&self, variable_num: usize) -> Vec<usize> {
+        let mut starts = vec![0];
+        for log_arity in self.folding_schedule(variable_num) {
+            starts.push(starts.last().unwrap() + log_arity);
+        }
+        starts
+    }
+
+    pub fn blowup(&self) -> usize {
+        1 << self.log_blowup
+    }
+
+    pub fn rate(&self) -> f64 {
+        1.0 / self.blowup() as f64
+    }
+
+    // By the proximity-gap conjectures a word far from the code survives one query with
+    // probability about rate, which gives log_blowup bits per query. Proven bounds give less:
+    // a word outside the unique decoding radius (1 - rate) / 2 survives with probability at
+    // most (1 + rate) / 2. The out-of-domain sample of DEEP-FRI leaves one codeword of the
+    // list within the Johnson bound 1 - sqrt(rate), so a word beyond it survives with
+    // probability at most sqrt(rate).
+    pub fn bits_per_query(&self) -> f64 {
+        if !self.proven {
+            self.log_blowup as f64
+        } else if self.deep {
+            self.log_blowup as f64 / 2.0
+        } else {
+            (2.0 / (1.0 + self.rate())).log2()
+        }
+    }
+
+    // Security of the query phase and the grinding together
+    pub fn query_bits(&self) -> usize {
+        (self.query_num as f64 * self.bits_per_query()).floor() as usize + self.grinding_bits
+    }
+
+    // Conservative security level for a field of 2^field_bits elements. Each of the `rounds`
+    // folding challenges is bad with probability at most |D| / |F| for the domain D it
+    // folds, which caps the query phase.
+    pub fn soundness_bits(
+        &self,
+        field_bits: usize,
+        log_domain_size: usize,
+        rounds: usize,
+    ) -> usize {
+        let log_rounds = rounds.max(1).next_power_of_two().ilog2() as usize;
+        let commit_bits = field_bits.saturating_sub(log_domain_size + log_rounds);
+        self.query_bits().min(commit_bits)
+    }
+}
+
+// Rate 2^-3 with the 128 / 3 = 42 queries the benchmarks have always run with
+impl Default for FriParameters {
+    fn default() -> Self {
+        FriParameters::with_query_num(3, 128 / 3, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_count() {
+        let parameters = FriParameters::default();
+        assert_eq!(parameters.blowup(), 8);
+        assert_eq!(parameters.query_num, 42);
+        let fitted = FriParameters::new(3, 128, 0);
+        assert!(fitted.query_bits() >= 128);
+        let fewer = FriParameters::with_query_num(3, fitted.query_num - 1, 0);
+        assert!(fewer.security_bits < 128);
+        // Grinding buys back queries, a larger blowup makes each query worth more
+        assert!(FriParameters::new(3, 128, 20).query_num < fitted.query_num);
+        assert!(FriParameters::new(4, 128, 0).query_num < fitted.query_num);
+        // Under the conjecture the out-of-domain sample changes nothing
+        assert_eq!(fitted.with_deep().query_num, fitted.query_num);
+        // Proven bounds need more queries, fewer in the list-decoding regime of DEEP-FRI
+        let proven = fitted.with_proven_bounds();
+        assert_eq!(proven.query_num, 155);
+        let deep = proven.with_deep();
+        assert!(fitted.query_num < deep.query_num && deep.query_num < proven.query_num);
+        assert!(deep.query_bits() >= 128);
+        assert_eq!(deep.security_bits, 128);
+        // A fixed query count survives, with the security it now provides
+        let fixed = FriParameters::with_query_num(3, 20, 0).with_proven_bounds();
+        assert_eq!(fixed.query_num, 20);
+        assert_eq!(fixed.security_bits, fixed.query_bits());
+        let fixed_deep = fixed.with_deep();
+        assert_eq!(fixed_deep.query_num, 20);
+        assert!(fixed.security_bits < fixed_deep.security_bits);
+        let conjectured = FriParameters::with_query_num(3, 20, 0);
+        assert!(fixed_deep.security_bits < conjectured.security_bits);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grinding_cap() {
+        FriParameters::new(3, 128, MAX_GRINDING_BITS + 1);
+    }
+
+    #[test]
+    fn folding_rounds() {
+        let parameters = FriParameters::default().with_folding_arity(3);
+        assert_eq!(parameters.folding_schedule(10), vec![3, 3, 3, 1]);
+        assert_eq!(parameters.round_starts(10), vec![0, 3, 6, 9, 10]);
+        assert_eq!(parameters.folding_schedule(2), vec![2]);
+        assert_eq!(FriParameters::default().folding_schedule(3), vec![1, 1, 1]);
+        let parameters = parameters.with_final_degree(4);
+        assert_eq!(parameters.round_starts(10), vec![0, 3, 6]);
+        assert_eq!(parameters.folds(3), 1);
+    }
+
+    #[test]
+    fn commit_phase_caps_security() {
+        let parameters = FriParameters::default();
+        assert_eq!(
+            parameters.soundness_bits(256, 30, 27),
+            parameters.query_bits()
+        );
+        assert_eq!(parameters.soundness_bits(64, 30, 27), 64 - 30 - 5);
+    }
+}
@@ -0,0 +1,61 @@
+use std::io;
+
+use rand::RngCore;
+
+use crate::algebra::field::Field;
+use crate::error::{ParameterError, VerifyError};
+use crate::merkle_tree::{Blake3Algorithm, MerkleHasher};
+use crate::parameters::FriParameters;
+
+// Common interface of the FRI-family commitment schemes, so that applications and
+// benchmarks can be written once and switch schemes through a type parameter.
+// The Merkle hash `H` comes with the parameters.
+pub trait PolynomialCommitmentScheme<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    type Params;
+    type Polynomial;
+    type Point;
+    type Commitment;
+    // What the polynomial evaluates to at a point, one value per polynomial of a batch
+    type Evaluation;
+    // Prover-side state kept between `commit` and `open`
+    type ProverData;
+    type Proof;
+
+    // Fails on parameters the scheme does not support, such as hiding ones for a scheme
+    // that cannot hide
+    fn setup(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+    ) -> Result<Self::Params, ParameterError> {
+        Self::setup_with(variable_num, parameters, &mut rand::thread_rng())
+    }
+
+    // The coset shifts, or the seed of the code, of the parameters are drawn from `rng`
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError>;
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData);
+
+    // Returns the evaluation at `point` with the proof that the commitment opens to it. The
+    // prover data of a scheme may live on disk, in which case reading it back can fail.
+    fn open(
+        params: &Self::Params,
+        prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)>;
+
+    // Checks that the commitment opens to `value` at `point`
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError>;
+}
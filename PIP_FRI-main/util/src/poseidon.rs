@@ -0,0 +1,332 @@
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use rs_merkle::Hasher;
+
+use crate::algebra::field::{fp64::Fp64, ft255::Ft255, Field};
+use crate::merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE};
+
+mod constants;
+
+// A prime field with a Poseidon instance over it. The digest is the first rate elements of
+// the state, as many as fill MERKLE_ROOT_SIZE bytes.
+pub trait PoseidonField: Field {
+    // x -> x^ALPHA has to be a permutation of the field
+    const ALPHA: usize;
+    const WIDTH: usize;
+    const RATE: usize;
+    const FULL_ROUNDS: usize;
+    const PARTIAL_ROUNDS: usize;
+    // Input bytes packed into one element, few enough that any packing is below the modulus
+    const BYTES_PER_ELEMENT: usize;
+
+    fn constants() -> &'static PoseidonConstants<Self>;
+}
+
+// The round constants, WIDTH per round, and the MDS matrix of an instance, from the tables in
+// `constants`
+pub struct PoseidonConstants<F: Field> {
+    round_constants: Vec<F>,
+    mds: Vec<Vec<F>>,
+}
+
+// The element with the given little-endian limbs, which have to be canonical
+fn from_limbs<F: Field>(limbs: &[u64]) -> F {
+    let bytes = limbs
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect::<Vec<_>>();
+    F::from_bytes(&bytes).expect("Poseidon constants are canonical")
+}
+
+// The Goldilocks instance of Plonky2, with the same constants and the same permutation
+impl PoseidonField for Fp64 {
+    const ALPHA: usize = 7;
+    const WIDTH: usize = 12;
+    const RATE: usize = 8;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 22;
+    const BYTES_PER_ELEMENT: usize = 7;
+
+    fn constants() -> &'static PoseidonConstants<Self> {
+        static CONSTANTS: OnceLock<PoseidonConstants<Fp64>> = OnceLock::new();
+        CONSTANTS.get_or_init(|| PoseidonConstants {
+            round_constants: constants::GOLDILOCKS_ROUND_CONSTANTS
+                .iter()
+                .map(|x| from_limbs(&[*x]))
+                .collect(),
+            mds: (0..Self::WIDTH)
+                .map(|i| {
+                    (0..Self::WIDTH)
+                        .map(|j| {
+                            let circ = constants::GOLDILOCKS_MDS_CIRC[(j + Self::WIDTH - i) % 12];
+                            let diag = if i == j {
+                                constants::GOLDILOCKS_MDS_DIAG[i]
+                            } else {
+                                0
+                            };
+                            Fp64::from_int(circ + diag)
+                        })
+                        .collect()
+                })
+                .collect(),
+        })
+    }
+}
+
+// A width-3 instance with the round numbers of the width-3 instances over 255-bit fields,
+// its constants generated as the reference script generates them, since no instance over
+// this field is published
+impl PoseidonField for Ft255 {
+    const ALPHA: usize = 5;
+    const WIDTH: usize = 3;
+    const RATE: usize = 2;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 57;
+    const BYTES_PER_ELEMENT: usize = 31;
+
+    fn constants() -> &'static PoseidonConstants<Self> {
+        static CONSTANTS: OnceLock<PoseidonConstants<Ft255>> = OnceLock::new();
+        CONSTANTS.get_or_init(|| PoseidonConstants {
+            round_constants: constants::FT255_ROUND_CONSTANTS
+                .iter()
+                .map(|x| from_limbs(x))
+                .collect(),
+            mds: constants::FT255_MDS
+                .iter()
+                .map(|row| row.iter().map(|x| from_limbs(x)).collect())
+                .collect(),
+        })
+    }
+}
+
+pub fn permute<F: PoseidonField>(state: &mut [F]) {
+    assert_eq!(state.len(), F::WIDTH);
+    let constants = F::constants();
+    let half_full = F::FULL_ROUNDS / 2;
+    for (round, round_constants) in constants.round_constants.chunks(F::WIDTH).enumerate() {
+        for (x, c) in state.iter_mut().zip(round_constants) {
+            *x += *c;
+        }
+        if round < half_full || round >= half_full + F::PARTIAL_ROUNDS {
+            for x in state.iter_mut() {
+                *x = x.pow(F::ALPHA);
+            }
+        } else {
+            state[0] = state[0].pow(F::ALPHA);
+        }
+        let mixed = constants
+            .mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .fold(F::from_int(0), |acc, (m, x)| acc + *m * *x)
+            })
+            .collect::<Vec<_>>();
+        state.copy_from_slice(&mixed);
+    }
+}
+
+fn element_size<F: Field>() -> usize {
+    F::from_int(0).to_bytes().len()
+}
+
+// Sponge with `tag` in the capacity, returning the digest elements
+fn sponge<F: PoseidonField>(elements: &[F], tag: u64) -> Vec<F> {
+    let mut state = vec![F::from_int(0); F::WIDTH];
+    state[F::WIDTH - 1] = F::from_int(tag);
+    for block in elements.chunks(F::RATE) {
+        for (x, m) in state.iter_mut().zip(block) {
+            *x += *m;
+        }
+        permute(&mut state);
+    }
+    if elements.is_empty() {
+        permute(&mut state);
+    }
+    state.truncate(MERKLE_ROOT_SIZE / element_size::<F>());
+    state
+}
+
+// A leaf is tagged with its length plus one, so that inputs that pack to the same elements
+// still hash apart and no leaf hashes like an inner node
+pub fn hash_bytes<F: PoseidonField>(data: &[u8]) -> Vec<F> {
+    let elements = data
+        .chunks(F::BYTES_PER_ELEMENT)
+        .map(|chunk| {
+            let mut bytes = vec![0u8; element_size::<F>()];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            F::from_bytes(&bytes).unwrap()
+        })
+        .collect::<Vec<_>>();
+    sponge(&elements, data.len() as u64 + 1)
+}
+
+// Inner nodes absorb the digest elements of their children as they are, tagged with 0
+pub fn compress<F: PoseidonField>(left: &[F], right: &[F]) -> Vec<F> {
+    sponge(&[left, right].concat(), 0)
+}
+
+// Panics unless every element of `digest` is canonical. The Merkle verifier checks the
+// digests of a proof with `is_digest` before it hashes them.
+fn to_elements<F: Field>(digest: &[u8; MERKLE_ROOT_SIZE]) -> Vec<F> {
+    digest
+        .chunks(element_size::<F>())
+        .map(|x| F::from_bytes(x).expect("a Poseidon digest is canonical"))
+        .collect()
+}
+
+fn to_digest<F: Field>(elements: &[F]) -> [u8; MERKLE_ROOT_SIZE] {
+    let mut digest = [0u8; MERKLE_ROOT_SIZE];
+    let bytes = elements
+        .iter()
+        .flat_map(|x| x.to_bytes())
+        .collect::<Vec<_>>();
+    digest.copy_from_slice(&bytes);
+    digest
+}
+
+// Merkle trees whose hashing can be checked cheaply inside an arithmetic circuit over `F`
+#[derive(Debug, Clone, Copy)]
+pub struct PoseidonAlgorithm<F: PoseidonField> {
+    _marker: PhantomData<F>,
+}
+
+impl<F: PoseidonField> Hasher for PoseidonAlgorithm<F> {
+    type Hash = [u8; MERKLE_ROOT_SIZE];
+
+    fn hash(data: &[u8]) -> [u8; MERKLE_ROOT_SIZE] {
+        to_digest(&hash_bytes::<F>(data))
+    }
+
+    fn concat_and_hash(
+        left: &[u8; MERKLE_ROOT_SIZE],
+        right: Option<&[u8; MERKLE_ROOT_SIZE]>,
+    ) -> [u8; MERKLE_ROOT_SIZE] {
+        match right {
+            Some(right) => to_digest(&compress::<F>(&to_elements(left), &to_elements(right))),
+            None => *left,
+        }
+    }
+}
+
+impl<F: PoseidonField> MerkleHasher for PoseidonAlgorithm<F> {
+    fn is_digest(digest: &[u8; MERKLE_ROOT_SIZE]) -> bool {
+        digest
+            .chunks(element_size::<F>())
+            .all(|x| F::from_bytes(x).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_tree::{MerkleTreeProver, MerkleTreeVerifier};
+
+    fn distinct_digests<F: PoseidonField>() {
+        let digest = PoseidonAlgorithm::<F>::hash(b"data");
+        assert_eq!(digest, PoseidonAlgorithm::<F>::hash(b"data"));
+        assert_ne!(digest, PoseidonAlgorithm::<F>::hash(b"date"));
+        // Trailing zero bytes pack to the same elements, the length tells them apart
+        assert_ne!(
+            PoseidonAlgorithm::<F>::hash(&[1]),
+            PoseidonAlgorithm::<F>::hash(&[1, 0])
+        );
+        assert_ne!(
+            PoseidonAlgorithm::<F>::hash(&[]),
+            PoseidonAlgorithm::<F>::hash(&[0])
+        );
+        // The digest elements fill a Merkle node exactly and come back out of it
+        let elements = hash_bytes::<F>(&[7; 100]);
+        assert_eq!(elements.len() * element_size::<F>(), MERKLE_ROOT_SIZE);
+        assert_eq!(to_elements::<F>(&to_digest(&elements)), elements);
+        assert_ne!(
+            PoseidonAlgorithm::<F>::concat_and_hash(&digest, Some(&digest)),
+            PoseidonAlgorithm::<F>::hash(&[digest, digest].concat())
+        );
+    }
+
+    #[test]
+    fn poseidon() {
+        distinct_digests::<Fp64>();
+        distinct_digests::<Ft255>();
+    }
+
+    fn permutes_to<F: PoseidonField>(input: &[F], output: &[F]) {
+        let mut state = input.to_vec();
+        permute(&mut state);
+        assert_eq!(state, output);
+    }
+
+    // The test vectors of Plonky2's Goldilocks Poseidon
+    #[rustfmt::skip]
+    #[test]
+    fn goldilocks_test_vectors() {
+        let elements = |x: [u64; 12]| x.map(|x| from_limbs::<Fp64>(&[x]));
+        permutes_to(
+            &elements([0; 12]),
+            &elements([
+                0x3c18a9786cb0b359, 0xc4055e3364a246c3, 0x7953db0ab48808f4, 0xc71603f33a1144ca,
+                0xd7709673896996dc, 0x46a84e87642f44ed, 0xd032648251ee0b3c, 0x1c687363b207df62,
+                0xdf8565563e8045fe, 0x40f5b37ff4254dae, 0xd070f637b431067c, 0x1792b1c4342109d7,
+            ]),
+        );
+        permutes_to(
+            &elements([0xffffffff00000000; 12]),
+            &elements([
+                0xbe0085cfc57a8357, 0xd95af71847d05c09, 0xcf55a13d33c1c953, 0x95803a74f4530e82,
+                0xfcd99eb30a135df1, 0xe095905e913a3029, 0xde0392461b42919b, 0x7d3260e24e81d031,
+                0x10d3d0465d9deaa0, 0xa87571083dfc2a47, 0xe18263681e9958f8, 0xe28e96f1ae5e60d3,
+            ]),
+        );
+    }
+
+    // Computed with an independent implementation of the reference script and permutation
+    #[rustfmt::skip]
+    #[test]
+    fn ft255_test_vectors() {
+        let elements = |x: [[u64; 4]; 3]| x.map(|x| from_limbs::<Ft255>(&x));
+        permutes_to(
+            &elements([[0; 4]; 3]),
+            &elements([
+                [0x1240e627588f5be8, 0xdf27fff2764cda1b, 0xa15e0952541865f1, 0x47af6a245abef77c],
+                [0xb15aea8e9fcd24c5, 0x0e301f6ad289c32e, 0x83a5aa35954aa378, 0x5f0f344bc46f7b8c],
+                [0xb00b064caba1225d, 0x3548b093af795441, 0x66b38467a8c3fade, 0x60338a23f7f1e784],
+            ]),
+        );
+        permutes_to(
+            &[0, 1, 2].map(Ft255::from_int),
+            &elements([
+                [0x09350fee1b836c35, 0x51ee9eb5897a9eed, 0x0d39b00fecfc6bb3, 0x176adefd193b865c],
+                [0x1b3b2350abc09c13, 0x9550d744e0168368, 0xada06fbcde33231f, 0x039b33f71668cd18],
+                [0xfb726d4b45d3701c, 0x476fd1f688586fb4, 0x7cd42e9966e1c09e, 0x04f9c1ca92525ea9],
+            ]),
+        );
+    }
+
+    #[test]
+    fn rejects_non_canonical_digests() {
+        let digest = PoseidonAlgorithm::<Fp64>::hash(b"data");
+        assert!(PoseidonAlgorithm::<Fp64>::is_digest(&digest));
+        assert!(!PoseidonAlgorithm::<Fp64>::is_digest(
+            &[0xff; MERKLE_ROOT_SIZE]
+        ));
+        assert!(!PoseidonAlgorithm::<Ft255>::is_digest(
+            &[0xff; MERKLE_ROOT_SIZE]
+        ));
+        let mut digest = digest;
+        digest[8..16].copy_from_slice(&0xffffffff00000001u64.to_le_bytes());
+        assert!(!PoseidonAlgorithm::<Fp64>::is_digest(&digest));
+
+        // Nor does the verifier hash them
+        let leaves = (0..8u8).map(|i| vec![i; 10]).collect::<Vec<_>>();
+        let prover = MerkleTreeProver::<PoseidonAlgorithm<Fp64>>::new(leaves.clone());
+        let verifier = MerkleTreeVerifier::<PoseidonAlgorithm<Fp64>>::new(8, &prover.commit());
+        let mut proof_bytes = prover.open(&[2]);
+        assert!(verifier.verify(&proof_bytes, &[2], &leaves[2..3]));
+        proof_bytes[..MERKLE_ROOT_SIZE].copy_from_slice(&digest);
+        assert!(!verifier.verify(&proof_bytes, &[2], &leaves[2..3]));
+        assert!(MerkleTreeVerifier::<PoseidonAlgorithm<Fp64>>::with_cap(8, vec![digest]).is_err());
+    }
+}
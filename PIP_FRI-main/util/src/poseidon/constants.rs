@@ -0,0 +1,321 @@
+// Goldilocks Poseidon as in Plonky2: its round constants, drawn from ChaCha8 seeded with 0,
+// and its MDS matrix, the circulant of MDS_CIRC plus MDS_DIAG on the diagonal
+#[rustfmt::skip]
+pub const GOLDILOCKS_ROUND_CONSTANTS: [u64; 12 * 30] = [
+    0xb585f766f2144405, 0x7746a55f43921ad7, 0xb2fb0d31cee799b4, 0x0f6760a4803427d7,
+    0xe10d666650f4e012, 0x8cae14cb07d09bf1, 0xd438539c95f63e9f, 0xef781c7ce35b4c3d,
+    0xcdc4a239b0c44426, 0x277fa208bf337bff, 0xe17653a29da578a1, 0xc54302f225db2c76,
+    0x86287821f722c881, 0x59cd1a8a41c18e55, 0xc3b919ad495dc574, 0xa484c4c5ef6a0781,
+    0x308bbd23dc5416cc, 0x6e4a40c18f30c09c, 0x9a2eedb70d8f8cfa, 0xe360c6e0ae486f38,
+    0xd5c7718fbfc647fb, 0xc35eae071903ff0b, 0x849c2656969c4be7, 0xc0572c8c08cbbbad,
+    0xe9fa634a21de0082, 0xf56f6d48959a600d, 0xf7d713e806391165, 0x8297132b32825daf,
+    0xad6805e0e30b2c8a, 0xac51d9f5fcf8535e, 0x502ad7dc18c2ad87, 0x57a1550c110b3041,
+    0x66bbd30e6ce0e583, 0x0da2abef589d644e, 0xf061274fdb150d61, 0x28b8ec3ae9c29633,
+    0x92a756e67e2b9413, 0x70e741ebfee96586, 0x019d5ee2af82ec1c, 0x6f6f2ed772466352,
+    0x7cf416cfe7e14ca1, 0x61df517b86a46439, 0x85dc499b11d77b75, 0x4b959b48b9c10733,
+    0xe8be3e5da8043e57, 0xf5c0bc1de6da8699, 0x40b12cbf09ef74bf, 0xa637093ecb2ad631,
+    0x3cc3f892184df408, 0x2e479dc157bf31bb, 0x6f49de07a6234346, 0x213ce7bede378d7b,
+    0x5b0431345d4dea83, 0xa2de45780344d6a1, 0x7103aaf94a7bf308, 0x5326fc0d97279301,
+    0xa9ceb74fec024747, 0x27f8ec88bb21b1a3, 0xfceb4fda1ded0893, 0xfac6ff1346a41675,
+    0x7131aa45268d7d8c, 0x9351036095630f9f, 0xad535b24afc26bfb, 0x4627f5c6993e44be,
+    0x645cf794b8f1cc58, 0x241c70ed0af61617, 0xacb8e076647905f1, 0x3737e9db4c4f474d,
+    0xe7ea5e33e75fffb6, 0x90dee49fc9bfc23a, 0xd1b1edf76bc09c92, 0x0b65481ba645c602,
+    0x99ad1aab0814283b, 0x438a7c91d416ca4d, 0xb60de3bcc5ea751c, 0xc99cab6aef6f58bc,
+    0x69a5ed92a72ee4ff, 0x5e7b329c1ed4ad71, 0x5fc0ac0800144885, 0x32db829239774eca,
+    0x0ade699c5830f310, 0x7cc5583b10415f21, 0x85df9ed2e166d64f, 0x6604df4fee32bcb1,
+    0xeb84f608da56ef48, 0xda608834c40e603d, 0x8f97fe408061f183, 0xa93f485c96f37b89,
+    0x6704e8ee8f18d563, 0xcee3e9ac1e072119, 0x510d0e65e2b470c1, 0xf6323f486b9038f0,
+    0x0b508cdeffa5ceef, 0xf2417089e4fb3cbd, 0x60e75c2890d15730, 0xa6217d8bf660f29c,
+    0x7159cd30c3ac118e, 0x839b4e8fafead540, 0x0d3f3e5e82920adc, 0x8f7d83bddee7bba8,
+    0x780f2243ea071d06, 0xeb915845f3de1634, 0xd19e120d26b6f386, 0x016ee53a7e5fecc6,
+    0xcb5fd54e7933e477, 0xacb8417879fd449f, 0x9c22190be7f74732, 0x5d693c1ba3ba3621,
+    0xdcef0797c2b69ec7, 0x3d639263da827b13, 0xe273fd971bc8d0e7, 0x418f02702d227ed5,
+    0x8c25fda3b503038c, 0x2cbaed4daec8c07c, 0x5f58e6afcdd6ddc2, 0x284650ac5e1b0eba,
+    0x635b337ee819dab5, 0x9f9a036ed4f2d49f, 0xb93e260cae5c170e, 0xb0a7eae879ddb76d,
+    0xd0762cbc8ca6570c, 0x34c6efb812b04bf5, 0x40bf0ab5fa14c112, 0xb6b570fc7c5740d3,
+    0x5a27b9002de33454, 0xb1a5b165b6d2b2d2, 0x8722e0ace9d1be22, 0x788ee3b37e5680fb,
+    0x14a726661551e284, 0x98b7672f9ef3b419, 0xbb93ae776bb30e3a, 0x28fd3b046380f850,
+    0x30a4680593258387, 0x337dc00c61bd9ce1, 0xd5eca244c7a4ff1d, 0x7762638264d279bd,
+    0xc1e434bedeefd767, 0x0299351a53b8ec22, 0xb2d456e4ad251b80, 0x3e9ed1fda49cea0b,
+    0x2972a92ba450bed8, 0x20216dd77be493de, 0xadffe8cf28449ec6, 0x1c4dbb1c4c27d243,
+    0x15a16a8a8322d458, 0x388a128b7fd9a609, 0x2300e5d6baedf0fb, 0x2f63aa8647e15104,
+    0xf1c36ce86ecec269, 0x27181125183970c9, 0xe584029370dca96d, 0x4d9bbc3e02f1cfb2,
+    0xea35bc29692af6f8, 0x18e21b4beabb4137, 0x1e3b9fc625b554f4, 0x25d64362697828fd,
+    0x5a3f1bb1c53a9645, 0xdb7f023869fb8d38, 0xb462065911d4e1fc, 0x49c24ae4437d8030,
+    0xd793862c112b0566, 0xaadd1106730d8feb, 0xc43b6e0e97b0d568, 0xe29024c18ee6fca2,
+    0x5e50c27535b88c66, 0x10383f20a4ff9a87, 0x38e8ee9d71a45af8, 0xdd5118375bf1a9b9,
+    0x775005982d74d7f7, 0x86ab99b4dde6c8b0, 0xb1204f603f51c080, 0xef61ac8470250ecf,
+    0x1bbcd90f132c603f, 0x0cd1dabd964db557, 0x11a3ae5beb9d1ec9, 0xf755bfeea585d11d,
+    0xa3b83250268ea4d7, 0x516306f4927c93af, 0xddb4ac49c9efa1da, 0x64bb6dec369d4418,
+    0xf9cc95c22b4c1fcc, 0x08d37f755f4ae9f6, 0xeec49b613478675b, 0xf143933aed25e0b0,
+    0xe4c5dd8255dfc622, 0xe7ad7756f193198e, 0x92c2318b87fff9cb, 0x739c25f8fd73596d,
+    0x5636cac9f16dfed0, 0xdd8f909a938e0172, 0xc6401fe115063f5b, 0x8ad97b33f1ac1455,
+    0x0c49366bb25e8513, 0x0784d3d2f1698309, 0x530fb67ea1809a81, 0x410492299bb01f49,
+    0x139542347424b9ac, 0x9cb0bd5ea1a1115e, 0x02e3f615c38f49a1, 0x985d4f4a9c5291ef,
+    0x775b9feafdcd26e7, 0x304265a6384f0f2d, 0x593664c39773012c, 0x4f0a2e5fb028f2ce,
+    0xdd611f1000c17442, 0xd8185f9adfea4fd0, 0xef87139ca9a3ab1e, 0x3ba71336c34ee133,
+    0x7d3a455d56b70238, 0x660d32e130182684, 0x297a863f48cd1f43, 0x90e0a736a751ebb7,
+    0x549f80ce550c4fd3, 0x0f73b2922f38bd64, 0x16bf1f73fb7a9c3f, 0x6d1f5a59005bec17,
+    0x02ff876fa5ef97c4, 0xc5cb72a2a51159b0, 0x8470f39d2d5c900e, 0x25abb3f1d39fcb76,
+    0x23eb8cc9b372442f, 0xd687ba55c64f6364, 0xda8d9e90fd8ff158, 0xe3cbdc7d2fe45ea7,
+    0xb9a8c9b3aee52297, 0xc0d28a5c10960bd3, 0x45d7ac9b68f71a34, 0xeeb76e397069e804,
+    0x3d06c8bd1514e2d9, 0x9c9c98207cb10767, 0x65700b51aedfb5ef, 0x911f451539869408,
+    0x7ae6849fbc3a0ec6, 0x3bb340eba06afe7e, 0xb46e9d8b682ea65e, 0x8dcf22f9a3b34356,
+    0x77bdaeda586257a7, 0xf19e400a5104d20d, 0xc368a348e46d950f, 0x9ef1cd60e679f284,
+    0xe89cd854d5d01d33, 0x5cd377dc8bb882a2, 0xa7b0fb7883eee860, 0x7684403ec392950d,
+    0x5fa3f06f4fed3b52, 0x8df57ac11bc04831, 0x2db01efa1e1e1897, 0x54846de4aadb9ca2,
+    0xba6745385893c784, 0x541d496344d2c75b, 0xe909678474e687fe, 0xdfe89923f6c9c2ff,
+    0xece5a71e0cfedc75, 0x5ff98fd5d51fe610, 0x83e8941918964615, 0x5922040b47f150c1,
+    0xf97d750e3dd94521, 0x5080d4c2b86f56d7, 0xa7de115b56c78d70, 0x6a9242ac87538194,
+    0xf7856ef7f9173e44, 0x2265fc92feb0dc09, 0x17dfc8e4f7ba8a57, 0x9001a64209f21db8,
+    0x90004c1371b893c5, 0xb932b7cf752e5545, 0xa0b1df81b6fe59fc, 0x8ef1dd26770af2c2,
+    0x0541a4f9cfbeed35, 0x9e61106178bfc530, 0xb3767e80935d8af2, 0x0098d5782065af06,
+    0x31d191cd5c1466c7, 0x410fefafa319ac9d, 0xbdf8f242e316c4ab, 0x9e8cd55b57637ed0,
+    0xde122bebe9a39368, 0x4d001fd58f002526, 0xca6637000eb4a9f8, 0x2f2339d624f91f78,
+    0x6d1a7918c80df518, 0xdf9a4939342308e9, 0xebc2151ee6c8398c, 0x03cc2ba8a1116515,
+    0xd341d037e840cf83, 0x387cb5d25af4afcc, 0xbba2515f22909e87, 0x7248fe7705f38e47,
+    0x4d61e56a525d225a, 0x262e963c8da05d3d, 0x59e89b094d220ec2, 0x055d5b52b78b9c5e,
+    0x82b27eb33514ef99, 0xd30094ca96b7ce7b, 0xcf5cb381cd0a1535, 0xfeed4db6919e5a7c,
+    0x41703f53753be59f, 0x5eeea940fcde8b6f, 0x4cd1f1b175100206, 0x4a20358574454ec0,
+    0x1478d361dbbf9fac, 0x6f02dc07d141875c, 0x296a202ed8e556a2, 0x2afd67999bf32ee5,
+    0x7acfd96efa95491d, 0x6798ba0c0abb2c6d, 0x34c6f57b26c92122, 0x5736e1bad206b5de,
+    0x20057d2a0056521b, 0x3dea5bd5d0578bd7, 0x16e50d897d4634ac, 0x29bff3ecb9b7a6e3,
+    0x475cd3205a3bdcde, 0x18a42105c31b7e88, 0x023e7414af663068, 0x15147108121967d7,
+    0xe4a3dff1d7d6fef9, 0x01a8d1a588085737, 0x11b4c74eda62beef, 0xe587cc0d69a73346,
+    0x1ff7327017aa2a6e, 0x594e29c42473d06b, 0xf6f31db1899b12d5, 0xc02ac5e47312d3ca,
+    0xe70201e960cb78b8, 0x6f90ff3b6a65f108, 0x42747a7245e7fa84, 0xd1f507e43ab749b2,
+    0x1c86d265f15750cd, 0x3996ce73dd832c1c, 0x8e7fba02983224bd, 0xba0dec7103255dd4,
+    0x9e9cbd781628fc5b, 0xdae8645996edd6a5, 0xdebe0853b1a1d378, 0xa49229d24d014343,
+    0x7be5b9ffda905e1c, 0xa3c95eaec244aa30, 0x0230bca8f4df0544, 0x4135c2bebfe148c6,
+    0x166fc0cc438a3c72, 0x3762b59a8ae83efa, 0xe8928a4c89114750, 0x2a440b51a4945ee5,
+    0x80cefd2b7d99ff83, 0xbb9879c6e61fd62a, 0x6e7c8f1a84265034, 0x164bb2de1bbeddc8,
+    0xf3c12fe54d5c653b, 0x40b9e922ed9771e2, 0x551f5b0fbe7b1840, 0x25032aa7c4cb1811,
+    0xaaed34074b164346, 0x8ffd96bbf9c9c81d, 0x70fc91eb5937085c, 0x7f795e2a5f915440,
+    0x4543d9df5476d3cb, 0xf172d73e004fc90d, 0xdfd1c4febcc81238, 0xbc8dfb627fe558fc,
+];
+
+pub const GOLDILOCKS_MDS_CIRC: [u64; 12] = [17, 15, 41, 16, 2, 28, 13, 13, 39, 18, 34, 20];
+pub const GOLDILOCKS_MDS_DIAG: [u64; 12] = [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+// Poseidon over Ft255 with x^5, width 3, 8 full and 57 partial rounds. The round constants and
+// then the Cauchy MDS matrix are drawn from the Grain LFSR of the reference parameter script
+// (generate_parameters_grain.sage), with field 1, sbox 0 and n = 255. The matrix passes the
+// script's first check: M^i has an irreducible characteristic polynomial for i <= 6.
+// Elements are given by their little-endian limbs.
+#[rustfmt::skip]
+pub const FT255_ROUND_CONSTANTS: [[u64; 4]; 3 * 65] = [
+    [0xfd8dd970fddcdd9a, 0x56ab69a403b6b9fd, 0x2c2f53a3476e45a1, 0x54dd837eccf180c9],
+    [0xeee225bf9423a85e, 0x4fb924adccf8962e, 0x0e7d0a29680d49d5, 0x64f56d735286c35f],
+    [0x0b28f33550c68937, 0x7530f277e06f78ee, 0x2ca874b015120d7e, 0x2ef5299e2077b239],
+    [0xc73f51c7c22d8095, 0xe2f45ed024488f67, 0x384e7dfa49cd4236, 0x0c0981889405b59c],
+    [0x62cfca43f1649c82, 0x0d048e2fdf5659de, 0x26c61ea458288e5a, 0x0d88548e6296171b],
+    [0x22f61123faa6b638, 0x4995f6f1df123842, 0xbce4140202abaaa4, 0x3371c00f3715d44a],
+    [0x706f6d7975b0fe1b, 0x52911085c8d86baa, 0x0348f4857f0006a6, 0x4ce428fec6d178d1],
+    [0x5847aece52070ab0, 0xa3ae2c0d8dfb2a34, 0x6b03521c94582d91, 0x1a3c26d755bf6532],
+    [0x83a6b059adda82b5, 0x57ed4dfb212014c0, 0x35a118742bf482d2, 0x02dbb4709583838c],
+    [0xa48ead411d8ff5a7, 0xa9066690de8f13b9, 0x721b0035259f45f2, 0x41f2dd64b9a0dcea],
+    [0x688726b4108c46a8, 0xa82c0a4dd0efdb24, 0x6993eea6431580c0, 0x5f154892782617b2],
+    [0x81a6e04079e00c0c, 0x67a1be374f60fc42, 0xdb557872f4b7f815, 0x0db98520f9b97cbc],
+    [0x63329c7040d29350, 0xc667809ce1512e59, 0x152cfed30028c5ed, 0x04e9919eb06ff327],
+    [0x82a0c7090ff71288, 0x81c991b856178f61, 0xedd38595feead654, 0x573bc78e3ed162e5],
+    [0xbb7f38ddd455db0b, 0x2695a1996a4db968, 0x1dec942469e07660, 0x102800af87fd92eb],
+    [0xebe81ef1c0fb7a8f, 0x8d619c02902ef775, 0x6f8779acc32d8f18, 0x593d1894c17e5b62],
+    [0x4a81c43e0faffc57, 0x8e96a807b28c1e49, 0x80d6aa16cc03465d, 0x46aaa1206232ceb4],
+    [0xc1c5f2fae4780579, 0x8c36671191d4ee9a, 0xffd5db908bf28b7f, 0x2102aab97ce5bd94],
+    [0xbc94e5b8fb5178b7, 0x5fcb546a2a7d481a, 0xbbe720164c409318, 0x14387b24d1c0c712],
+    [0x5cfd713c9550fe9f, 0xa6cef2cf737f020b, 0xcfced83e64e206f6, 0x5f2179b3a7845836],
+    [0x485e68ab96d57188, 0x657d27323b87e3a8, 0xb5443334562b0bc3, 0x1787986ab56e1b56],
+    [0xdcf4861da65f543a, 0x074c496de2c9ed67, 0x451adda44428aa22, 0x39ef4b00deefe7e7],
+    [0x16b07420bb321c65, 0xac1c5696da8eb194, 0x754cba5e46edc1f1, 0x0c1f1b492b27539d],
+    [0x6c39848c9bd44692, 0x1dfc38e79e7e5351, 0x67f1f184cf191f33, 0x1c4d41a133b97dc4],
+    [0xdda4ac74cf9768e2, 0x0c9288e67a359dc0, 0xf88be9205ab84018, 0x369ea8e699181b1c],
+    [0x533dbde6fb985043, 0xbfa714b9e3100eaa, 0xf0b8a2a719123f7e, 0x4cfa7d72afed332b],
+    [0x4a9e5b3bfbbb3e45, 0x889942e9fe23d9fd, 0xe54c6f34d7a8bd41, 0x4e592fcde9f3c360],
+    [0x2722ed865b8438a5, 0xf54ff91598a948ec, 0x235570996d3a4c40, 0x032b5885586212fb],
+    [0x825982990c068940, 0x3cff0d8a33f3523d, 0xe6614fb134597d3b, 0x03f3178956cfd3e2],
+    [0x0ac08a16c6248f74, 0xe6109af5bb2bd78d, 0xf0ce0d9273d8ad40, 0x3126e84dfd67a22b],
+    [0x56d8e087f4087b6f, 0xa39011390c2dbbcf, 0x8d7b928e9af244f0, 0x3527888062f1e273],
+    [0x421182a8b187ecf7, 0xc3d34c6ff92494f3, 0xbe2eb423bf7b5bf6, 0x64635758efc701db],
+    [0xef7b5b5f9d74546e, 0x6a5f4243dbeb2dda, 0x2a428cbf77ddc191, 0x4d7f71960f03db8a],
+    [0xd74ac12db57b29c5, 0x5359310617f3fc06, 0x43243eba3b9765d7, 0x37832ba2da93de36],
+    [0x6c94b3282ba3fae6, 0xc93147965252cb1d, 0xf96c9e6556a3bb4f, 0x4dce55879ffd9398],
+    [0xf845bfecd6dc4b40, 0x5d98563697a34c0a, 0x0fd5a4727a58c3d8, 0x4ba85e4d2537972c],
+    [0x84d0eb96ff5b02ce, 0x3b503703979689f3, 0xd19734b0f337d342, 0x582dc453b4cbf6b1],
+    [0x091a1c891fa224ba, 0x82f07b23e799566b, 0x3540d69b25420a26, 0x0e6f127f479ee611],
+    [0xfdfff82306f37dbb, 0xe900b2fb16d5a779, 0x8ae5035472944706, 0x39c815508d2995bb],
+    [0xb300d9459f709051, 0xc5060cfd7f95724a, 0x8aa03220372179a4, 0x6591aba215bcf96d],
+    [0x830452a44a343188, 0x0a69e816ace98fad, 0xc546a734ad2cd7f6, 0x221807cb4909d549],
+    [0x59f3035e1336b53f, 0x2b13b0aecb87f386, 0xa58bce78722380b2, 0x2766a1e33038004d],
+    [0x1babd7f53cada9e3, 0x074b661537ff890b, 0xb5d2f5336dc9bad5, 0x11b5e993e6a9cdc3],
+    [0x92deb60ad49eddcd, 0x4c5fc86ec59c0f24, 0x3d0a2c59af26b51f, 0x29576176f9a5a10e],
+    [0xa5f6f72363991e98, 0xe16ee7571f1340a9, 0x747d8a6d333fb2b3, 0x51e72c44f9de491c],
+    [0x23b69bab09f4d1a6, 0x264d04f0289f0987, 0x71e071764a5e41eb, 0x2fb360d959be4aa8],
+    [0x9c1e8c07ae250f47, 0x739a5009311142b6, 0xb595ed61fe13f9e8, 0x03f46b4c3c77957c],
+    [0x7818cec979b57b5e, 0xebfc0c33ba96ad93, 0x7e0ff672cd0543aa, 0x4683311e382a9992],
+    [0x89d271f3dd6bee25, 0xa4dff19ab5c4d289, 0xf254d61c2a9b56d6, 0x3bd670c3ce88ea43],
+    [0xa01b9d7bee361a1b, 0x2c5c6005feed28d8, 0x995d15061ddb487f, 0x2fd2ed0ba1135575],
+    [0xe707ca693f4f933e, 0x810f932a206aacec, 0xb32d8d2ebfe2695f, 0x5cbc378be1db3840],
+    [0x9967aa3ff9970aac, 0x6568f0a14cb14d51, 0xd42e7d39eaca316b, 0x35b716410b3c9374],
+    [0x0bd1465f3852811f, 0xc929b564667377a1, 0xc192db40e586eded, 0x231c6db056e47a01],
+    [0x2962f336558b0f32, 0x43079b2f70230375, 0x4b0f856acb016c7a, 0x4904d5de1f512eb1],
+    [0x3302481eddba4074, 0xc74406a57607c2ba, 0xec7bdd133581f2ab, 0x56d6bc63f429bb7f],
+    [0x1d28ce1022579735, 0x5f28b9f74f1e65d2, 0x67fa79d1afdf36b2, 0x519d0daccadfbb01],
+    [0xc7787f8094e5a71a, 0x7575f85688cdb981, 0xf352bb26da106663, 0x0576cf2418d6bd88],
+    [0x5baa0e3aa49e437a, 0x6ab171db4eadd993, 0x0aa110bdce12e1e6, 0x16672be70221dfa2],
+    [0x80ce4bcbc43b0b69, 0xa3dfcc04adfc6497, 0x77d9c2c18f17b03e, 0x1e51c73bc2aeb9e8],
+    [0xb35d55e4d155e169, 0xa42a9e84e4fdb0dc, 0x8e2ebdfb372f79c8, 0x1271c830507a211c],
+    [0x60adc45f35570b9d, 0xcce371202abc28a2, 0xf0ab01924767b4ae, 0x2e5454b258106b63],
+    [0x1c1e9cd18df36f82, 0x3b2381b72b68e97b, 0x78f05063cfa4e2b7, 0x440f72769f137a80],
+    [0xa2c315d4441b6987, 0x965b8932621aab4c, 0xa39ebb33c5c7cf18, 0x0c0f3630409a2242],
+    [0x03893af36d5e75dc, 0x2cb50ce4682bafe1, 0xdf88b51f253bd903, 0x0d1bd84a786a990a],
+    [0x3a998926539dd401, 0x8bc52ff701c385c4, 0x4aae2f255666b0fe, 0x30ce425059810dd9],
+    [0x2ea311abe5e3d96d, 0xe446dfa668dc73da, 0x1a9ec2ca73099425, 0x395a1e753153b56d],
+    [0x58e256a03d20ef91, 0xae2d689894ced82f, 0x92fdc0c3d21b1a5b, 0x57f09d89e827d003],
+    [0xf6a03854bfdbcacc, 0x4cb5624b0ba76f48, 0x8b3cba3c252daa08, 0x1065b71b135e4feb],
+    [0x59d9bd4c8883203d, 0xbc413e17957977e3, 0x045547952a06bc83, 0x3d5f53bd162f053f],
+    [0x5cd58ffd05322d85, 0x264b42b95b2bed47, 0x9d6b49f697b0a75d, 0x05f467a5081bd347],
+    [0x159e9d6f115df3b8, 0x2e76aa6d62820542, 0x5cdbffae1f00a271, 0x23c293275e282bf1],
+    [0xbf8cde584722d31b, 0xeeeff3beb4113174, 0xbba29308b9922354, 0x3757e7009ca9bec8],
+    [0xe53715ae24725342, 0xbe114898f84deb06, 0x50473ec95a7ec53e, 0x406f25e72d0264ed],
+    [0x96c4fc753130ad45, 0xe7d3f2c65ac52e44, 0xc551f7440772b056, 0x046dcfa2d6d655c7],
+    [0x3b04687b2063259f, 0x96ab29dbb0486fe5, 0x4e72ce8c1833c337, 0x49c2e954d649ee1c],
+    [0x45af8aebc872a759, 0x28ffd8bf7a5331e2, 0x63dbe8e5efc89d7d, 0x2caa8aae247ef83e],
+    [0xf2d899f8109bc717, 0x09b3d3562fdc3f91, 0x3a3d8cea806e0689, 0x5efa9f8f32d9ec1d],
+    [0x09122c8c39aa1301, 0x92b933250b79371e, 0x95cd7380029a6336, 0x0df424bdf3b0c603],
+    [0x4321906efb474930, 0x56dbae1741f50157, 0x8aed6f38d5cc8c34, 0x2d012e3e811cf4b8],
+    [0x17dc8077dc830916, 0xcea114185a8ed34e, 0x79a1dad95740513d, 0x1bb9b23d6805ed11],
+    [0xd4b7bd8a38a47f61, 0x690c200d0f2318aa, 0x1e2349b1e50b56d0, 0x0fab922a838c55af],
+    [0xf3054f59d3dc74d1, 0xf5e80fd5984afc67, 0x89c73db7a4ff48d9, 0x4d58799d4501ee8e],
+    [0xb7bf8e293b46a758, 0x8765956ad2ca7b75, 0x40da337d187934e4, 0x4f130b733cb78f39],
+    [0x259e7ed4598cca97, 0x923a743a4e60e58a, 0xaef03c3beadfb882, 0x03e7812afd6c480f],
+    [0xc7ba0ca2decd7d01, 0x373ef7ce765ecb1b, 0x1815fde0e9506629, 0x0ae97e00a91a4e76],
+    [0x46d2fa2a8d515c34, 0xff865c4b1b8bd66e, 0x8c2b5c632cb1854d, 0x18d683776871c191],
+    [0xc7e69d574eb5a15a, 0x91700acad1b543e8, 0x5475eb8b04b0e2ae, 0x3597acab641c21dc],
+    [0xa44d341b9e7d24a2, 0xf2b9703e5814ddec, 0x4e8bf2ddd6bcaee6, 0x63df64938297594b],
+    [0x7ad61bde4009b873, 0xcd12a88d1f9b583d, 0x5da22615073e9839, 0x009ab455f6b4c775],
+    [0xf61cc23d1c673a12, 0x7743bbeea99ac2b8, 0xc26d0fb7a3ebfd3a, 0x09e21d43c56b0abf],
+    [0x90ada0a60df0d16c, 0x136a0784b73789d5, 0x9417be43c93a9f6d, 0x4db404b9eae6a9f3],
+    [0xfc905bfeaf2b80a9, 0x10ee19def3836b78, 0xaaebeaf3f8ccb00a, 0x0c6f0ecaf32a3d60],
+    [0xc44ce8974ea8ad8d, 0x038720408a3c822d, 0x48165b9796a4279d, 0x3518d688407ca0e5],
+    [0xb37741de7ac5b185, 0x2b56455c30e6da78, 0xe23aa0340dc266b3, 0x27ba9d4584a23881],
+    [0x32e0efab4c3ad6eb, 0x9143d60fe00817cf, 0x50858e482fbff7c2, 0x63d33e44fda7868d],
+    [0x4bf01c71f2641ae9, 0xf21b22cff2d79c11, 0x3d67a5022d9a221c, 0x561a72b93fecdbd8],
+    [0xcb6994824c2e5e36, 0xac6424801502ff4f, 0x71aa28bc07a5ba88, 0x48a1625a9ee11029],
+    [0x6a18ecd47667d1bc, 0xbe4d63a3847634b4, 0x0fa8ef7dbb356366, 0x46a003c184ecf0e0],
+    [0xbb36ded243ded838, 0x997947b96f633607, 0x63a60821e50853d0, 0x37d6efb2876f3cba],
+    [0xbefa447ab1918163, 0x598bdff1f6c0219b, 0xf98a5bed063f6911, 0x14f96acdb291ed2b],
+    [0x8abf95e037d3c668, 0xbdd1a2c792cbadd5, 0x24efced0c465587c, 0x573d156263dc8edf],
+    [0xbbad18abb9891f95, 0xaff07615747d2101, 0xb35b3a07d8406acb, 0x46839e7d70370149],
+    [0x61fbc8031d8ff59b, 0x16f8d17d082da9ba, 0x08fe8d8fcb51a81f, 0x3b74a3420d1b9884],
+    [0x8f4e44016aaa172d, 0x7c56c16bfbbe6a05, 0x6798b07a8578611d, 0x059f3301178a2202],
+    [0x73e1de428eaa3f32, 0x2e987c58b7ef4868, 0x8b07acf918453746, 0x467d9ff3508feb31],
+    [0x940a7531105ef7e0, 0xcb7ae0c30d4aa141, 0xbba4f59c68f312be, 0x10000c75e6e03366],
+    [0x6925cd8c5888c3ca, 0x06c2cb4c1838c996, 0xa5b6a5736344466a, 0x375487214c07542f],
+    [0x367e289f0964c486, 0x18ed9485449edcfd, 0x06b159772a097f35, 0x2361aaf969f732be],
+    [0x130fd9e4f283f906, 0x1e73dcd64a39b22b, 0xc27023a893c0458d, 0x2ddba8679308f327],
+    [0xec0883e39695b15c, 0x62245106f53d7407, 0x65495bae9685e051, 0x6303e21755b1de4d],
+    [0xdf110ab42f5b0c1f, 0x5d08264f8a0ed320, 0x2b2e1c8f2aacf062, 0x5aa3dddf8da36972],
+    [0xedb5f777284cd1fd, 0xf3294defeee702ef, 0xcd08652d815d7c91, 0x3525eb41c2db9cf9],
+    [0x7a29398c17c26c9e, 0x8d4a0c4827e333d2, 0x7cb0e3c3f4b22553, 0x0079ae4df49f78b9],
+    [0xc13bc7afdd7bc3b7, 0x559c2e2a622207b0, 0x7e19ea4b027cc8bd, 0x533c8c1b05e2dd7e],
+    [0x79f6e2b08024d3ab, 0x8897cba899ddb017, 0x544e5cd428889506, 0x4989a01e4fe4b1bd],
+    [0xafbd2fcc42cdd3c8, 0xde05381fc74704a2, 0x8c3104808dc68ae3, 0x1c7f5858eabb1e2b],
+    [0x345238c6dc557639, 0x1e4729c90b4cce4c, 0x35413b9808c135fb, 0x55faf16bbea2ee0f],
+    [0x3f255e075c72ebd0, 0xcbce0ecaafdd13b9, 0xd9c8c436f89c6f9e, 0x156a82f8e5aea455],
+    [0x5864aee420724703, 0xf25ba0b7387230a0, 0xd7bc128e9a107582, 0x37c7047032df0027],
+    [0x2a83a9b6b6f4b264, 0xd87492cbde5c1c26, 0xaf06d5000ceedb82, 0x40ab847795176c24],
+    [0x8bc9ed9bda7af685, 0x2f36e2250c76711e, 0x2de9ef263d503615, 0x5a73bece689545bd],
+    [0xb64ab5d21d9562f5, 0xacc57371aeebb06c, 0x0b4f38e56f988cdd, 0x1c4a903be5dff444],
+    [0xf367277a2302b2f8, 0x100b0bd86d69b4b4, 0xc7c176730fd05cfd, 0x5bba81a692e87b51],
+    [0x0b90ddb26902319d, 0x1541d4b184b7e1bc, 0x920c3d7bedd74c10, 0x2f875bdd6669a8ff],
+    [0x084b763efceff3bd, 0x8d3f1654050c7ffe, 0x6024db13c58bbc74, 0x5e89035bbe943f9e],
+    [0xbeff0c0be47e5a51, 0x2b8f74c4ac74c350, 0x3053bb62c0710000, 0x24ce56469aeaa424],
+    [0xa6a587be0fb0081c, 0xd4306fd5f4247ad0, 0x028e9aa00a136613, 0x59922ae3f06524d2],
+    [0x3709e0f1d9185552, 0x60b1e9199b5f59ed, 0x1a0c2b5313f8ac96, 0x50d8b98688f4980b],
+    [0x94caa605b4a3caf1, 0xcb86ed6e25e536fa, 0xb57cfc898fb68342, 0x3184262ef10e9b0a],
+    [0x0880e80bbf8078e1, 0xf95e41e3778fe042, 0x745ba00bd9094b67, 0x1d5a91b930b89934],
+    [0x0882eb79c445778f, 0x15c1aadadccda74e, 0x9b2f70c8b54e4256, 0x0ddebce4b6ca45d6],
+    [0x9c4240b948cde438, 0x94e6c958f96f3d7d, 0x0ffc0e5426e1bb68, 0x3dcc6a17e074d035],
+    [0x3c9eb57adeca2308, 0xd3a9b59797394c3d, 0x51cb5cfc458b0f4a, 0x03b8aba0ee959a4e],
+    [0x14c074d461001ec4, 0x2d193fc1a203ccc5, 0x5375c71d71bbb97b, 0x0f24cc57f3b2fbf2],
+    [0xa8adf701d68cd02a, 0x803d6674e65117f1, 0xcda6405a6c52a47e, 0x3ac9c11890e96a2d],
+    [0xf5c0e7a68dadb63c, 0x751b88997d8fba1a, 0xccb8a02202482023, 0x45c00146e1b89ad5],
+    [0x7ae035b04bda3b5c, 0xf74b57e15b02e163, 0x2e28cc3d8b85e334, 0x1f98bdb8dc318e3e],
+    [0x782004f65f51257b, 0x3019cddc8ce55cf9, 0xfe7ddd8b8a7f900f, 0x2ec077dbbc7bf2af],
+    [0x1fbfec1ff337568d, 0xfaf1c2d1a618db01, 0x2c2ef5d5376e2e31, 0x32c377fc988f600a],
+    [0x38e829db8d05861e, 0xc7f2eb345ab6c21d, 0x3bdfc1a053d8acee, 0x0a820d131da84438],
+    [0x7d5a40f961092e60, 0xe786dba563e210b7, 0xe263e013f45a92c0, 0x5bd95df8a933f7b7],
+    [0xd0a186cd3817708d, 0x9e78e796e8b3735b, 0xb420fb3f31c06429, 0x264cf7b75095fb96],
+    [0xb891e71d11dc29f2, 0x9836be92ee92429a, 0xa9a5d329e00a128c, 0x27d3e47b2f11ada6],
+    [0x0548689295d2bce2, 0xfbbde04d85bef4ad, 0x19e4afd891e619a8, 0x64354b412c8cfa13],
+    [0x0b855a5afac1f770, 0xbd32887e911de52d, 0x0836fb7135bce37f, 0x0db0f967487ee52e],
+    [0xa5000f5396978e9d, 0xd870737a9ce5d9d3, 0x6475995417197faa, 0x1c9a155911b36c89],
+    [0x964f1f93180ac531, 0x669dc0f8865b140f, 0xf870fa2804bfb88e, 0x65ae557151ae9ec7],
+    [0xd136e7fe885fcb40, 0x74749106a6dad534, 0x066020764fef4a55, 0x52c6f6242517362c],
+    [0x85c2adf032c8b710, 0xd45a458dccb059c7, 0xa8595049a285944b, 0x2146d3e371040feb],
+    [0xadcdb19fabdff0b9, 0xa9b07a5a25824aa6, 0x499a37c20ffc9eac, 0x16db9ceb3074a795],
+    [0x650c4e228a6e6571, 0xd3a1785039397060, 0xc9cf2a273017eb01, 0x5903725fd86fec14],
+    [0x47189885cbbc566a, 0xc414bc2aebacd81c, 0x9e05718bd1f59bb6, 0x54c75952f908e3f9],
+    [0x76dd0b52619a39f6, 0x22870ca90bcee7b5, 0xe7f309317b7b9f5c, 0x0dba4abc7f188e33],
+    [0xfffa4d16366d257e, 0xed6f364b9346541d, 0xad3ba5b78cad4c6b, 0x3950231611808399],
+    [0xb3c57160a5bc4ae2, 0x05931dd87e200fc7, 0xf552b9bab8babfe5, 0x1a6d8230bb9e8d1a],
+    [0x175424bf063c6e18, 0xbd709ec9497a3162, 0x9424e29c37acb7df, 0x37c3720b132d3a71],
+    [0xe26b4bf8fd6838fb, 0x2fd499c7e6ed1ee6, 0x05c5e93ae7008480, 0x500f85a3d06a0b5a],
+    [0xab27be85f4faaaf4, 0xe539b73f83c6e7ea, 0xd575186d0787fc8c, 0x2b37f70d73366d32],
+    [0x2a53aab21ddb68de, 0x04e2386857a4befa, 0x415e5c4814f33668, 0x1d8efd6e52d4f936],
+    [0xc2d4517e3eb390e3, 0x3f3a64c59fe2a9c5, 0xe65a977907d17d13, 0x33303b8a8f2d811b],
+    [0xbb0272b5ca14a449, 0xab3ee15c145f404f, 0xaf4a9a6bf095c65f, 0x2c1ba860f51e0c2e],
+    [0x584db9a75aec2378, 0xf66ef917d09c7957, 0xa89d101081c9c9f4, 0x0b0849c7a3adea03],
+    [0x2f8a734d4abdc792, 0xb53f103fcb752ac8, 0x809c3e757821c869, 0x41e7e30c77579da7],
+    [0x75ff52a716371b9c, 0x24fe8d7d43e628bf, 0x5e6518430845f989, 0x182e66be60686c8c],
+    [0x74f3d06b1a00ddc4, 0x2661e664dcf4ff2a, 0x88fa4f54a6edf02f, 0x373b2508c2fca1a2],
+    [0x393533cee1a45c30, 0xa6f56bdb029e50c1, 0xbbe630f308e03f1a, 0x1735b442b3acaad0],
+    [0x59feb5d75f4a1f9f, 0x220491d76caeaa59, 0x911bcef1367e10aa, 0x22abe8ea470a0372],
+    [0xd9c92f2719b8e80b, 0x364a83c6f065d92b, 0x4f64151ed21abfa5, 0x5caab387eb997f77],
+    [0xc5e2e835aaeff725, 0x8d07c668ef25a36b, 0x77897b56e1c43297, 0x57b33094aeff8283],
+    [0x4252db4ab66c359e, 0xb5339a8478995fee, 0x3660bd435a53725f, 0x358ac928badd1ef7],
+    [0x4ba2c553892d9af7, 0xb9453a73b0a40743, 0xa9a92a30fb681234, 0x467775be1af25a92],
+    [0x129a4315f0ab7826, 0x2cf11aec4192113d, 0x5d3e36292443f39e, 0x1bfdbf64e67ff597],
+    [0x6f37d5b0e8cf6a13, 0x58d2662f32883239, 0x442d03704f69a117, 0x0e5626898ad3536a],
+    [0xdd652169047a78cd, 0x6287102cc3d0cb40, 0xf25b68eef5f9381d, 0x4f152629febdb816],
+    [0x52001c0e56b7e356, 0xb1a1e52c7fe3eaff, 0x800b6984e8637186, 0x042e6e8b6a8c7e0d],
+    [0x38577804d118a66d, 0x8b6a998da7a1c609, 0xcc9babf29615282a, 0x2ba0ed1cd48e7c41],
+    [0x0d66f2874458bb0b, 0xb1815771790f7a1e, 0x97ab2c07d8512465, 0x451b4aa5d75c0061],
+    [0x62a9ef0ed4d60c68, 0x2b814612e13da9b5, 0xce5449dca561e4a1, 0x33986da007912b8e],
+    [0x4f4fab4d971f52d5, 0xdfd4974eab3fe443, 0x5e013bd9a69740bf, 0x09b1eac0bc3f0436],
+    [0x93fc8fff5a9629c2, 0x32046825ecdbf1fb, 0x5703b948c071b450, 0x0a83445e64ce7235],
+    [0x84a233e67ce66f5c, 0x03df5a2919dce099, 0x67094e56d4c19679, 0x46c7d746c5fde22b],
+    [0x37660d3901e718d7, 0xe30571677c0e6201, 0x116515c22ee0312b, 0x3694ddb54723b79e],
+    [0x1e071c1851effa37, 0xdb171dd98e2000d6, 0x448edc98e427e56b, 0x44e6207a43220854],
+    [0xaa93b0b42a47625e, 0x316d67506bff0467, 0xce706501f41f8aa9, 0x62bfef45071e5a08],
+    [0x6e63859bfdb9a7c5, 0xf2e75a6bb72fd00c, 0xdfb3e9ba46b5396f, 0x35e498a680b14b19],
+    [0xceb88ac1b11e84d4, 0xb77c3b00f2fb1b22, 0x904af7f804a1c0a5, 0x23d048570a85d6e0],
+    [0xd01b94f808273342, 0xfae5127e627644c4, 0x15944622ee42ec1a, 0x182e5321536a111d],
+    [0x680a1fe7dd01aa36, 0x12cd203a7509c9bd, 0x7a51cc455c853493, 0x09f8b5e1f2ef8db4],
+    [0x4f7795a10126d189, 0x1e3e4aa80dbb8c6c, 0xdecc334168df472b, 0x44dba927e2000f1c],
+    [0xa85d9abeb0e21e31, 0x5dc00dd366d62846, 0x87ff5e46b21b895c, 0x12f4d9fae5564aa2],
+    [0x16e29445ea0edbec, 0xf2a1cf003877af42, 0x91903adae80ec4cb, 0x33bb22a0b956e43e],
+    [0x42d294ee3bad54c0, 0x0ad38149acaa98a3, 0x816781e966262dcb, 0x5b04d9533aa395ff],
+    [0x4c5557089ed79e1f, 0xb94eee065a26780e, 0x5bc5aa61c97e6669, 0x57a03c90dd71645e],
+    [0x428510d9b116523a, 0x6d7a60ab2a2afc48, 0x20c608230ab54353, 0x239e1c12d3b9d588],
+    [0xe3b746a4aa0a62da, 0x944bc7bf8848ef86, 0x4cf3ebaa4ec3d9ac, 0x049d0ecd8f91b28c],
+];
+
+#[rustfmt::skip]
+pub const FT255_MDS: [[[u64; 4]; 3]; 3] = [
+    [
+        [0x81b4443db7ceb14e, 0x74e49f619d7f7b2e, 0xcf1cf00b2121b3b8, 0x0627c98f8d83845d],
+        [0xf6e032cdf35460d2, 0x822ba7a07be596cb, 0xa73d2c0d56edbc4f, 0x61c6381d8fa9bc11],
+        [0x820fd9f4f86c3ec5, 0x6d01055c59442251, 0x43dcd2f2ced8a9cc, 0x5540740d0a5b4bdc],
+    ],
+    [
+        [0x704889abfa88a873, 0xc568058c74418cd5, 0x5f167318554d3994, 0x2e7f76bef78515f0],
+        [0x5cb667091b3d1020, 0x92c83d01c9843bd3, 0xd4e1ce9e7aa604b6, 0x3ed0224665668423],
+        [0x12f642ad851a733b, 0x16446e76d6eaa252, 0x163fc8535481ff50, 0x0de2bcd77c0ee71f],
+    ],
+    [
+        [0x34c5b12d4d5cd678, 0x551a7516b34ef58e, 0x2ef6059a04f4216a, 0x48fd04d823a868d9],
+        [0x2f232e031ee28fa9, 0xdba6670ddae69910, 0x4e838145033471ec, 0x43510efed393adf2],
+        [0xf7277baa15b5b025, 0xb4590fc758dff704, 0x33e61cb3c08b6934, 0x2c11015d4cdf5f25],
+    ],
+];
@@ -1,38 +1,151 @@
 use crate::algebra::field::{as_bytes_vec, Field};
-use crate::merkle_tree::MerkleTreeVerifier;
+use crate::error::VerifyError;
+use crate::merkle_tree::{MerkleHasher, MerkleTreeVerifier, SALT_SIZE};
+use crate::serialize::{write_field, write_usize, ByteReader};
 use std::collections::HashMap;
-use std::mem::size_of;
 
+// A batched opening of some leaves of a committed codeword. Leaf j holds the values at
+// positions j + t * leave_number for t below the arity of the tree, and `values` lists
+// them leaf by leaf in increasing leaf order. The verifier knows which leaves it asked
+// for, so neither the values nor the authentication paths carry any index.
 #[derive(Clone)]
 pub struct QueryResult<T: Field> {
     pub proof_bytes: Vec<u8>,
-    pub proof_values: HashMap<usize, T>,
+    pub values: Vec<T>,
+    // The leaf salts of a salted tree, in the order of the queried leaves
+    pub salts: Vec<[u8; SALT_SIZE]>,
+}
+
+// The values of an opening keyed by their position in the codeword
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenedValues<T: Field> {
+    values: HashMap<usize, T>,
+}
+
+impl<T: Field> OpenedValues<T> {
+    pub fn get_value(&self, round: usize, index: usize) -> Result<T, VerifyError> {
+        self.values
+            .get(&index)
+            .copied()
+            .ok_or(VerifyError::MissingValue { round, index })
+    }
 }
 
 impl<T: Field> QueryResult<T> {
-    pub fn verify_merkle_tree(
+    // The opening of `leaf_indices`, which are sorted and free of repeats first
+    pub fn new(
+        leaf_indices: &[usize],
+        leave_num: usize,
+        arity: usize,
+        value: impl Fn(usize) -> T,
+        proof_bytes: Vec<u8>,
+        salts: Vec<[u8; SALT_SIZE]>,
+    ) -> Self {
+        QueryResult {
+            proof_bytes,
+            values: leaf_indices
+                .iter()
+                .flat_map(|j| (0..arity).map(|t| value(*j + t * leave_num)).collect::<Vec<_>>())
+                .collect(),
+            salts,
+        }
+    }
+
+    // The number of values in every leaf, if they split evenly between the leaves
+    fn arity(&self, leaf_indices: &[usize]) -> Option<usize> {
+        if leaf_indices.is_empty() || !self.values.len().is_multiple_of(leaf_indices.len()) {
+            return None;
+        }
+        Some(self.values.len() / leaf_indices.len())
+    }
+
+    // `leaf_indices` have to be increasing, as the verifiers derive them
+    pub fn verify_merkle_tree<H: MerkleHasher>(
         &self,
-        leaf_indices: &Vec<usize>,
-        merkle_verifier: &MerkleTreeVerifier,
+        leaf_indices: &[usize],
+        merkle_verifier: &MerkleTreeVerifier<H>,
     ) -> bool {
-        let leaves: Vec<Vec<u8>> = leaf_indices
+        let arity = match self.arity(leaf_indices) {
+            Some(arity) => arity,
+            None => return false,
+        };
+        if !self.salts.is_empty() && self.salts.len() != leaf_indices.len() {
+            return false;
+        }
+        let leaves = self
+            .values
+            .chunks(arity)
+            .enumerate()
+            .map(|(k, coset)| {
+                let mut leaf = as_bytes_vec(coset);
+                if let Some(salt) = self.salts.get(k) {
+                    leaf.extend_from_slice(salt);
+                }
+                leaf
+            })
+            .collect::<Vec<_>>();
+        merkle_verifier.verify(&self.proof_bytes, leaf_indices, &leaves)
+    }
+
+    // Gives the values back their positions, from the leaves the verifier queried
+    pub fn open_values(
+        &self,
+        leaf_indices: &[usize],
+        leave_number: usize,
+    ) -> Result<OpenedValues<T>, VerifyError> {
+        let arity = self
+            .arity(leaf_indices)
+            .ok_or(VerifyError::MalformedProof)?;
+        let values = leaf_indices
             .iter()
-            .map(|x| {
-                as_bytes_vec(&[
-                    self.proof_values.get(x).unwrap().clone(),
-                    self.proof_values
-                        .get(&(x + merkle_verifier.leave_number))
-                        .unwrap()
-                        .clone(),
-                ])
+            .zip(self.values.chunks(arity))
+            .flat_map(|(j, coset)| {
+                coset
+                    .iter()
+                    .enumerate()
+                    .map(move |(t, x)| (*j + t * leave_number, *x))
             })
             .collect();
-        let res = merkle_verifier.verify(self.proof_bytes.clone(), leaf_indices, &leaves);
-        assert!(res);
-        res
+        Ok(OpenedValues { values })
+    }
+
+    pub fn write_bytes(&self, buf: &mut Vec<u8>) {
+        write_usize(buf, self.proof_bytes.len());
+        buf.extend_from_slice(&self.proof_bytes);
+        write_usize(buf, self.values.len());
+        for x in &self.values {
+            write_field(buf, x);
+        }
+        write_usize(buf, self.salts.len());
+        for salt in &self.salts {
+            buf.extend_from_slice(salt);
+        }
+    }
+
+    pub fn read_bytes(reader: &mut ByteReader) -> Result<Self, VerifyError> {
+        let len = reader.read_usize()?;
+        let proof_bytes = reader.read_bytes(len)?.to_vec();
+        let value_num = reader.read_usize()?;
+        let values = (0..value_num)
+            .map(|_| reader.read_field())
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        let salt_num = reader.read_usize()?;
+        let salts = (0..salt_num)
+            .map(|_| Ok(reader.read_bytes(SALT_SIZE)?.try_into().unwrap()))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        Ok(QueryResult {
+            proof_bytes,
+            values,
+            salts,
+        })
     }
 
+    // The length of the encoding `write_bytes` produces
     pub fn proof_size(&self) -> usize {
-        self.proof_bytes.len() + self.proof_values.len() * size_of::<T>()
+        let field_size = T::from_int(0).to_bytes().len();
+        3 * 8
+            + self.proof_bytes.len()
+            + self.values.len() * field_size
+            + self.salts.len() * SALT_SIZE
     }
 }
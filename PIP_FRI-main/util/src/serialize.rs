@@ -0,0 +1,62 @@
+use crate::algebra::field::Field;
+use crate::error::VerifyError;
+use crate::merkle_tree::MERKLE_ROOT_SIZE;
+
+// Helpers for the length-prefixed little-endian proof encodings. Every read checks the
+// remaining length first, so truncated or oversized inputs surface as `MalformedProof`.
+pub fn write_u64(buf: &mut Vec<u8>, x: u64) {
+    buf.extend_from_slice(&x.to_le_bytes());
+}
+
+pub fn write_usize(buf: &mut Vec<u8>, x: usize) {
+    write_u64(buf, x as u64);
+}
+
+pub fn write_field<T: Field>(buf: &mut Vec<u8>, x: &T) {
+    buf.extend_from_slice(&x.to_bytes());
+}
+
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes }
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], VerifyError> {
+        if len > self.bytes.len() {
+            return Err(VerifyError::MalformedProof);
+        }
+        let (res, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(res)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, VerifyError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn read_usize(&mut self) -> Result<usize, VerifyError> {
+        usize::try_from(self.read_u64()?).map_err(|_| VerifyError::MalformedProof)
+    }
+
+    pub fn read_root(&mut self) -> Result<[u8; MERKLE_ROOT_SIZE], VerifyError> {
+        Ok(self.read_bytes(MERKLE_ROOT_SIZE)?.try_into().unwrap())
+    }
+
+    pub fn read_field<T: Field>(&mut self) -> Result<T, VerifyError> {
+        let field_size = T::from_int(0).to_bytes().len();
+        T::from_bytes(self.read_bytes(field_size)?).ok_or(VerifyError::MalformedProof)
+    }
+
+    pub fn finish(self) -> Result<(), VerifyError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(VerifyError::MalformedProof)
+        }
+    }
+}
@@ -0,0 +1,686 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::algebra::{
+    coset::Coset,
+    fft::FftPlan,
+    field::{as_bytes_vec, Field},
+};
+use crate::merkle_tree::{opening_nodes, Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
+use crate::query_result::QueryResult;
+
+// Coefficients that can be read back in chunks, possibly several times
+pub trait CoefficientSource<T: Field> {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    // Fills `buf` with the coefficients starting at `start`
+    fn read_chunk(&mut self, start: usize, buf: &mut [T]) -> io::Result<()>;
+}
+
+// In-memory coefficients, e.g. a slice over a memory-mapped file
+impl<T: Field> CoefficientSource<T> for &[T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn read_chunk(&mut self, start: usize, buf: &mut [T]) -> io::Result<()> {
+        buf.copy_from_slice(&self[start..start + buf.len()]);
+        Ok(())
+    }
+}
+
+fn field_size<T: Field>() -> usize {
+    T::from_int(0).to_bytes().len()
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid field element")
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn read_values<T: Field>(reader: &mut impl Read, buf: &mut [T]) -> io::Result<()> {
+    let mut bytes = vec![0u8; field_size::<T>()];
+    for x in buf.iter_mut() {
+        reader.read_exact(&mut bytes)?;
+        *x = T::from_bytes(&bytes).ok_or_else(invalid_data)?;
+    }
+    Ok(())
+}
+
+// Coefficients stored back to back in their `to_bytes` encoding
+pub struct FileSource<T: Field> {
+    file: BufReader<File>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Field> FileSource<T> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let bytes = file.metadata()?.len() as usize;
+        // A trailing partial element means the file holds something else
+        if !bytes.is_multiple_of(field_size::<T>()) {
+            return Err(invalid_data());
+        }
+        let len = bytes / field_size::<T>();
+        Ok(FileSource {
+            file: BufReader::new(file),
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    // Spills a single-pass iterator to `path` so that it can be read several times
+    pub fn from_iter<I: IntoIterator<Item = T>>(path: &Path, coefficients: I) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for c in coefficients {
+            writer.write_all(&c.to_bytes())?;
+        }
+        writer.flush()?;
+        Self::open(path)
+    }
+}
+
+impl<T: Field> CoefficientSource<T> for FileSource<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read_chunk(&mut self, start: usize, buf: &mut [T]) -> io::Result<()> {
+        self.file
+            .seek(SeekFrom::Start((start * field_size::<T>()) as u64))?;
+        read_values(&mut self.file, buf)
+    }
+}
+
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A fresh path under `dir`, which no other spilled file of this process uses
+fn spill_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!(
+        "{}-{}-{}",
+        name,
+        std::process::id(),
+        SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+// A table of `len` field elements in a file of its own under a spill directory, which is
+// removed with the table. Reads and writes seek in the file, one at a time.
+pub struct SpilledTable<T: Field> {
+    path: PathBuf,
+    file: Mutex<File>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Field> SpilledTable<T> {
+    pub fn new(dir: &Path, len: usize) -> io::Result<Self> {
+        let path = spill_path(dir, "table");
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        file.set_len((len * field_size::<T>()) as u64)?;
+        Ok(SpilledTable {
+            path,
+            file: Mutex::new(file),
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    // Writes the table front to back, `fill(start, buf)` filling the `chunk_size` entries
+    // from `start` on, or the ones left at the end
+    pub fn from_chunks(
+        dir: &Path,
+        len: usize,
+        chunk_size: usize,
+        mut fill: impl FnMut(usize, &mut [T]) -> io::Result<()>,
+    ) -> io::Result<Self> {
+        let table = Self::new(dir, len)?;
+        {
+            let mut file = table.file.lock().unwrap();
+            file.seek(SeekFrom::Start(0))?;
+            let mut writer = BufWriter::new(&mut *file);
+            let mut buf = vec![T::from_int(0); chunk_size.min(len)];
+            for start in (0..len).step_by(chunk_size.max(1)) {
+                let buf = &mut buf[..chunk_size.min(len - start)];
+                fill(start, buf)?;
+                writer.write_all(&as_bytes_vec(buf))?;
+            }
+            writer.flush()?;
+        }
+        Ok(table)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Fills `buf` with the entries from `start` on
+    pub fn read(&self, start: usize, buf: &mut [T]) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((start * field_size::<T>()) as u64))?;
+        read_values(&mut BufReader::new(&mut *file), buf)
+    }
+
+    // Overwrites the entries from `start` on with `values`
+    pub fn write(&self, start: usize, values: &[T]) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start((start * field_size::<T>()) as u64))?;
+        file.write_all(&as_bytes_vec(values))
+    }
+
+    // Keeps the first `len` entries only
+    pub fn truncate(&mut self, len: usize) -> io::Result<()> {
+        assert!(len <= self.len);
+        self.file
+            .get_mut()
+            .unwrap()
+            .set_len((len * field_size::<T>()) as u64)?;
+        self.len = len;
+        Ok(())
+    }
+}
+
+impl<T: Field> CoefficientSource<T> for SpilledTable<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read_chunk(&mut self, start: usize, buf: &mut [T]) -> io::Result<()> {
+        self.read(start, buf)
+    }
+}
+
+impl<T: Field> Drop for SpilledTable<T> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// A committed codeword whose values and Merkle layers live on disk. The root and the
+// query answers are the same as those of `InterpolateValue` over the same evaluations.
+pub struct SpilledCodeword<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    dir: PathBuf,
+    size: usize,
+    block_size: usize,
+    log_arity: usize,
+    // Shared openings seek in these, one at a time
+    values: Mutex<File>,
+    layers: Vec<Mutex<File>>,
+    root: [u8; MERKLE_ROOT_SIZE],
+    _marker: PhantomData<(T, H)>,
+}
+
+impl<T: Field, H: MerkleHasher> SpilledCodeword<T, H> {
+    // Encodes the coefficients over `coset` with a four-step FFT, in two passes.
+    //
+    // With size = blocks * chunk_size, coefficient k1 + chunk_size * k2 sits in row k2 and
+    // column k1 of a matrix. The first pass reads strips of columns, each of about
+    // batch * chunk_size elements, and evaluates every column at the points x^chunk_size
+    // takes on the coset, spilling the result in row order. Row r is then the polynomial
+    // whose evaluations over the r-th smaller coset s * w^r * <w^blocks> are those of f,
+    // which the second pass computes with a coset FFT per row, `batch` rows at a time.
+    // About batch * chunk_size elements, and at least one column, are held in memory.
+    // Everything else is spilled to a fresh directory under `dir`. Leaves hold the
+    // 2^log_arity values a round of that folding arity takes together. A layout that does not
+    // fit the coset is an `InvalidInput` error.
+    pub fn new<S: CoefficientSource<T>>(
+        coset: &Coset<T>,
+        source: &mut S,
+        chunk_size: usize,
+        batch: usize,
+        log_arity: usize,
+        dir: &Path,
+    ) -> io::Result<Self> {
+        let n = source.len();
+        let size = coset.size();
+        let arity = 1 << log_arity;
+        if log_arity == 0 || !chunk_size.is_power_of_two() || chunk_size < arity {
+            return Err(invalid_input("chunks must be powers of two holding a leaf"));
+        }
+        if chunk_size > size || n > size {
+            return Err(invalid_input("the coefficients do not fit the coset"));
+        }
+        if batch == 0 {
+            return Err(invalid_input("the batch must hold at least one row"));
+        }
+        let blocks = size / chunk_size;
+        let leave_num = size >> log_arity;
+        let field_size = field_size::<T>();
+
+        let dir = spill_path(dir, "codeword");
+        fs::create_dir_all(&dir)?;
+
+        // Column k1 holds the coefficients k1 + chunk_size * k2, which are zero past the
+        // rows the source fills
+        let rows = n.div_ceil(chunk_size);
+        let width = (batch * chunk_size / blocks).clamp(1, chunk_size);
+        let column_plan = FftPlan::new(blocks);
+        let column_shift = coset.shift().pow(chunk_size);
+        let mut transposed = File::create(dir.join("transposed"))?;
+        transposed.set_len((size * field_size) as u64)?;
+        let mut chunk = vec![T::from_int(0); width];
+        for first in (0..chunk_size).step_by(width) {
+            let width = width.min(chunk_size - first);
+            let mut columns = vec![vec![T::from_int(0); blocks]; width];
+            for row in 0..rows {
+                let start = row * chunk_size + first;
+                if start >= n {
+                    break;
+                }
+                let len = width.min(n - start);
+                source.read_chunk(start, &mut chunk[..len])?;
+                for (column, c) in columns.iter_mut().zip(chunk[..len].iter()) {
+                    column[row] = *c;
+                }
+            }
+            for column in columns.iter_mut() {
+                column_plan.coset_fft(column, column_shift);
+            }
+            for r in 0..blocks {
+                transposed.seek(SeekFrom::Start(
+                    ((r * chunk_size + first) * field_size) as u64,
+                ))?;
+                transposed.write_all(&as_bytes_vec(
+                    &columns.iter().map(|x| x[r]).collect::<Vec<_>>(),
+                ))?;
+            }
+        }
+        drop(transposed);
+
+        // Block r holds the values at positions r + blocks * i, so leaf r + blocks * i holds
+        // entries i + j * chunk_size / arity of the same block
+        let mut transposed = BufReader::new(File::open(dir.join("transposed"))?);
+        let mut values = BufWriter::new(File::create(dir.join("values"))?);
+        let mut leaves = File::create(dir.join("layer-0"))?;
+        leaves.set_len((leave_num * MERKLE_ROOT_SIZE) as u64)?;
+        let row_plan = FftPlan::new(chunk_size);
+        let stride = chunk_size >> log_arity;
+        let mut g = vec![T::from_int(0); chunk_size];
+        for first in (0..blocks).step_by(batch) {
+            let mut leaf_hashes = vec![vec![]; stride];
+            for r in first..(first + batch).min(blocks) {
+                read_values(&mut transposed, &mut g)?;
+                row_plan.coset_fft(&mut g, coset.element_at(r));
+                for (i, hashes) in leaf_hashes.iter_mut().enumerate() {
+                    let leaf = (0..arity).map(|j| g[i + j * stride]).collect::<Vec<_>>();
+                    hashes.extend_from_slice(&H::hash(&as_bytes_vec(&leaf)));
+                }
+                values.write_all(&as_bytes_vec(&g))?;
+            }
+            for (i, hashes) in leaf_hashes.iter().enumerate() {
+                leaves.seek(SeekFrom::Start(
+                    ((first + blocks * i) * MERKLE_ROOT_SIZE) as u64,
+                ))?;
+                leaves.write_all(hashes)?;
+            }
+        }
+        values.flush()?;
+        drop(leaves);
+        drop(transposed);
+        fs::remove_file(dir.join("transposed"))?;
+        Self::from_leaf_layer(dir, size, chunk_size, log_arity)
+    }
+
+    // Commits to the values `source` holds in codeword order, each leaf read as the
+    // 2^log_arity runs of entries that start at its index, `window` leaves at a time. The
+    // values are stored leaf by leaf, so that folding the leaves reads them front to back.
+    pub fn from_source<S: CoefficientSource<T>>(
+        source: &mut S,
+        log_arity: usize,
+        window: usize,
+        dir: &Path,
+    ) -> io::Result<Self> {
+        let size = source.len();
+        let arity = 1 << log_arity;
+        if log_arity == 0 || !size.is_power_of_two() || size < arity {
+            return Err(invalid_input("the values do not fill leaves of the arity"));
+        }
+        let leave_num = size >> log_arity;
+        let window = window.clamp(1, leave_num);
+        let dir = spill_path(dir, "codeword");
+        fs::create_dir_all(&dir)?;
+
+        let mut values = BufWriter::new(File::create(dir.join("values"))?);
+        let mut leaves = BufWriter::new(File::create(dir.join("layer-0"))?);
+        let mut runs = vec![vec![T::from_int(0); window]; arity];
+        for first in (0..leave_num).step_by(window) {
+            let width = window.min(leave_num - first);
+            for (j, run) in runs.iter_mut().enumerate() {
+                source.read_chunk(first + j * leave_num, &mut run[..width])?;
+            }
+            for t in 0..width {
+                let leaf = runs.iter().map(|run| run[t]).collect::<Vec<_>>();
+                let bytes = as_bytes_vec(&leaf);
+                leaves.write_all(&H::hash(&bytes))?;
+                values.write_all(&bytes)?;
+            }
+        }
+        values.flush()?;
+        leaves.flush()?;
+        drop(values);
+        drop(leaves);
+        Self::from_leaf_layer(dir, size, arity, log_arity)
+    }
+
+    // Hashes the layers above the leaf hashes in `dir`, whose values are stored in blocks of
+    // `block_size`, block r holding the positions r + blocks * i
+    fn from_leaf_layer(
+        dir: PathBuf,
+        size: usize,
+        block_size: usize,
+        log_arity: usize,
+    ) -> io::Result<Self> {
+        let leave_num = size >> log_arity;
+        let mut layers = vec![File::open(dir.join("layer-0"))?];
+        let mut layer_len = leave_num;
+        while layer_len > 1 {
+            let path = dir.join(format!("layer-{}", layers.len()));
+            let mut reader =
+                BufReader::new(File::open(dir.join(format!("layer-{}", layers.len() - 1)))?);
+            let mut writer = BufWriter::new(File::create(&path)?);
+            let mut left = [0u8; MERKLE_ROOT_SIZE];
+            let mut right = [0u8; MERKLE_ROOT_SIZE];
+            for _ in 0..layer_len / 2 {
+                reader.read_exact(&mut left)?;
+                reader.read_exact(&mut right)?;
+                writer.write_all(&H::concat_and_hash(&left, Some(&right)))?;
+            }
+            writer.flush()?;
+            layers.push(File::open(path)?);
+            layer_len /= 2;
+        }
+        let mut root = [0u8; MERKLE_ROOT_SIZE];
+        layers.last_mut().unwrap().read_exact(&mut root)?;
+
+        Ok(SpilledCodeword {
+            values: Mutex::new(File::open(dir.join("values"))?),
+            dir,
+            size,
+            block_size,
+            log_arity,
+            layers: layers.into_iter().map(Mutex::new).collect(),
+            root,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn leave_num(&self) -> usize {
+        self.size >> self.log_arity
+    }
+
+    pub fn commit(&self) -> [u8; MERKLE_ROOT_SIZE] {
+        self.root
+    }
+
+    // The codeword value at position `index` of the coset
+    pub fn value_at(&self, index: usize) -> io::Result<T> {
+        let blocks = self.size / self.block_size;
+        let position = (index % blocks) * self.block_size + index / blocks;
+        let mut value = [T::from_int(0)];
+        let mut file = self.values.lock().unwrap();
+        file.seek(SeekFrom::Start((position * field_size::<T>()) as u64))?;
+        read_values(&mut *file, &mut value)?;
+        Ok(value[0])
+    }
+
+    // Calls `f(t, values)` with the values of every leaf t, reading the codeword once and in
+    // the order it is stored, which is the order of the leaves only for `from_source`
+    pub fn for_each_leaf(
+        &self,
+        mut f: impl FnMut(usize, &[T]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let blocks = self.size / self.block_size;
+        let stride = self.block_size >> self.log_arity;
+        let mut values = BufReader::new(File::open(self.dir.join("values"))?);
+        let mut g = vec![T::from_int(0); self.block_size];
+        for r in 0..blocks {
+            read_values(&mut values, &mut g)?;
+            for i in 0..stride {
+                let leaf = (0..1 << self.log_arity)
+                    .map(|j| g[i + j * stride])
+                    .collect::<Vec<_>>();
+                f(r + blocks * i, &leaf)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_hash(&self, layer: usize, index: usize) -> io::Result<[u8; MERKLE_ROOT_SIZE]> {
+        let mut hash = [0u8; MERKLE_ROOT_SIZE];
+        let mut file = self.layers[layer].lock().unwrap();
+        file.seek(SeekFrom::Start((index * MERKLE_ROOT_SIZE) as u64))?;
+        file.read_exact(&mut hash)?;
+        Ok(hash)
+    }
+
+    // Reads the same helper hashes, in the same order, as `MerkleTreeProver::open`
+    fn open(&self, leaf_indices: &[usize]) -> io::Result<Vec<u8>> {
+        let mut proof = vec![];
+        for (layer, index) in opening_nodes(leaf_indices, self.leave_num(), 1) {
+            proof.extend_from_slice(&self.read_hash(layer, index)?);
+        }
+        Ok(proof)
+    }
+
+    pub fn query(&self, leaf_indices: &[usize]) -> io::Result<QueryResult<T>> {
+        let len = self.leave_num();
+        let mut values = vec![];
+        for j in leaf_indices {
+            for t in 0..1 << self.log_arity {
+                values.push(self.value_at(*j + t * len)?);
+            }
+        }
+        Ok(QueryResult {
+            proof_bytes: self.open(leaf_indices)?,
+            values,
+            salts: vec![],
+        })
+    }
+}
+
+impl<T: Field, H: MerkleHasher> Drop for SpilledCodeword<T, H> {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::field::fp64::Fp64;
+    use crate::algebra::polynomial::Polynomial;
+    use crate::interpolation::InterpolateValue;
+    use crate::merkle_tree::MerkleTreeVerifier;
+    use crate::poseidon::PoseidonAlgorithm;
+
+    // A directory of its own for every test, removed even when the test fails
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn matches_in_memory<H: MerkleHasher>(name: &str) {
+        let coset = Coset::new(1 << 9, Fp64::random_element());
+        let poly = Polynomial::<Fp64>::random_polynomial(1 << 6);
+        let expected = InterpolateValue::<Fp64, H>::new(coset.fft(poly.coefficients().clone()));
+        let temp_dir = TempDir::new(name);
+        let dir = &temp_dir.0;
+        let source_path = dir.join("coefficients");
+        let mut source = FileSource::from_iter(&source_path, poly.coefficients().clone()).unwrap();
+        for (chunk_size, batch) in [(1 << 6, 1), (1 << 4, 3), (1 << 2, 128), (1 << 9, 1)] {
+            let codeword =
+                SpilledCodeword::<Fp64, H>::new(&coset, &mut source, chunk_size, batch, 1, dir)
+                    .unwrap();
+            assert_eq!(codeword.commit(), expected.commit());
+            let indices = vec![3, 4, 17, 200];
+            let proof = codeword.query(&indices).unwrap();
+            let in_memory = expected.query(&indices);
+            assert_eq!(proof.proof_bytes, in_memory.proof_bytes);
+            assert_eq!(proof.values, in_memory.values);
+            let verifier = MerkleTreeVerifier::<H>::new(codeword.leave_num(), &codeword.commit());
+            assert!(proof.verify_merkle_tree(&indices, &verifier));
+            // The values go without their indices, which the verifier puts back
+            let mut bytes = vec![];
+            proof.write_bytes(&mut bytes);
+            assert_eq!(bytes.len(), proof.proof_size());
+            let opened = proof.open_values(&indices, codeword.leave_num()).unwrap();
+            assert_eq!(
+                opened.get_value(0, 200 + 256).unwrap(),
+                expected.value[200 + 256]
+            );
+            assert!(opened.get_value(0, 5).is_err());
+        }
+
+        // Leaves of a larger folding arity, and a source that does not fill whole rows
+        let poly = Polynomial::<Fp64>::random_polynomial(40);
+        let expected = InterpolateValue::<Fp64, H>::with_arity(
+            coset.fft(poly.coefficients().clone()),
+            3,
+            None,
+        );
+        let codeword = SpilledCodeword::<Fp64, H>::new(
+            &coset,
+            &mut poly.coefficients().as_slice(),
+            16,
+            2,
+            3,
+            dir,
+        )
+        .unwrap();
+        assert_eq!(codeword.commit(), expected.commit());
+        let indices = vec![0, 9, 63];
+        assert_eq!(
+            codeword.query(&indices).unwrap().values,
+            expected.query(&indices).values
+        );
+        let mut leaves = vec![vec![]; codeword.leave_num()];
+        codeword
+            .for_each_leaf(|t, values| {
+                leaves[t] = values.to_vec();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            leaves[9],
+            (0..8)
+                .map(|j| expected.value[9 + j * 64])
+                .collect::<Vec<_>>()
+        );
+
+        // The same codeword committed from its values, stored leaf by leaf
+        let codeword =
+            SpilledCodeword::<Fp64, H>::from_source(&mut expected.value.as_slice(), 3, 5, dir)
+                .unwrap();
+        assert_eq!(codeword.commit(), expected.commit());
+        assert_eq!(
+            codeword.query(&indices).unwrap().values,
+            expected.query(&indices).values
+        );
+        let mut order = vec![];
+        codeword
+            .for_each_leaf(|t, values| {
+                assert_eq!(values, leaves[t]);
+                order.push(t);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(order, (0..64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn spilled_table() {
+        let temp_dir = TempDir::new("table");
+        let values = (0..20).map(|i| Fp64::from_int(i)).collect::<Vec<_>>();
+        let mut table = SpilledTable::from_chunks(&temp_dir.0, 20, 8, |start, buf| {
+            buf.copy_from_slice(&values[start..start + buf.len()]);
+            Ok(())
+        })
+        .unwrap();
+        let mut buf = vec![Fp64::from_int(0); 20];
+        table.read(0, &mut buf).unwrap();
+        assert_eq!(buf, values);
+        table.write(3, &values[10..12]).unwrap();
+        table.truncate(6).unwrap();
+        let mut buf = vec![Fp64::from_int(0); 6];
+        table.read_chunk(0, &mut buf).unwrap();
+        assert_eq!(buf[2..5], [values[2], values[10], values[11]]);
+        assert!(table.read(5, &mut [Fp64::from_int(0); 2]).is_err());
+        drop(table);
+        assert_eq!(fs::read_dir(&temp_dir.0).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn rejects_partial_elements() {
+        let temp_dir = TempDir::new("partial");
+        let path = temp_dir.0.join("coefficients");
+        fs::write(&path, [0u8; 13]).unwrap();
+        assert_eq!(
+            FileSource::<Fp64>::open(&path).err().unwrap().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let temp_dir = TempDir::new("layouts");
+        let coset = Coset::new(1 << 6, Fp64::random_element());
+        let layouts = [
+            // (coefficients, chunk_size, batch, log_arity)
+            (1 << 4, 12, 1, 1),
+            (1 << 4, 4, 1, 3),
+            (1 << 4, 8, 1, 0),
+            (1 << 4, 128, 1, 1),
+            (1 << 4, 8, 0, 1),
+            (1 << 7, 8, 1, 1),
+        ];
+        for (n, chunk_size, batch, log_arity) in layouts {
+            let coefficients = vec![Fp64::from_int(1); n];
+            let result = SpilledCodeword::<Fp64>::new(
+                &coset,
+                &mut coefficients.as_slice(),
+                chunk_size,
+                batch,
+                log_arity,
+                &temp_dir.0,
+            );
+            assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        }
+        // Nothing is spilled for a rejected layout
+        assert_eq!(fs::read_dir(&temp_dir.0).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn matches_in_memory_commitment() {
+        matches_in_memory::<Blake3Algorithm>("blake3-codeword");
+        matches_in_memory::<PoseidonAlgorithm<Fp64>>("poseidon-codeword");
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::algebra::field::{as_bytes_vec, Field};
+use crate::merkle_tree::MERKLE_ROOT_SIZE;
+
+// The nonces are u64, so a proof of work of more bits cannot be expected to exist
+pub const MAX_GRINDING_BITS: usize = 64;
+
+// Fiat-Shamir transcript: every prover message is absorbed into a running blake3 state
+// and every verifier challenge is squeezed from it, so prover and verifier derive the
+// same challenges as long as they absorb the same messages in the same order.
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: blake3::Hasher,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            hasher: blake3::Hasher::new(),
+        };
+        transcript.append_message(label);
+        transcript
+    }
+
+    pub fn append_message(&mut self, message: &[u8]) {
+        self.hasher.update(&(message.len() as u64).to_le_bytes());
+        self.hasher.update(message);
+    }
+
+    pub fn append_root(&mut self, root: &[u8; MERKLE_ROOT_SIZE]) {
+        self.append_message(root);
+    }
+
+    pub fn append_field<T: Field>(&mut self, element: &T) {
+        self.append_message(&element.to_bytes());
+    }
+
+    pub fn append_fields<T: Field>(&mut self, elements: &[T]) {
+        self.append_message(&as_bytes_vec(elements));
+    }
+
+    pub fn challenge_bytes(&mut self) -> [u8; 32] {
+        let challenge: [u8; 32] = self.hasher.finalize().into();
+        // Absorb the challenge so that the next squeeze yields a fresh value
+        self.append_message(&challenge);
+        challenge
+    }
+
+    pub fn challenge_field<T: Field>(&mut self) -> T {
+        T::from_hash(self.challenge_bytes())
+    }
+
+    pub fn challenge_fields<T: Field>(&mut self, n: usize) -> Vec<T> {
+        (0..n).map(|_| self.challenge_field()).collect()
+    }
+
+    pub fn challenge_indices(&mut self, n: usize) -> Vec<usize> {
+        (0..n)
+            .map(|_| {
+                let bytes = self.challenge_bytes();
+                u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize
+            })
+            .collect()
+    }
+
+    // Proof of work: finds a nonce that, hashed with the current state, has `bits` leading
+    // zero bits and absorbs it. The prover pays about 2^bits hashes for it, so grinding
+    // on the challenges that follow costs that much more per attempt.
+    pub fn grind(&mut self, bits: usize) -> u64 {
+        debug_assert!(bits <= MAX_GRINDING_BITS);
+        if bits == 0 {
+            return 0;
+        }
+        let state: [u8; 32] = self.hasher.finalize().into();
+        let nonce = find_nonce(&state, bits);
+        self.append_message(&nonce.to_le_bytes());
+        nonce
+    }
+
+    // The verifier side of `grind`, which absorbs the nonce only when it is valid
+    pub fn check_grinding(&mut self, bits: usize, nonce: u64) -> bool {
+        debug_assert!(bits <= MAX_GRINDING_BITS);
+        if bits == 0 {
+            return true;
+        }
+        let state: [u8; 32] = self.hasher.finalize().into();
+        if pow_bits(&state, nonce) < bits {
+            return false;
+        }
+        self.append_message(&nonce.to_le_bytes());
+        true
+    }
+}
+
+fn pow_bits(state: &[u8; 32], nonce: u64) -> usize {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(state);
+    hasher.update(&nonce.to_le_bytes());
+    let hash: [u8; 32] = hasher.finalize().into();
+    u128::from_be_bytes(hash[..16].try_into().unwrap()).leading_zeros() as usize
+}
+
+#[cfg(not(feature = "parallel"))]
+fn find_nonce(state: &[u8; 32], bits: usize) -> u64 {
+    (0..u64::MAX)
+        .find(|&nonce| pow_bits(state, nonce) >= bits)
+        .unwrap()
+}
+
+// `find_first` keeps the nonce, and so the proof, the same as the sequential search
+#[cfg(feature = "parallel")]
+fn find_nonce(state: &[u8; 32], bits: usize) -> u64 {
+    (0..u64::MAX)
+        .into_par_iter()
+        .find_first(|&nonce| pow_bits(state, nonce) >= bits)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::field::mersenne61_ext::Mersenne61Ext;
+
+    #[test]
+    fn deterministic_challenges() {
+        let mut prover_transcript = Transcript::new(b"test");
+        let mut verifier_transcript = Transcript::new(b"test");
+        let root = [7u8; MERKLE_ROOT_SIZE];
+        let value = Mersenne61Ext::random_element();
+        prover_transcript.append_root(&root);
+        prover_transcript.append_field(&value);
+        verifier_transcript.append_root(&root);
+        verifier_transcript.append_field(&value);
+
+        let challenges: Vec<Mersenne61Ext> = prover_transcript.challenge_fields(3);
+        assert_eq!(challenges, verifier_transcript.challenge_fields(3));
+        assert_ne!(challenges[0], challenges[1]);
+        assert_eq!(
+            prover_transcript.challenge_indices(10),
+            verifier_transcript.challenge_indices(10)
+        );
+    }
+
+    #[test]
+    fn messages_change_challenges() {
+        let mut transcript = Transcript::new(b"test");
+        let mut other_transcript = Transcript::new(b"test");
+        transcript.append_field(&Mersenne61Ext::from_int(1));
+        other_transcript.append_field(&Mersenne61Ext::from_int(2));
+        assert_ne!(
+            transcript.challenge_field::<Mersenne61Ext>(),
+            other_transcript.challenge_field::<Mersenne61Ext>()
+        );
+    }
+
+    #[test]
+    fn proof_of_work() {
+        let mut transcript = Transcript::new(b"test");
+        let mut verifier_transcript = transcript.clone();
+        let nonce = transcript.grind(8);
+        // `grind` returns the first valid nonce
+        for wrong in 0..nonce {
+            assert!(!verifier_transcript.clone().check_grinding(8, wrong));
+        }
+        assert!(verifier_transcript.check_grinding(8, nonce));
+        assert_eq!(
+            transcript.challenge_indices(4),
+            verifier_transcript.challenge_indices(4)
+        );
+    }
+}
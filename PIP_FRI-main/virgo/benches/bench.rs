@@ -2,27 +2,24 @@ extern crate criterion;
 
 use criterion::*;
 
-use util::{
-    algebra::{
-        coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
-        polynomial::MultilinearPolynomial,
-    },
-    random_oracle::RandomOracle,
+use util::algebra::{
+    coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
+    polynomial::MultilinearPolynomial,
 };
 use virgo::{prover::FriProver, verifier::FriVerifier};
 
-use util::{CODE_RATE, SECURITY_BITS};
+use util::parameters::FriParameters;
 
 fn commit(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let random_oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
     let vector_interpolation_coset = Coset::new(1 << variable_num, Mersenne61Ext::random_element());
     criterion.bench_function(&format!("bench virgo commit {}", variable_num), move |b| {
         b.iter_batched(
@@ -33,8 +30,9 @@ fn commit(criterion: &mut Criterion, variable_num: usize) {
                     &interpolate_cosets,
                     &vector_interpolation_coset,
                     p,
-                    &random_oracle,
-                );
+                    parameters,
+                )
+                .unwrap();
                 prover.commit_first_polynomial();
             },
             BatchSize::SmallInput,
@@ -49,32 +47,36 @@ fn bench_commit(c: &mut Criterion) {
 }
 
 fn open(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let random_oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
     let vector_interpolation_coset = Coset::new(1 << variable_num, Mersenne61Ext::random_element());
     let prover = FriProver::new(
         variable_num,
         &interpolate_cosets,
         &vector_interpolation_coset,
         polynomial,
-        &random_oracle,
-    );
+        parameters,
+    )
+    .unwrap();
     let commit = prover.commit_first_polynomial();
-    let mut verifier = FriVerifier::new(
+    let open_point = (0..variable_num)
+        .map(|_| Mersenne61Ext::random_element())
+        .collect();
+    let verifier = FriVerifier::new(
         variable_num,
         &interpolate_cosets,
         &vector_interpolation_coset,
         commit,
-        &random_oracle,
+        &open_point,
+        parameters,
     );
-    let open_point = verifier.get_open_point();
     criterion.bench_function(&format!("virgo prove {}", variable_num), |b| {
         b.iter_batched(
             || (prover.clone(), verifier.clone()),
@@ -96,39 +98,45 @@ fn bench_open(c: &mut Criterion) {
 }
 
 fn verify(criterion: &mut Criterion, variable_num: usize) {
+    let parameters = FriParameters::default();
     let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
     let mut interpolate_cosets = vec![Coset::new(
-        1 << (variable_num + CODE_RATE),
+        1 << (variable_num + parameters.log_blowup),
         Mersenne61Ext::random_element(),
     )];
     for i in 1..variable_num {
         interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
     }
-    let random_oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
     let vector_interpolation_coset = Coset::new(1 << variable_num, Mersenne61Ext::random_element());
     let mut prover = FriProver::new(
         variable_num,
         &interpolate_cosets,
         &vector_interpolation_coset,
         polynomial,
-        &random_oracle,
-    );
+        parameters,
+    )
+    .unwrap();
     let commit = prover.commit_first_polynomial();
+    let open_point = (0..variable_num)
+        .map(|_| Mersenne61Ext::random_element())
+        .collect();
     let mut verifier = FriVerifier::new(
         variable_num,
         &interpolate_cosets,
         &vector_interpolation_coset,
         commit,
-        &random_oracle,
+        &open_point,
+        parameters,
     );
-    let open_point = verifier.get_open_point();
     prover.commit_functions(&mut verifier, &open_point);
     prover.prove();
     prover.commit_foldings(&mut verifier);
     let (folding_proofs, function_proofs, v_value) = prover.query();
     criterion.bench_function(&format!("virgo verify {}", variable_num), |b| {
         b.iter(|| {
-            assert!(verifier.verify(&folding_proofs, &v_value, &function_proofs));
+            assert!(verifier
+                .verify(&folding_proofs, &v_value, &function_proofs)
+                .is_ok());
         })
     });
 }
@@ -1,30 +1,34 @@
+pub mod proof;
 pub mod prover;
+pub mod scheme;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
-    use crate::{prover::FriProver, verifier::FriVerifier};
+    use crate::{proof::VirgoParams, prover::FriProver, scheme::Virgo, verifier::FriVerifier};
     use std::mem::size_of;
     use util::{
         algebra::{
-            coset::Coset, field::mersenne61_ext::Mersenne61Ext, field::Field,
+            coset::Coset, field::fp64::Fp64, field::mersenne61_ext::Mersenne61Ext, field::Field,
             polynomial::MultilinearPolynomial,
         },
-        merkle_tree::MERKLE_ROOT_SIZE,
-        random_oracle::RandomOracle,
+        error::{ParameterError, VerifyError},
+        merkle_tree::{Keccak256Algorithm, MerkleHasher, Sha256Algorithm, MERKLE_ROOT_SIZE},
+        parameters::FriParameters,
+        pcs::PolynomialCommitmentScheme,
+        poseidon::PoseidonAlgorithm,
     };
 
-    use util::{CODE_RATE, SECURITY_BITS};
     fn output_proof_size(variable_num: usize) -> usize {
+        let parameters = FriParameters::default();
         let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
         let mut interpolate_cosets = vec![Coset::new(
-            1 << (variable_num + CODE_RATE),
+            1 << (variable_num + parameters.log_blowup),
             Mersenne61Ext::random_element(),
         )];
         for i in 1..variable_num {
             interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
         }
-        let random_oracle = RandomOracle::new(variable_num, SECURITY_BITS / CODE_RATE);
         let vector_interpolation_coset =
             Coset::new(1 << variable_num, Mersenne61Ext::random_element());
         let mut prover = FriProver::new(
@@ -32,22 +36,28 @@ mod tests {
             &interpolate_cosets,
             &vector_interpolation_coset,
             polynomial,
-            &random_oracle,
-        );
+            parameters,
+        )
+        .unwrap();
         let commit = prover.commit_first_polynomial();
+        let open_point = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
         let mut verifier = FriVerifier::new(
             variable_num,
             &interpolate_cosets,
             &vector_interpolation_coset,
             commit,
-            &random_oracle,
+            &open_point,
+            parameters,
         );
-        let open_point = verifier.get_open_point();
         prover.commit_functions(&mut verifier, &open_point);
         prover.prove();
         prover.commit_foldings(&mut verifier);
         let (folding_proofs, function_proofs, v_value) = prover.query();
-        assert!(verifier.verify(&folding_proofs, &v_value, &function_proofs));
+        assert!(verifier
+            .verify(&folding_proofs, &v_value, &function_proofs)
+            .is_ok());
         println!(
             "The first layer proof size is {} bytes",
             function_proofs[0].proof_size()
@@ -71,4 +81,43 @@ mod tests {
             );
         }
     }
+
+    fn commitment_scheme<H: MerkleHasher>(parameters: FriParameters<H>) {
+        let variable_num = 8;
+        let params: VirgoParams<Mersenne61Ext, H> = Virgo::setup(variable_num, parameters).unwrap();
+        let polynomial = MultilinearPolynomial::random_polynomial(variable_num);
+        let (commitment, prover_data) = Virgo::commit(&params, polynomial.clone());
+        let point: Vec<Mersenne61Ext> = (0..variable_num)
+            .map(|_| Mersenne61Ext::random_element())
+            .collect();
+        let (value, proof) = Virgo::open(&params, prover_data, &point).unwrap();
+        assert_eq!(value, polynomial.evaluate(&point));
+        assert!(Virgo::verify(&params, &commitment, &point, &value, &proof).is_ok());
+        // A caller that expects another value rejects the proof
+        let wrong_value = value + Mersenne61Ext::from_int(1);
+        assert_eq!(
+            Virgo::verify(&params, &commitment, &point, &wrong_value, &proof),
+            Err(VerifyError::ClaimedValue)
+        );
+    }
+
+    #[test]
+    fn test_commitment_scheme() {
+        commitment_scheme(FriParameters::default());
+    }
+
+    #[test]
+    fn test_hiding_rejected() {
+        let parameters = FriParameters::default().with_hiding();
+        let params: Result<VirgoParams<Mersenne61Ext>, _> = Virgo::setup(8, parameters);
+        assert_eq!(params.err(), Some(ParameterError::HidingUnsupported));
+    }
+
+    #[test]
+    fn test_merkle_hashers() {
+        let parameters = FriParameters::default();
+        commitment_scheme(parameters.with_hasher::<Sha256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<Keccak256Algorithm>());
+        commitment_scheme(parameters.with_hasher::<PoseidonAlgorithm<Fp64>>());
+    }
 }
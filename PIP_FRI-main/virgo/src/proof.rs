@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use util::{
+    algebra::{coset::Coset, field::Field},
+    error::ParameterError,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    query_result::QueryResult,
+};
+
+#[derive(Clone)]
+pub struct VirgoParams<T: Field, H: MerkleHasher = Blake3Algorithm> {
+    pub variable_num: usize,
+    pub interpolate_cosets: Vec<Coset<T>>,
+    pub vector_interpolation_coset: Coset<T>,
+    // Private, so that the parameters stay the checked ones
+    parameters: FriParameters<H>,
+}
+
+impl<T: Field, H: MerkleHasher> VirgoParams<T, H> {
+    pub fn new(
+        variable_num: usize,
+        shift: T,
+        vector_shift: T,
+        parameters: FriParameters<H>,
+    ) -> Result<Self, ParameterError> {
+        // Nothing in a virgo opening is masked
+        parameters.reject_hiding()?;
+        let mut interpolate_cosets = vec![Coset::new(
+            1 << (variable_num + parameters.log_blowup),
+            shift,
+        )];
+        for i in 1..variable_num {
+            interpolate_cosets.push(interpolate_cosets[i - 1].pow(2));
+        }
+        Ok(VirgoParams {
+            variable_num,
+            interpolate_cosets,
+            vector_interpolation_coset: Coset::new(1 << variable_num, vector_shift),
+            parameters,
+        })
+    }
+
+    pub fn parameters(&self) -> FriParameters<H> {
+        self.parameters
+    }
+}
+
+// Everything the prover sends in a virgo opening, in transcript order
+#[derive(Clone)]
+pub struct VirgoProof<T: Field> {
+    pub h_root: [u8; MERKLE_ROOT_SIZE],
+    pub evaluation: T,
+    pub folding_roots: Vec<[u8; MERKLE_ROOT_SIZE]>,
+    pub final_value: T,
+    pub pow_nonce: u64,
+    pub folding_proofs: Vec<QueryResult<T>>,
+    pub function_proofs: Vec<QueryResult<T>>,
+    pub v_values: HashMap<usize, T>,
+}
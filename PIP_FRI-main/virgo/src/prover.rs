@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 
+use super::proof::VirgoProof;
 use super::verifier::FriVerifier;
 use util::{
     algebra::polynomial::{MultilinearPolynomial, Polynomial, VanishingPolynomial},
-    merkle_tree::MERKLE_ROOT_SIZE,
-    random_oracle::RandomOracle,
+    error::ParameterError,
+    merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    transcript::Transcript,
 };
 
 use util::query_result::QueryResult;
@@ -16,13 +19,16 @@ use util::{
     merkle_tree::MerkleTreeProver,
 };
 
+// The query results of the foldings and the functions, with the values of v at the queries
+type QueryResults<T> = (Vec<QueryResult<T>>, Vec<QueryResult<T>>, HashMap<usize, T>);
+
 #[derive(Clone)]
-struct InterpolateValue<T: Field> {
+struct InterpolateValue<T: Field, H: MerkleHasher> {
     value: Vec<T>,
-    merkle_tree: MerkleTreeProver,
+    merkle_tree: MerkleTreeProver<H>,
 }
 
-impl<T: Field> InterpolateValue<T> {
+impl<T: Field, H: MerkleHasher> InterpolateValue<T, H> {
     fn new(value: Vec<T>) -> Self {
         let len = value.len() / 2;
         let merkle_tree = MerkleTreeProver::new(
@@ -41,62 +47,86 @@ impl<T: Field> InterpolateValue<T> {
         self.merkle_tree.commit()
     }
 
-    fn query(&self, leaf_indices: &Vec<usize>) -> QueryResult<T> {
-        let len = self.merkle_tree.leave_num();
-        let proof_values = leaf_indices
-            .iter()
-            .flat_map(|j| [(*j, self.value[*j]), (*j + len, self.value[*j + len])])
-            .collect();
-        let proof_bytes = self.merkle_tree.open(&leaf_indices);
-        QueryResult {
-            proof_bytes,
-            proof_values,
-        }
+    fn query(&self, leaf_indices: &[usize]) -> QueryResult<T> {
+        QueryResult::new(
+            leaf_indices,
+            self.merkle_tree.leave_num(),
+            2,
+            |i| self.value[i],
+            self.merkle_tree.open(leaf_indices),
+            vec![],
+        )
     }
 }
 
 #[derive(Clone)]
-pub struct FriProver<T: Field> {
+pub struct FriProver<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     vector_interpolation_coset: Coset<T>,
     fri_cosets: Vec<Coset<T>>,
-    function_h: Option<InterpolateValue<T>>,
-    function_u: InterpolateValue<T>,
+    function_h: Option<InterpolateValue<T, H>>,
+    function_u: InterpolateValue<T, H>,
     interpolation_v: Option<Vec<T>>,
     poly_u: Polynomial<T>,
     polynomial: MultilinearPolynomial<T>,
-    foldings: Vec<InterpolateValue<T>>,
-    oracle: RandomOracle<T>,
+    foldings: Vec<InterpolateValue<T, H>>,
+    transcript: Transcript,
+    parameters: FriParameters<H>,
     evaluation: Option<T>,
     final_value: Option<T>,
+    pow_nonce: Option<u64>,
 }
 
-impl<T: Field> FriProver<T> {
+impl<T: Field, H: MerkleHasher> FriProver<T, H> {
+    // Fails on hiding parameters, which the scheme does not support
     pub fn new(
         total_round: usize,
         fri_cosets: &Vec<Coset<T>>,
         vector_interpolation_coset: &Coset<T>,
         polynomial: MultilinearPolynomial<T>,
-        oracle: &RandomOracle<T>,
-    ) -> FriProver<T> {
+        parameters: FriParameters<H>,
+    ) -> Result<FriProver<T, H>, ParameterError> {
+        parameters.reject_hiding()?;
+        Ok(Self::with_parameters(
+            total_round,
+            fri_cosets,
+            vector_interpolation_coset,
+            polynomial,
+            parameters,
+        ))
+    }
+
+    // `new` for parameters already checked not to hide
+    pub(crate) fn with_parameters(
+        total_round: usize,
+        fri_cosets: &[Coset<T>],
+        vector_interpolation_coset: &Coset<T>,
+        polynomial: MultilinearPolynomial<T>,
+        parameters: FriParameters<H>,
+    ) -> FriProver<T, H> {
         assert_eq!(
             vector_interpolation_coset.size(),
             1 << polynomial.variable_num()
         );
         let interpolation = vector_interpolation_coset.ifft(polynomial.coefficients().clone());
+        let function_u = InterpolateValue::new(fri_cosets[0].fft(interpolation.clone()));
+        let mut transcript = Transcript::new(b"virgo");
+        transcript.append_root(&function_u.commit());
         FriProver {
             total_round,
             vector_interpolation_coset: vector_interpolation_coset.clone(),
-            fri_cosets: fri_cosets.clone(),
+            fri_cosets: fri_cosets.to_vec(),
             function_h: None,
-            function_u: InterpolateValue::new(fri_cosets[0].fft(interpolation.clone())),
+            function_u,
             interpolation_v: None,
             poly_u: Polynomial::new(interpolation),
             polynomial,
             foldings: vec![],
-            oracle: oracle.clone(),
+            transcript,
+            parameters,
             evaluation: None,
             final_value: None,
+            pow_nonce: None,
         }
     }
 
@@ -104,8 +134,15 @@ impl<T: Field> FriProver<T> {
         self.function_u.commit()
     }
 
-    pub fn commit_functions(&mut self, verifier: &mut FriVerifier<T>, open_point: &Vec<T>) {
+    pub fn commit_functions(&mut self, verifier: &mut FriVerifier<T, H>, open_point: &Vec<T>) {
+        self.compute_functions(open_point);
+        verifier.set_h_root(self.function_h.as_ref().unwrap().commit());
+        verifier.set_evaluation(self.evaluation.unwrap());
+    }
+
+    fn compute_functions(&mut self, open_point: &Vec<T>) {
         assert_eq!(open_point.len(), self.total_round);
+        self.transcript.append_fields(open_point);
         let mut public_vector = vec![T::from_int(1)];
         for i in open_point {
             let len = public_vector.len();
@@ -119,23 +156,40 @@ impl<T: Field> FriProver<T> {
             .over_vanish_polynomial(&VanishingPolynomial::new(&self.vector_interpolation_coset));
         assert!(h.degree() < self.vector_interpolation_coset.size());
         let function_h = InterpolateValue::new(self.fri_cosets[0].fft(h.coefficients().clone()));
-        verifier.set_h_root(function_h.commit());
+        self.transcript.append_root(&function_h.commit());
         self.function_h = Some(function_h);
         self.interpolation_v = Some(self.fri_cosets[0].fft(poly_v.coefficients().clone()));
         let evaluation = self.polynomial.evaluate(open_point);
         self.evaluation = Some(evaluation);
-        verifier.set_evaluation(evaluation);
+        self.transcript.append_field(&evaluation);
+    }
+
+    // Runs the whole opening non-interactively and packs every prover message into a proof
+    pub fn open(&mut self, open_point: &Vec<T>) -> VirgoProof<T> {
+        self.compute_functions(open_point);
+        self.prove();
+        let (folding_proofs, function_proofs, v_values) = self.query();
+        VirgoProof {
+            h_root: self.function_h.as_ref().unwrap().commit(),
+            evaluation: self.evaluation.unwrap(),
+            folding_roots: self.foldings.iter().map(|x| x.commit()).collect(),
+            final_value: self.final_value.unwrap(),
+            pow_nonce: self.pow_nonce.unwrap(),
+            folding_proofs,
+            function_proofs,
+            v_values,
+        }
     }
 
-    pub fn commit_foldings(&self, verifier: &mut FriVerifier<T>) {
+    pub fn commit_foldings(&self, verifier: &mut FriVerifier<T, H>) {
         for i in 0..(self.total_round - 1) {
             verifier.receive_folding_root(self.foldings[i].leave_num(), self.foldings[i].commit());
         }
-        verifier.set_final_value(self.final_value.unwrap())
+        verifier.set_final_value(self.final_value.unwrap());
+        verifier.set_pow_nonce(self.pow_nonce.unwrap());
     }
 
-    fn initial_interpolation(&self) -> Vec<T> {
-        let rlc = self.oracle.rlc;
+    fn initial_interpolation(&self, rlc: T) -> Vec<T> {
         let u = &self.function_u.value;
         let mut res = u.clone();
         let h = &self.function_h.as_ref().unwrap().value;
@@ -159,12 +213,12 @@ impl<T: Field> FriProver<T> {
         res
     }
 
-    fn evaluation_next_domain(&self, round: usize, challenge: T) -> Vec<T> {
+    fn evaluation_next_domain(&self, round: usize, challenge: T, rlc: T) -> Vec<T> {
         let mut res = vec![];
         let coset = &self.fri_cosets[round];
         let len = coset.size();
         if round == 0 {
-            let function = self.initial_interpolation();
+            let function = self.initial_interpolation(rlc);
             for i in 0..(len / 2) {
                 let x = function[i];
                 let nx = function[i + len / 2];
@@ -184,11 +238,13 @@ impl<T: Field> FriProver<T> {
     }
 
     pub fn prove(&mut self) {
+        let rlc = self.transcript.challenge_field();
         for i in 0..self.total_round {
-            let challenge = self.oracle.folding_challenges[i];
-            let next_evalutation = self.evaluation_next_domain(i, challenge);
+            let challenge = self.transcript.challenge_field();
+            let next_evalutation = self.evaluation_next_domain(i, challenge, rlc);
             if i < self.total_round - 1 {
                 let interpolate_value = InterpolateValue::new(next_evalutation);
+                self.transcript.append_root(&interpolate_value.commit());
                 self.foldings.push(interpolate_value);
             } else {
                 let x = next_evalutation[0];
@@ -196,14 +252,16 @@ impl<T: Field> FriProver<T> {
                     assert_eq!(x, *i);
                 }
                 self.final_value = Some(next_evalutation[0]);
+                self.transcript.append_field(&next_evalutation[0]);
+                self.pow_nonce = Some(self.transcript.grind(self.parameters.grinding_bits));
             }
         }
     }
 
-    pub fn query(&self) -> (Vec<QueryResult<T>>, Vec<QueryResult<T>>, HashMap<usize, T>) {
+    pub fn query(&mut self) -> QueryResults<T> {
         let mut folding_res = vec![];
         let mut functions_res = None;
-        let mut leaf_indices = self.oracle.query_list.clone();
+        let mut leaf_indices = self.transcript.challenge_indices(self.parameters.query_num);
         let mut v_value = None;
 
         for i in 0..self.total_round {
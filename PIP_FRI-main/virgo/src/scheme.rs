@@ -0,0 +1,102 @@
+use std::io;
+
+use rand::RngCore;
+use util::{
+    algebra::{field::Field, polynomial::MultilinearPolynomial},
+    error::{ParameterError, VerifyError},
+    merkle_tree::{MerkleHasher, MERKLE_ROOT_SIZE},
+    parameters::FriParameters,
+    pcs::PolynomialCommitmentScheme,
+};
+
+use crate::{
+    proof::{VirgoParams, VirgoProof},
+    prover::FriProver,
+    verifier::FriVerifier,
+};
+
+pub struct Virgo;
+
+impl<T: Field, H: MerkleHasher> PolynomialCommitmentScheme<T, H> for Virgo {
+    type Params = VirgoParams<T, H>;
+    type Polynomial = MultilinearPolynomial<T>;
+    type Point = Vec<T>;
+    type Commitment = [u8; MERKLE_ROOT_SIZE];
+    type Evaluation = T;
+    type ProverData = FriProver<T, H>;
+    type Proof = VirgoProof<T>;
+
+    fn setup_with<R: RngCore>(
+        variable_num: usize,
+        parameters: FriParameters<H>,
+        rng: &mut R,
+    ) -> Result<Self::Params, ParameterError> {
+        VirgoParams::new(
+            variable_num,
+            T::random_element_with(rng),
+            T::random_element_with(rng),
+            parameters,
+        )
+    }
+
+    fn commit(
+        params: &Self::Params,
+        polynomial: Self::Polynomial,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let prover = FriProver::with_parameters(
+            params.variable_num,
+            &params.interpolate_cosets,
+            &params.vector_interpolation_coset,
+            polynomial,
+            params.parameters(),
+        );
+        (prover.commit_first_polynomial(), prover)
+    }
+
+    fn open(
+        _params: &Self::Params,
+        mut prover_data: Self::ProverData,
+        point: &Self::Point,
+    ) -> io::Result<(Self::Evaluation, Self::Proof)> {
+        let proof = prover_data.open(point);
+        Ok((proof.evaluation, proof))
+    }
+
+    fn verify(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifyError> {
+        if proof.evaluation != *value {
+            return Err(VerifyError::ClaimedValue);
+        }
+        if params.variable_num == 0
+            || point.len() != params.variable_num
+            || proof.folding_roots.len() != params.variable_num - 1
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+        let mut verifier = FriVerifier::new(
+            params.variable_num,
+            &params.interpolate_cosets,
+            &params.vector_interpolation_coset,
+            *commitment,
+            point,
+            params.parameters(),
+        );
+        verifier.set_h_root(proof.h_root);
+        verifier.set_evaluation(proof.evaluation);
+        for (i, root) in proof.folding_roots.iter().enumerate() {
+            verifier.receive_folding_root(params.interpolate_cosets[i + 1].size() / 2, *root);
+        }
+        verifier.set_final_value(proof.final_value);
+        verifier.set_pow_nonce(proof.pow_nonce);
+        verifier.verify(
+            &proof.folding_proofs,
+            &proof.v_values,
+            &proof.function_proofs,
+        )
+    }
+}
@@ -1,51 +1,58 @@
 use std::collections::HashMap;
 
 use util::algebra::polynomial::VanishingPolynomial;
-use util::merkle_tree::MERKLE_ROOT_SIZE;
+use util::error::VerifyError;
+use util::merkle_tree::{Blake3Algorithm, MerkleHasher, MERKLE_ROOT_SIZE};
 use util::query_result::QueryResult;
-use util::random_oracle::RandomOracle;
+use util::transcript::Transcript;
 use util::{
     algebra::{coset::Coset, field::Field},
     merkle_tree::MerkleTreeVerifier,
+    parameters::FriParameters,
 };
 
 #[derive(Clone)]
-pub struct FriVerifier<T: Field> {
+pub struct FriVerifier<T: Field, H: MerkleHasher = Blake3Algorithm> {
     total_round: usize,
     interpolate_cosets: Vec<Coset<T>>,
     vector_interpolation_coset: Coset<T>,
-    u_root: MerkleTreeVerifier,
-    h_root: Option<MerkleTreeVerifier>,
-    folding_root: Vec<MerkleTreeVerifier>,
-    oracle: RandomOracle<T>,
+    u_root: MerkleTreeVerifier<H>,
+    h_root: Option<MerkleTreeVerifier<H>>,
+    folding_root: Vec<MerkleTreeVerifier<H>>,
+    parameters: FriParameters<H>,
     vanishing_polynomial: VanishingPolynomial<T>,
     final_value: Option<T>,
+    pow_nonce: Option<u64>,
     evaluation: Option<T>,
-    open_point: Option<Vec<T>>,
+    open_point: Vec<T>,
 }
 
-impl<T: Field> FriVerifier<T> {
+impl<T: Field, H: MerkleHasher> FriVerifier<T, H> {
     pub fn new(
         total_round: usize,
         coset: &Vec<Coset<T>>,
         vector_interpolation_coset: &Coset<T>,
         polynomial_commitment: [u8; MERKLE_ROOT_SIZE],
-        oracle: &RandomOracle<T>,
+        open_point: &Vec<T>,
+        parameters: FriParameters<H>,
     ) -> Self {
+        // The query count of DEEP parameters is only sound with the out-of-domain sample
+        assert!(
+            !parameters.deep(),
+            "DEEP-FRI is not supported by this scheme"
+        );
         FriVerifier {
             total_round,
             interpolate_cosets: coset.clone(),
             vector_interpolation_coset: vector_interpolation_coset.clone(),
-            u_root: MerkleTreeVerifier {
-                leave_number: coset[0].size() / 2,
-                merkle_root: polynomial_commitment,
-            },
+            u_root: MerkleTreeVerifier::new(coset[0].size() / 2, &polynomial_commitment),
             h_root: None,
             folding_root: vec![],
-            oracle: oracle.clone(),
+            parameters,
             vanishing_polynomial: VanishingPolynomial::new(vector_interpolation_coset),
             final_value: None,
-            open_point: None,
+            pow_nonce: None,
+            open_point: open_point.clone(),
             evaluation: None,
         }
     }
@@ -54,19 +61,11 @@ impl<T: Field> FriVerifier<T> {
         self.evaluation = Some(v);
     }
 
-    pub fn get_open_point(&mut self) -> Vec<T> {
-        let point = (0..self.total_round)
-            .map(|_| T::random_element())
-            .collect::<Vec<T>>();
-        self.open_point = Some(point.clone());
-        point
-    }
-
     pub fn set_h_root(&mut self, h_root: [u8; MERKLE_ROOT_SIZE]) {
-        self.h_root = Some(MerkleTreeVerifier {
-            merkle_root: h_root,
-            leave_number: self.interpolate_cosets[0].size() / 2,
-        });
+        self.h_root = Some(MerkleTreeVerifier::new(
+            self.interpolate_cosets[0].size() / 2,
+            &h_root,
+        ));
     }
 
     pub fn receive_folding_root(
@@ -74,26 +73,61 @@ impl<T: Field> FriVerifier<T> {
         leave_number: usize,
         folding_root: [u8; MERKLE_ROOT_SIZE],
     ) {
-        self.folding_root.push(MerkleTreeVerifier {
-            leave_number,
-            merkle_root: folding_root,
-        });
+        self.folding_root
+            .push(MerkleTreeVerifier::new(leave_number, &folding_root));
     }
 
     pub fn set_final_value(&mut self, value: T) {
-        assert_ne!(value, T::from_int(0));
         self.final_value = Some(value);
     }
 
+    pub fn set_pow_nonce(&mut self, nonce: u64) {
+        self.pow_nonce = Some(nonce);
+    }
+
     pub fn verify(
         &self,
         folding_proofs: &Vec<QueryResult<T>>,
         v_values: &HashMap<usize, T>,
         function_proofs: &Vec<QueryResult<T>>,
-    ) -> bool {
-        let mut leaf_indices = self.oracle.query_list.clone();
-        let rlc = self.oracle.rlc;
+    ) -> Result<(), VerifyError> {
+        let (h_root, evaluation, final_value) =
+            match (self.h_root.as_ref(), self.evaluation, self.final_value) {
+                (Some(h_root), Some(evaluation), Some(final_value)) => {
+                    (h_root, evaluation, final_value)
+                }
+                _ => return Err(VerifyError::MalformedProof),
+            };
+        if folding_proofs.len() != self.total_round - 1
+            || self.folding_root.len() != self.total_round - 1
+            || function_proofs.len() != 2
+        {
+            return Err(VerifyError::MalformedProof);
+        }
+
+        // Replay the prover's transcript to derive every challenge
+        let mut transcript = Transcript::new(b"virgo");
+        transcript.append_root(&self.u_root.merkle_root);
+        transcript.append_fields(&self.open_point);
+        transcript.append_root(&h_root.merkle_root);
+        transcript.append_field(&evaluation);
+        let rlc: T = transcript.challenge_field();
+        let mut folding_challenges: Vec<T> = vec![];
+        for i in 0..self.total_round {
+            folding_challenges.push(transcript.challenge_field());
+            if i < self.total_round - 1 {
+                transcript.append_root(&self.folding_root[i].merkle_root);
+            }
+        }
+        transcript.append_field(&final_value);
+        let pow_nonce = self.pow_nonce.ok_or(VerifyError::MalformedProof)?;
+        if !transcript.check_grinding(self.parameters.grinding_bits, pow_nonce) {
+            return Err(VerifyError::ProofOfWork);
+        }
+        let mut leaf_indices = transcript.challenge_indices(self.parameters.query_num);
         let h_size = T::from_int(self.vector_interpolation_coset.size() as u64);
+        // The leaves queried in every round, which place the opened values in the codewords
+        let mut round_indices = vec![];
         for i in 0..self.total_round {
             let domain_size = self.interpolate_cosets[i].size();
             leaf_indices = leaf_indices
@@ -102,23 +136,40 @@ impl<T: Field> FriVerifier<T> {
                 .collect();
             leaf_indices.sort();
             leaf_indices.dedup();
-
-            if i == 0 {
-                assert!(function_proofs[0].verify_merkle_tree(&leaf_indices, &self.u_root));
-                assert!(function_proofs[1]
-                    .verify_merkle_tree(&leaf_indices, &self.h_root.as_ref().unwrap()));
+            round_indices.push(leaf_indices.clone());
+        }
+        let u_values =
+            function_proofs[0].open_values(&round_indices[0], self.u_root.leave_number)?;
+        let h_values = function_proofs[1].open_values(&round_indices[0], h_root.leave_number)?;
+        let folding_values = folding_proofs
+            .iter()
+            .zip(round_indices[1..].iter())
+            .zip(self.folding_root.iter())
+            .map(|((x, indices), root)| x.open_values(indices, root.leave_number))
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        for i in 0..self.total_round {
+            let domain_size = self.interpolate_cosets[i].size();
+            let leaf_indices = &round_indices[i];
+            let merkle_ok = if i == 0 {
+                function_proofs[0].verify_merkle_tree(leaf_indices, &self.u_root)
+                    && function_proofs[1].verify_merkle_tree(leaf_indices, h_root)
             } else {
-                folding_proofs[i - 1].verify_merkle_tree(&leaf_indices, &self.folding_root[i - 1]);
+                folding_proofs[i - 1].verify_merkle_tree(leaf_indices, &self.folding_root[i - 1])
+            };
+            if !merkle_ok {
+                return Err(VerifyError::MerklePath { round: i });
             }
 
-            let challenge = self.oracle.folding_challenges[i];
-            let get_folding_value = |index: &usize| {
+            let challenge = folding_challenges[i];
+            let get_folding_value = |index: usize| {
                 if i == 0 {
-                    let u = function_proofs[0].proof_values[index];
-                    let h = function_proofs[1].proof_values[index];
-                    let v = v_values[index];
-                    let x = self.interpolate_cosets[i].element_at(*index);
-                    let x_inv = self.interpolate_cosets[i].element_inv_at(*index);
+                    let u = u_values.get_value(i, index)?;
+                    let h = h_values.get_value(i, index)?;
+                    let v = *v_values
+                        .get(&index)
+                        .ok_or(VerifyError::MissingValue { round: i, index })?;
+                    let x = self.interpolate_cosets[i].element_at(index);
+                    let x_inv = self.interpolate_cosets[i].element_inv_at(index);
 
                     let mut res = u;
                     let mut acc = rlc;
@@ -127,32 +178,29 @@ impl<T: Field> FriVerifier<T> {
                     res += acc
                         * (u * v * h_size
                             - self.vanishing_polynomial.evaluation_at(x) * h * h_size
-                            - self.evaluation.unwrap())
+                            - evaluation)
                         * x_inv;
-                    res
+                    Ok(res)
                 } else {
-                    folding_proofs[i - 1].proof_values[index]
+                    folding_values[i - 1].get_value(i, index)
                 }
             };
 
-            for j in &leaf_indices {
-                let x = get_folding_value(j);
-                let nx = get_folding_value(&(j + domain_size / 2));
+            for j in leaf_indices {
+                let j = *j;
+                let x = get_folding_value(j)?;
+                let nx = get_folding_value(j + domain_size / 2)?;
                 let v =
-                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(*j);
+                    x + nx + challenge * (x - nx) * self.interpolate_cosets[i].element_inv_at(j);
                 if i < self.total_round - 1 {
-                    if v != folding_proofs[i].proof_values[j] {
-                        panic!("{}", i);
-                        return false;
-                    }
-                } else {
-                    if v != self.final_value.unwrap() {
-                        panic!();
-                        return false;
+                    if v != folding_values[i].get_value(i + 1, j)? {
+                        return Err(VerifyError::FoldConsistency { round: i, index: j });
                     }
+                } else if v != final_value {
+                    return Err(VerifyError::FinalValue { index: j });
                 }
             }
         }
-        true
+        Ok(())
     }
 }
@@ -29,6 +29,7 @@ use ark_dh_commitments::{
 };
 use ark_inner_products::MultiexponentiationInnerProduct;
 
+pub mod pip;
 pub mod transparent;
 
 type PolynomialEvaluationSecondTierIPA<P, D> = TIPAWithSSM<
@@ -0,0 +1,276 @@
+use ark_ec::{pairing::Pairing, scalar_mul::variable_base::VariableBaseMSM, CurveGroup};
+use ark_ff::{Field, One, PrimeField};
+use ark_poly::polynomial::{
+    univariate::DensePolynomial as UnivariatePolynomial, DenseUVPolynomial, Polynomial,
+};
+use ark_serialize::CanonicalSerialize;
+
+use ark_std::{end_timer, start_timer};
+use std::marker::PhantomData;
+
+use ark_std::rand::Rng;
+use digest::Digest;
+
+use super::KZG;
+use crate::{tipa::VerifierSRS, Error, InnerProductArgumentError};
+
+#[derive(Clone)]
+pub struct PipSRS<P: Pairing> {
+    pub g_alpha_powers: Vec<P::G1Affine>,
+    pub v_srs: VerifierSRS<P>,
+    pub poly_num: usize,
+    pub chunk_degree: usize,
+}
+
+#[derive(Clone)]
+pub struct PipVerifierSRS<P: Pairing> {
+    /// Only the first `poly_num` powers are needed to recommit to the hashes
+    pub hash_powers: Vec<P::G1Affine>,
+    pub v_srs: VerifierSRS<P>,
+    pub poly_num: usize,
+    pub chunk_degree: usize,
+}
+
+impl<P: Pairing> PipSRS<P> {
+    pub fn get_verifier_key(&self) -> PipVerifierSRS<P> {
+        PipVerifierSRS {
+            hash_powers: self.g_alpha_powers[..self.poly_num].to_vec(),
+            v_srs: self.v_srs.clone(),
+            poly_num: self.poly_num,
+            chunk_degree: self.chunk_degree,
+        }
+    }
+}
+
+/// The chunk commitments and a single KZG proof for their combination. Its size on the wire
+/// is `compressed_size()`.
+#[derive(Clone, CanonicalSerialize)]
+pub struct OpeningProof<P: Pairing, D: Digest> {
+    chunk_comms: Vec<P::G1>,
+    kzg_proof: P::G1,
+    _digest: PhantomData<D>,
+}
+
+/// PIP over KZG: the polynomial is split into `poly_num` chunks of degree `chunk_degree`,
+/// every chunk is KZG-committed, and the final commitment is a KZG commitment to the
+/// polynomial whose coefficients are the hashes of the chunk commitments.
+pub struct PipKzg<P: Pairing, D: Digest> {
+    _pairing: PhantomData<P>,
+    _digest: PhantomData<D>,
+}
+
+impl<P: Pairing, D: Digest> PipKzg<P, D> {
+    pub fn setup<R: Rng>(rng: &mut R, degree: usize, poly_num: usize) -> Result<PipSRS<P>, Error> {
+        if poly_num == 0 || poly_num > degree + 1 {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                poly_num,
+                degree + 1,
+            )));
+        }
+        let chunk_degree = (degree + poly_num) / poly_num - 1;
+        let (g_alpha_powers, v_srs) =
+            KZG::<P>::setup(rng, std::cmp::max(chunk_degree, poly_num - 1))?;
+        Ok(PipSRS {
+            g_alpha_powers,
+            v_srs,
+            poly_num,
+            chunk_degree,
+        })
+    }
+
+    pub fn commit(
+        srs: &PipSRS<P>,
+        polynomial: &UnivariatePolynomial<P::ScalarField>,
+    ) -> Result<(P::G1, Vec<P::G1>), Error> {
+        let chunks = Self::split(srs.poly_num, srs.chunk_degree, polynomial)?;
+        let chunk_comms = chunks
+            .iter()
+            .map(|chunk| KZG::<P>::commit(&srs.g_alpha_powers, chunk))
+            .collect::<Result<Vec<P::G1>, Error>>()?;
+        let hash_polynomial = Self::hash_polynomial(&chunk_comms)?;
+        let com = KZG::<P>::commit(&srs.g_alpha_powers, &hash_polynomial)?;
+        Ok((com, chunk_comms))
+    }
+
+    pub fn open(
+        srs: &PipSRS<P>,
+        polynomial: &UnivariatePolynomial<P::ScalarField>,
+        chunk_comms: &[P::G1],
+        point: &P::ScalarField,
+    ) -> Result<OpeningProof<P, D>, Error> {
+        let chunks = Self::split(srs.poly_num, srs.chunk_degree, polynomial)?;
+        if chunk_comms.len() != srs.poly_num {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                chunk_comms.len(),
+                srs.poly_num,
+            )));
+        }
+
+        // f(z) = sum_j z^{j * (chunk_degree + 1)} f_j(z), so open the same combination of chunks
+        let batch_time = start_timer!(|| "Batching chunk polynomials");
+        let shifts = Self::chunk_shifts(srs.poly_num, srs.chunk_degree, point);
+        let mut batch_polynomial = UnivariatePolynomial::from_coefficients_vec(vec![]);
+        for (chunk, shift) in chunks.iter().zip(&shifts) {
+            batch_polynomial += (*shift, chunk);
+        }
+        end_timer!(batch_time);
+
+        let kzg_time = start_timer!(|| "Computing KZG opening proof");
+        let kzg_proof = KZG::<P>::open(&srs.g_alpha_powers, &batch_polynomial, point)?;
+        end_timer!(kzg_time);
+
+        Ok(OpeningProof {
+            chunk_comms: chunk_comms.to_vec(),
+            kzg_proof,
+            _digest: PhantomData,
+        })
+    }
+
+    pub fn verify(
+        v_srs: &PipVerifierSRS<P>,
+        com: &P::G1,
+        point: &P::ScalarField,
+        eval: &P::ScalarField,
+        proof: &OpeningProof<P, D>,
+    ) -> Result<bool, Error> {
+        if proof.chunk_comms.len() != v_srs.poly_num {
+            return Ok(false);
+        }
+        let hash_polynomial = Self::hash_polynomial(&proof.chunk_comms)?;
+        if KZG::<P>::commit(&v_srs.hash_powers, &hash_polynomial)? != *com {
+            return Ok(false);
+        }
+
+        let shifts = Self::chunk_shifts(v_srs.poly_num, v_srs.chunk_degree, point);
+        // Can unwrap because shifts.len() is guaranteed to be equal to chunk_comms.len()
+        let batch_com = P::G1::msm(&P::G1::normalize_batch(&proof.chunk_comms), &shifts).unwrap();
+        KZG::<P>::verify(&v_srs.v_srs, &batch_com, point, eval, &proof.kzg_proof)
+    }
+
+    fn split(
+        poly_num: usize,
+        chunk_degree: usize,
+        polynomial: &UnivariatePolynomial<P::ScalarField>,
+    ) -> Result<Vec<UnivariatePolynomial<P::ScalarField>>, Error> {
+        let chunk_len = chunk_degree + 1;
+        if polynomial.degree() + 1 > poly_num * chunk_len {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                polynomial.degree() + 1,
+                poly_num * chunk_len,
+            )));
+        }
+        let mut chunks = polynomial
+            .coeffs
+            .chunks(chunk_len)
+            .map(UnivariatePolynomial::from_coefficients_slice)
+            .collect::<Vec<_>>();
+        chunks.resize(
+            poly_num,
+            UnivariatePolynomial::from_coefficients_vec(vec![]),
+        );
+        Ok(chunks)
+    }
+
+    fn hash_polynomial(
+        chunk_comms: &[P::G1],
+    ) -> Result<UnivariatePolynomial<P::ScalarField>, Error> {
+        let hashes = chunk_comms
+            .iter()
+            .map(|com| {
+                let mut bytes = vec![];
+                com.serialize_compressed(&mut bytes)?;
+                let mut hasher = D::new();
+                hasher.update(&bytes);
+                Ok(P::ScalarField::from_le_bytes_mod_order(&hasher.finalize()))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(UnivariatePolynomial::from_coefficients_vec(hashes))
+    }
+
+    fn chunk_shifts(
+        poly_num: usize,
+        chunk_degree: usize,
+        point: &P::ScalarField,
+    ) -> Vec<P::ScalarField> {
+        let step = point.pow([(chunk_degree + 1) as u64]);
+        let mut shifts = vec![];
+        let mut cur = P::ScalarField::one();
+        for _ in 0..poly_num {
+            shifts.push(cur);
+            cur *= step;
+        }
+        shifts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use blake2::Blake2b;
+
+    const DEGREE: usize = 255;
+    const POLY_NUM: usize = 8;
+
+    type TestPipKzg = PipKzg<Bls12_381, Blake2b>;
+
+    #[test]
+    fn pip_kzg_test() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let srs = TestPipKzg::setup(&mut rng, DEGREE, POLY_NUM).unwrap();
+        let v_srs = srs.get_verifier_key();
+
+        let polynomial = UnivariatePolynomial::rand(DEGREE, &mut rng);
+        let (com, chunk_comms) = TestPipKzg::commit(&srs, &polynomial).unwrap();
+
+        let point = UniformRand::rand(&mut rng);
+        let proof = TestPipKzg::open(&srs, &polynomial, &chunk_comms, &point).unwrap();
+        let eval = polynomial.evaluate(&point);
+        assert!(TestPipKzg::verify(&v_srs, &com, &point, &eval, &proof).unwrap());
+
+        let wrong_eval = eval + <Bls12_381 as Pairing>::ScalarField::one();
+        assert!(!TestPipKzg::verify(&v_srs, &com, &point, &wrong_eval, &proof).unwrap());
+
+        // The chunk count, one compressed G1 element per chunk, and one for the KZG proof
+        let g1_size = srs.v_srs.g.compressed_size();
+        assert_eq!(proof.compressed_size(), 8 + (POLY_NUM + 1) * g1_size);
+    }
+
+    #[test]
+    fn pip_kzg_rejects_substituted_chunks() {
+        let mut rng = StdRng::seed_from_u64(1u64);
+        let srs = TestPipKzg::setup(&mut rng, DEGREE, POLY_NUM).unwrap();
+        let v_srs = srs.get_verifier_key();
+
+        let polynomial = UnivariatePolynomial::rand(DEGREE, &mut rng);
+        let other_polynomial = UnivariatePolynomial::rand(DEGREE, &mut rng);
+        let (com, _) = TestPipKzg::commit(&srs, &polynomial).unwrap();
+        let (_, other_chunk_comms) = TestPipKzg::commit(&srs, &other_polynomial).unwrap();
+
+        // A valid opening of another polynomial does not verify against `com`
+        let point = UniformRand::rand(&mut rng);
+        let proof = TestPipKzg::open(&srs, &other_polynomial, &other_chunk_comms, &point).unwrap();
+        let eval = other_polynomial.evaluate(&point);
+        assert!(!TestPipKzg::verify(&v_srs, &com, &point, &eval, &proof).unwrap());
+    }
+
+    #[test]
+    fn pip_kzg_uneven_chunks_test() {
+        let mut rng = StdRng::seed_from_u64(2u64);
+        let degree = 100;
+        let srs = TestPipKzg::setup(&mut rng, degree, 7).unwrap();
+        let v_srs = srs.get_verifier_key();
+
+        let polynomial = UnivariatePolynomial::rand(degree, &mut rng);
+        let (com, chunk_comms) = TestPipKzg::commit(&srs, &polynomial).unwrap();
+        let point = UniformRand::rand(&mut rng);
+        let proof = TestPipKzg::open(&srs, &polynomial, &chunk_comms, &point).unwrap();
+        let eval = polynomial.evaluate(&point);
+        assert!(TestPipKzg::verify(&v_srs, &com, &point, &eval, &proof).unwrap());
+
+        let too_large = UnivariatePolynomial::rand(7 * (srs.chunk_degree + 1), &mut rng);
+        assert!(TestPipKzg::commit(&srs, &too_large).is_err());
+    }
+}